@@ -3,8 +3,11 @@ use std::path::PathBuf;
 fn main() {
     println!("cargo:rerun-if-changed=build.rs");
 
-    #[cfg(not(feature = "ci-check"))]
+    #[cfg(not(any(feature = "ci-check", feature = "dynamic-loading")))]
     link_cuda();
+
+    #[cfg(feature = "dynamic-loading")]
+    dynamic_loading::generate_all();
 }
 
 #[allow(unused)]
@@ -63,3 +66,208 @@ fn libs(root: PathBuf) -> Vec<PathBuf> {
     .filter(|p| p.is_dir())
     .collect()
 }
+
+/// Rewrites the bindgen generated `extern "C"` blocks of each `sys` module into
+/// functions that look up their symbol in a library loaded at runtime.
+///
+/// Everything else in the bindings (types, constants, layout tests) is copied
+/// over unchanged, so the generated module is a drop in replacement for `sys`.
+#[cfg(feature = "dynamic-loading")]
+mod dynamic_loading {
+    use std::{collections::BTreeSet, fs, path::Path};
+
+    pub fn generate_all() {
+        #[cfg(feature = "driver")]
+        generate("driver", &["sys.rs"], &["result.rs", "safe"]);
+        #[cfg(feature = "nvrtc")]
+        generate("nvrtc", &["sys.rs", "lto.rs"], &["result.rs", "safe.rs"]);
+        #[cfg(feature = "curand")]
+        generate("curand", &["sys.rs"], &["result.rs", "safe.rs"]);
+        #[cfg(feature = "cublas")]
+        generate("cublas", &["sys.rs", "half.rs"], &["result.rs", "safe.rs"]);
+    }
+
+    /// Symbols of newer cuda versions, which are only needed by the features that use them
+    /// (e.g. `cuda-12`), so they must not stop older libraries from loading.
+    const OPTIONAL_SYMBOLS: &[&str] = &["nvrtcGetLTOIRSize", "nvrtcGetLTOIR"];
+
+    /// Generates `$OUT_DIR/{module}_{binding}` for each of `bindings`, and
+    /// `$OUT_DIR/{module}_required.rs` containing the symbols referenced by `users`.
+    fn generate(module: &str, bindings: &[&str], users: &[&str]) {
+        let src = Path::new("src").join(module);
+        let out_dir = std::env::var("OUT_DIR").unwrap();
+        let out_dir = Path::new(&out_dir);
+
+        let mut symbols = Vec::new();
+        for &binding in bindings {
+            let path = src.join(binding);
+            println!("cargo:rerun-if-changed={}", path.display());
+            let bindings = fs::read_to_string(&path).unwrap();
+            let library = if binding == "sys.rs" {
+                "LIBRARY"
+            } else {
+                "sys::LIBRARY"
+            };
+            let (generated, names) = rewrite_extern_blocks(&bindings, library);
+            symbols.extend(names);
+            fs::write(out_dir.join(format!("{module}_{binding}")), generated).unwrap();
+        }
+
+        let mut sources = String::new();
+        for &user in users {
+            read_sources(&src.join(user), &mut sources);
+        }
+        let required: BTreeSet<&str> = symbols
+            .iter()
+            .map(String::as_str)
+            .filter(|name| !OPTIONAL_SYMBOLS.contains(name))
+            .filter(|name| sources.contains(&format!("{name}(")))
+            .collect();
+        let mut generated = String::from("pub const REQUIRED_SYMBOLS: &[&str] = &[\n");
+        for name in required {
+            generated.push_str(&format!("    \"{name}\",\n"));
+        }
+        generated.push_str("];\n");
+        fs::write(out_dir.join(format!("{module}_required.rs")), generated).unwrap();
+    }
+
+    fn read_sources(path: &Path, sources: &mut String) {
+        println!("cargo:rerun-if-changed={}", path.display());
+        if path.is_dir() {
+            for entry in fs::read_dir(path).unwrap() {
+                read_sources(&entry.unwrap().path(), sources);
+            }
+        } else {
+            sources.push_str(&fs::read_to_string(path).unwrap());
+        }
+    }
+
+    /// Returns the rewritten bindings and the names of all the functions that were rewritten.
+    fn rewrite_extern_blocks(bindings: &str, library: &str) -> (String, Vec<String>) {
+        const START: &str = "extern \"C\" {\n";
+        const END: &str = "\n}\n";
+
+        let mut generated = String::new();
+        let mut names = Vec::new();
+        let mut rest = bindings;
+        while let Some(start) = find_at_line_start(rest, START) {
+            generated.push_str(&rest[..start]);
+            let body_start = start + START.len();
+            let body_len = rest[body_start..].find(END).unwrap();
+            let body = &rest[body_start..body_start + body_len];
+            for item in split_top_level(body, ';') {
+                let (name, function) = rewrite_function(item, library);
+                names.push(name);
+                generated.push_str(&function);
+            }
+            rest = &rest[body_start + body_len + END.len()..];
+        }
+        generated.push_str(rest);
+
+        // inner attributes & docs are not allowed in `include!`d files
+        let generated = generated
+            .lines()
+            .filter(|line| !line.starts_with("//!") && !line.starts_with("#!"))
+            .map(|line| format!("{line}\n"))
+            .collect();
+        (generated, names)
+    }
+
+    fn find_at_line_start(haystack: &str, needle: &str) -> Option<usize> {
+        let mut offset = 0;
+        while let Some(i) = haystack[offset..].find(needle) {
+            let i = offset + i;
+            if i == 0 || haystack.as_bytes()[i - 1] == b'\n' {
+                return Some(i);
+            }
+            offset = i + needle.len();
+        }
+        None
+    }
+
+    /// Rewrites `pub fn name(arg: Arg, ...) -> Ret` into a function that calls
+    /// the symbol `name` of `library`.
+    fn rewrite_function(item: &str, library: &str) -> (String, String) {
+        let item = item.trim().strip_prefix("pub fn ").unwrap();
+        let open = item.find('(').unwrap();
+        let name = item[..open].trim().to_string();
+        let close = open + matching_paren(&item[open..]);
+        let args = &item[open + 1..close];
+        let ret = item[close + 1..].trim();
+        let ret = if ret.is_empty() {
+            String::new()
+        } else {
+            format!(" {ret}")
+        };
+
+        let mut arg_names = Vec::new();
+        let mut arg_types = Vec::new();
+        for arg in split_top_level(args, ',') {
+            let (arg_name, arg_type) = arg.split_once(": ").unwrap();
+            arg_names.push(arg_name.trim());
+            arg_types.push(arg_type.trim());
+        }
+        let params: Vec<String> = arg_names
+            .iter()
+            .zip(arg_types.iter())
+            .map(|(n, t)| format!("{n}: {t}"))
+            .collect();
+
+        let function = format!(
+            "pub unsafe fn {name}({params}){ret} {{
+    static FUNCTION: ::std::sync::OnceLock<unsafe extern \"C\" fn({types}){ret}> =
+        ::std::sync::OnceLock::new();
+    let function = FUNCTION.get_or_init(|| {library}.get(\"{name}\"));
+    function({names})
+}}
+",
+            params = params.join(", "),
+            types = arg_types.join(", "),
+            names = arg_names.join(", "),
+        );
+        (name, function)
+    }
+
+    /// Returns the index of the paren that closes the one at the start of `s`.
+    fn matching_paren(s: &str) -> usize {
+        let mut depth = 0;
+        for (i, c) in s.char_indices() {
+            match c {
+                '(' => depth += 1,
+                ')' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return i;
+                    }
+                }
+                _ => {}
+            }
+        }
+        panic!("Unbalanced parens in {s}")
+    }
+
+    /// Splits `s` on `sep`, ignoring any `sep` nested in brackets.
+    fn split_top_level(s: &str, sep: char) -> Vec<&str> {
+        let mut parts = Vec::new();
+        let mut depth = 0i32;
+        let mut start = 0;
+        let mut prev = ' ';
+        for (i, c) in s.char_indices() {
+            match c {
+                '(' | '[' | '<' => depth += 1,
+                ')' | ']' => depth -= 1,
+                // the `>` of `->` does not close anything
+                '>' if prev != '-' => depth -= 1,
+                c if c == sep && depth == 0 => {
+                    parts.push(&s[start..i]);
+                    start = i + 1;
+                }
+                _ => {}
+            }
+            prev = c;
+        }
+        parts.push(&s[start..]);
+        parts.retain(|part| !part.trim().is_empty());
+        parts
+    }
+}
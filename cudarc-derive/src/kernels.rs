@@ -0,0 +1,330 @@
+//! Parsing of `extern "C" __global__` kernel signatures out of cuda source.
+
+/// A kernel declared in cuda source.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Kernel {
+    pub name: String,
+    /// The signature as written in the source, for docs
+    pub signature: String,
+    pub params: Vec<Param>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Param {
+    pub name: String,
+    pub kind: ParamKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum ParamKind {
+    /// Passed by value, e.g. `size_t n`
+    Value(String),
+    /// A pointer to memory the kernel only reads, e.g. `const float *inp`
+    ConstPtr(String),
+    /// A pointer to memory the kernel may write, e.g. `float *out`
+    MutPtr(String),
+}
+
+/// Finds every `extern "C" __global__` function in `src`.
+///
+/// Templated kernels are skipped since they can't be looked up by name. Any other
+/// kernel that isn't `extern "C"` is an error for the same reason.
+pub(crate) fn parse_kernels(src: &str) -> Result<Vec<Kernel>, String> {
+    let src = strip_comments(src);
+    let mut kernels = Vec::new();
+    let mut offset = 0;
+    while let Some(i) = src[offset..].find("__global__") {
+        let start = offset + i;
+        offset = start + "__global__".len();
+
+        // everything between the end of the previous statement (or start of a block)
+        // and `__global__`
+        let prefix_start = src[..start].rfind([';', '{', '}']).map_or(0, |i| i + 1);
+        let prefix = &src[prefix_start..start];
+        if prefix.contains("template") {
+            continue;
+        }
+
+        let rest = src[offset..].trim_start();
+        let rest = rest
+            .strip_prefix("void")
+            .ok_or("kernels must return `void`")?
+            .trim_start();
+        let rest = match rest.strip_prefix("__launch_bounds__") {
+            Some(bounds) => {
+                let bounds = bounds.trim_start();
+                let close = matching_paren(bounds).ok_or("unbalanced parentheses")?;
+                &bounds[close + 1..]
+            }
+            None => rest,
+        };
+        let open =
+            src.len() - rest.len() + rest.find('(').ok_or("expected `(` after the kernel name")?;
+        let name = src[src.len() - rest.len()..open].trim().to_string();
+        if name.is_empty() {
+            return Err("expected a kernel name after `__global__ void`".into());
+        }
+        if !prefix.contains("extern \"C\"") && !in_extern_c_block(&src[..start]) {
+            return Err(format!(
+                "kernel `{name}` must be declared `extern \"C\"` so it can be found by name"
+            ));
+        }
+
+        let close = open + matching_paren(&src[open..]).ok_or("unbalanced parentheses")?;
+        let params = src[open + 1..close]
+            .split(',')
+            .map(str::trim)
+            .filter(|p| !p.is_empty() && *p != "void")
+            .enumerate()
+            .map(|(i, p)| parse_param(p, i).map_err(|e| format!("in kernel `{name}`: {e}")))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let signature = src[prefix_start..close + 1]
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ");
+        kernels.push(Kernel {
+            name,
+            signature,
+            params,
+        });
+        offset = close;
+    }
+    Ok(kernels)
+}
+
+fn parse_param(param: &str, index: usize) -> Result<Param, String> {
+    let num_ptrs = param.matches('*').count();
+    if num_ptrs > 1 {
+        return Err(format!("`{param}`: pointers to pointers are not supported"));
+    }
+    if param.contains('&') || param.contains('[') || param.contains('<') {
+        return Err(format!("`{param}`: unsupported parameter type"));
+    }
+
+    // `const` only applies to the pointee if it comes before the `*`
+    let (before_ptr, after_ptr) = param.split_once('*').unwrap_or((param, ""));
+    let is_const = before_ptr.split_whitespace().any(|w| w == "const");
+
+    let qualifiers = ["const", "volatile", "struct", "__restrict__", "__restrict"];
+    let mut words: Vec<&str> = before_ptr
+        .split_whitespace()
+        .chain(after_ptr.split_whitespace())
+        .filter(|w| !qualifiers.contains(w))
+        .collect();
+
+    let name = if words.len() > 1 && !is_type_word(words[words.len() - 1]) {
+        words.pop().unwrap().to_string()
+    } else {
+        format!("arg{index}")
+    };
+    let c_type = words.join(" ");
+    let rust_type = rust_type(&c_type).ok_or_else(|| format!("`{param}`: unknown type"))?;
+
+    let kind = match (num_ptrs, is_const) {
+        (0, _) => ParamKind::Value(rust_type),
+        (_, true) => ParamKind::ConstPtr(rust_type),
+        (_, false) => ParamKind::MutPtr(rust_type),
+    };
+    Ok(Param { name, kind })
+}
+
+fn is_type_word(word: &str) -> bool {
+    matches!(
+        word,
+        "unsigned" | "signed" | "int" | "long" | "short" | "char" | "float" | "double" | "bool"
+    )
+}
+
+/// Maps a cuda type to the rust type with the same layout. Any other single identifier
+/// is assumed to be a struct with a rust equivalent of the same name.
+fn rust_type(c_type: &str) -> Option<String> {
+    let ty = match c_type {
+        "bool" => "bool",
+        // whether `char` is signed depends on the platform
+        "char" => "::core::ffi::c_char",
+        "signed char" | "int8_t" => "i8",
+        "unsigned char" | "uint8_t" => "u8",
+        "short" | "short int" | "signed short" | "int16_t" => "i16",
+        "unsigned short" | "unsigned short int" | "uint16_t" => "u16",
+        "int" | "signed" | "signed int" | "int32_t" => "i32",
+        "unsigned" | "unsigned int" | "uint32_t" => "u32",
+        // `long` is 32 bits on windows & 64 bits elsewhere, for both the host and device
+        "long" | "long int" | "signed long" => "::core::ffi::c_long",
+        "unsigned long" | "unsigned long int" => "::core::ffi::c_ulong",
+        "long long" | "long long int" | "int64_t" => "i64",
+        "unsigned long long" | "unsigned long long int" | "uint64_t" => "u64",
+        "size_t" => "usize",
+        "ptrdiff_t" => "isize",
+        "float" => "f32",
+        "double" => "f64",
+        "__half" | "half" => "half::f16",
+        "__nv_bfloat16" | "nv_bfloat16" => "half::bf16",
+        "void" => return None,
+        other => {
+            let is_ident = other
+                .chars()
+                .next()
+                .is_some_and(|c| c.is_alphabetic() || c == '_')
+                && other.chars().all(|c| c.is_alphanumeric() || c == '_');
+            return is_ident.then(|| other.to_string());
+        }
+    };
+    Some(ty.to_string())
+}
+
+/// Returns the index of the paren that closes the one at the start of `s`.
+fn matching_paren(s: &str) -> Option<usize> {
+    let mut depth = 0;
+    for (i, c) in s.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Whether the end of `src` is inside an `extern "C" { ... }` block.
+fn in_extern_c_block(src: &str) -> bool {
+    // whether each open brace started an `extern "C"` block
+    let mut blocks: Vec<bool> = Vec::new();
+    let mut statement_start = 0;
+    for (i, c) in src.char_indices() {
+        match c {
+            '{' => {
+                let statement = src[statement_start..i].trim_end();
+                blocks.push(statement.ends_with("extern \"C\""));
+                statement_start = i + 1;
+            }
+            '}' => {
+                blocks.pop();
+                statement_start = i + 1;
+            }
+            ';' => statement_start = i + 1,
+            _ => {}
+        }
+    }
+    blocks.contains(&true)
+}
+
+fn strip_comments(src: &str) -> String {
+    let mut out = String::with_capacity(src.len());
+    let mut rest = src;
+    while !rest.is_empty() {
+        if let Some(r) = rest.strip_prefix("//") {
+            rest = r.find('\n').map_or("", |i| &r[i..]);
+        } else if let Some(r) = rest.strip_prefix("/*") {
+            rest = r.find("*/").map_or("", |i| &r[i + 2..]);
+            out.push(' ');
+        } else {
+            let c = rest.chars().next().unwrap();
+            out.push(c);
+            rest = &rest[c.len_utf8()..];
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_sin_kernel() {
+        let src = "
+extern \"C\" __global__ void sin_kernel(float *out, const float *inp, const size_t numel) {
+    unsigned int i = blockIdx.x * blockDim.x + threadIdx.x;
+    if (i < numel) {
+        out[i] = sin(inp[i]);
+    }
+}";
+        let kernels = parse_kernels(src).unwrap();
+        assert_eq!(kernels.len(), 1);
+        assert_eq!(kernels[0].name, "sin_kernel");
+        assert_eq!(
+            kernels[0].params,
+            [
+                Param {
+                    name: "out".into(),
+                    kind: ParamKind::MutPtr("f32".into())
+                },
+                Param {
+                    name: "inp".into(),
+                    kind: ParamKind::ConstPtr("f32".into())
+                },
+                Param {
+                    name: "numel".into(),
+                    kind: ParamKind::Value("usize".into())
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_qualifiers() {
+        let src = "
+// __global__ void commented_out(int x) {}
+template<typename T> __global__ void generic(T *x) {}
+extern \"C\" __global__ void __launch_bounds__(256) k(
+    double * __restrict__ a,
+    const unsigned long long *b,
+    int const *c,
+    float *const d,
+    MyStruct s,
+    long l,
+    unsigned long ul
+) {}";
+        let kernels = parse_kernels(src).unwrap();
+        assert_eq!(kernels.len(), 1);
+        assert_eq!(kernels[0].name, "k");
+        let kinds: Vec<_> = kernels[0].params.iter().map(|p| p.kind.clone()).collect();
+        assert_eq!(
+            kinds,
+            [
+                ParamKind::MutPtr("f64".into()),
+                ParamKind::ConstPtr("u64".into()),
+                ParamKind::ConstPtr("i32".into()),
+                ParamKind::MutPtr("f32".into()),
+                ParamKind::Value("MyStruct".into()),
+                ParamKind::Value("::core::ffi::c_long".into()),
+                ParamKind::Value("::core::ffi::c_ulong".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_extern_c_block() {
+        let src = "
+extern \"C\" {
+__global__ void a(float *out) { if (true) { *out = 1.0f; } }
+__device__ float helper(float x) { return x; }
+__global__ void b(char c, long l);
+}
+";
+        let kernels = parse_kernels(src).unwrap();
+        let names: Vec<_> = kernels.iter().map(|k| k.name.as_str()).collect();
+        assert_eq!(names, ["a", "b"]);
+        assert_eq!(
+            kernels[1].params[0].kind,
+            ParamKind::Value("::core::ffi::c_char".into())
+        );
+        assert_eq!(kernels[1].signature, "__global__ void b(char c, long l)");
+
+        let src = "extern \"C\" { } __global__ void c(float *out) {}";
+        assert!(parse_kernels(src).is_err());
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(parse_kernels("__global__ void k(float *x) {}").is_err());
+        assert!(parse_kernels("extern \"C\" __global__ void k(float **x) {}").is_err());
+        assert!(parse_kernels("extern \"C\" __global__ void k(void *x) {}").is_err());
+    }
+}
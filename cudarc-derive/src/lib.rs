@@ -0,0 +1,414 @@
+//! Procedural macros for [cudarc](https://docs.rs/cudarc). Use them through cudarc's
+//! `derive` feature, which re-exports them from `cudarc::driver`.
+//!
+//! `kernels!` generates typed handles for the kernels in cuda source.
+//!
+//! The `DeviceRepr` and `ValidAsZeroBits` derives:
+//! 1. Only accept structs that are `#[repr(C)]` or `#[repr(transparent)]`, since
+//!    the rust layout of any other struct can differ from the one cuda expects.
+//!    `DeviceRepr` also rejects `#[repr(packed)]`, as cuda doesn't pack structs.
+//! 2. Require every field to implement the derived trait.
+
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::{quote, quote_spanned};
+use syn::{
+    parse::{Parse, ParseStream},
+    parse_macro_input, parse_quote,
+    spanned::Spanned,
+    Data, DeriveInput, Error, Fields, Generics, LitStr, Token, Type,
+};
+
+mod kernels;
+
+use kernels::{Kernel, ParamKind};
+
+/// Generates a typed handle for every `extern "C" __global__` kernel in cuda source, so
+/// that passing the wrong number, order, types or mutability of arguments is a compile error.
+///
+/// The source is either a string literal, or a file relative to the crate root:
+/// ```ignore
+/// kernels!("extern \"C\" __global__ void sin_kernel(float *out, const float *inp, size_t n) { ... }");
+/// kernels!(file = "kernels/sin.cu");
+/// ```
+///
+/// Each kernel `sin_kernel` becomes a struct `SinKernel` wrapping a `CudaFunction`, with:
+/// - `SinKernel::NAME`, the name to pass to `CudaDevice::load_ptx()`
+/// - `SinKernel::get(&dev, module_name)`, which looks up the loaded function
+/// - `unsafe fn launch(&self, cfg, out: &mut impl DevicePtrMut<f32>, inp: &impl DevicePtr<f32>, n: usize)`
+///   and `launch_on_stream` with the same arguments
+///
+/// `T *` parameters take a mutable borrow of device memory, `const T *` a shared one, and values
+/// are passed by value. Scalar types map to the rust type of the same size (e.g. `size_t` is
+/// `usize`, `char` & `long` are `c_char` & `c_long`, `__half` is `half::f16`); any other type
+/// name is used as is, so a kernel taking `MyStruct` needs a rust `MyStruct` that is
+/// `DeviceRepr` in scope.
+#[proc_macro]
+pub fn kernels(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as KernelsInput);
+    expand_kernels(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+/// Implements `DeviceRepr` and `DeviceLayout` for a `#[repr(C)]` or `#[repr(transparent)]` struct.
+///
+/// ```ignore
+/// #[derive(DeviceRepr)]
+/// #[repr(C)]
+/// struct Particle {
+///     x: f32,
+///     y: f32,
+///     mass: f64,
+/// }
+/// ```
+#[proc_macro_derive(DeviceRepr)]
+pub fn derive_device_repr(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_device_repr(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+/// Implements `ValidAsZeroBits` for a `#[repr(C)]` or `#[repr(transparent)]` struct.
+///
+/// ```ignore
+/// #[derive(DeviceRepr, ValidAsZeroBits)]
+/// #[repr(C)]
+/// struct Particle {
+///     x: f32,
+///     y: f32,
+///     mass: f64,
+/// }
+/// ```
+#[proc_macro_derive(ValidAsZeroBits)]
+pub fn derive_valid_as_zero_bits(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_valid_as_zero_bits(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+enum KernelsInput {
+    Src(LitStr),
+    File(LitStr),
+}
+
+impl Parse for KernelsInput {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        if input.peek(LitStr) {
+            return Ok(Self::Src(input.parse()?));
+        }
+        let key: syn::Ident = input.parse()?;
+        if key != "file" {
+            return Err(Error::new(
+                key.span(),
+                "expected a string literal or `file = \"...\"`",
+            ));
+        }
+        input.parse::<Token![=]>()?;
+        Ok(Self::File(input.parse()?))
+    }
+}
+
+fn expand_kernels(input: KernelsInput) -> syn::Result<TokenStream2> {
+    let (src, span, track_file) = match input {
+        KernelsInput::Src(lit) => (lit.value(), lit.span(), None),
+        KernelsInput::File(lit) => {
+            let root = std::env::var("CARGO_MANIFEST_DIR").unwrap_or_default();
+            let path = std::path::Path::new(&root).join(lit.value());
+            let src = std::fs::read_to_string(&path).map_err(|e| {
+                Error::new(
+                    lit.span(),
+                    format!("failed to read {}: {e}", path.display()),
+                )
+            })?;
+            let path = path.display().to_string();
+            (
+                src,
+                lit.span(),
+                Some(quote!(
+                    const _: &str = include_str!(#path);
+                )),
+            )
+        }
+    };
+
+    let kernels = kernels::parse_kernels(&src).map_err(|e| Error::new(span, e))?;
+    if kernels.is_empty() {
+        return Err(Error::new(
+            span,
+            "no `extern \"C\" __global__` kernels found",
+        ));
+    }
+    let handles = kernels.iter().map(kernel_handle);
+    Ok(quote! {
+        #track_file
+        #(#handles)*
+    })
+}
+
+fn kernel_handle(kernel: &Kernel) -> TokenStream2 {
+    let struct_name = syn::Ident::new(&camel_case(&kernel.name), Span::call_site());
+    let kernel_name = &kernel.name;
+    let doc = format!("Typed handle for the kernel `{}`", kernel.signature);
+
+    let mut params = Vec::new();
+    let mut args = Vec::new();
+    for param in kernel.params.iter() {
+        let name = syn::parse_str::<syn::Ident>(&param.name)
+            .unwrap_or_else(|_| quote::format_ident!("{}_", param.name));
+        match &param.kind {
+            ParamKind::Value(ty) => {
+                let ty: Type = syn::parse_str(ty).unwrap();
+                params.push(quote!(#name: #ty));
+                args.push(quote!(#name));
+            }
+            ParamKind::ConstPtr(ty) => {
+                let ty: Type = syn::parse_str(ty).unwrap();
+                params.push(quote!(#name: &impl ::cudarc::driver::DevicePtr<#ty>));
+                args.push(quote!(*::cudarc::driver::DevicePtr::device_ptr(#name)));
+            }
+            ParamKind::MutPtr(ty) => {
+                let ty: Type = syn::parse_str(ty).unwrap();
+                params.push(quote!(#name: &mut impl ::cudarc::driver::DevicePtrMut<#ty>));
+                args.push(quote!(*::cudarc::driver::DevicePtrMut::device_ptr_mut(#name)));
+            }
+        }
+    }
+
+    quote! {
+        #[doc = #doc]
+        #[derive(Debug, Clone)]
+        pub struct #struct_name(pub ::cudarc::driver::CudaFunction);
+
+        #[allow(non_snake_case, clippy::too_many_arguments)]
+        impl #struct_name {
+            /// The name of the kernel, to pass to `CudaDevice::load_ptx()`
+            pub const NAME: &'static str = #kernel_name;
+
+            /// Retrieves the kernel from `module_name`, which must already be loaded on `device`.
+            pub fn get(
+                device: &::std::sync::Arc<::cudarc::driver::CudaDevice>,
+                module_name: &str,
+            ) -> ::core::option::Option<Self> {
+                device.get_func(module_name, Self::NAME).map(Self)
+            }
+
+            /// Launches the kernel on the device's default stream.
+            ///
+            /// # Safety
+            /// The arguments are checked against the kernel's signature, but the kernel
+            /// itself may still access memory out of bounds. See `LaunchAsync::launch()`.
+            pub unsafe fn launch(
+                &self,
+                cfg: ::cudarc::driver::LaunchConfig,
+                #(#params),*
+            ) -> ::core::result::Result<(), ::cudarc::driver::LaunchError> {
+                ::cudarc::driver::LaunchAsync::launch(self.0.clone(), cfg, (#(#args,)*))
+            }
+
+            /// Launches the kernel on `stream`.
+            ///
+            /// # Safety
+            /// See `LaunchAsync::launch_on_stream()`.
+            pub unsafe fn launch_on_stream(
+                &self,
+                stream: &::cudarc::driver::CudaStream,
+                cfg: ::cudarc::driver::LaunchConfig,
+                #(#params),*
+            ) -> ::core::result::Result<(), ::cudarc::driver::LaunchError> {
+                ::cudarc::driver::LaunchAsync::launch_on_stream(
+                    self.0.clone(),
+                    stream,
+                    cfg,
+                    (#(#args,)*),
+                )
+            }
+        }
+    }
+}
+
+/// `sin_kernel` -> `SinKernel`
+fn camel_case(name: &str) -> String {
+    name.split('_')
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            let first = chars.next().unwrap().to_ascii_uppercase();
+            std::iter::once(first).chain(chars).collect::<String>()
+        })
+        .collect()
+}
+
+fn expand_device_repr(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let trait_path = quote!(::cudarc::driver::DeviceRepr);
+    let fields = struct_fields(&input, "DeviceRepr")?;
+    // cuda never packs structs, so the device would read the fields at different offsets
+    if let Some(packed) = repr_hints(&input)?
+        .into_iter()
+        .find(|hint| hint.is_ident("packed"))
+    {
+        return Err(Error::new(
+            packed.span(),
+            "DeviceRepr can't be derived for #[repr(packed)] structs, since cuda aligns every field",
+        ));
+    }
+    for field in fields.iter() {
+        if let Type::Reference(_) = &field.ty {
+            return Err(Error::new(
+                field.ty.span(),
+                "references can't be used as fields of a DeviceRepr struct, \
+                 since only their host address would be copied to the device",
+            ));
+        }
+    }
+
+    let name = &input.ident;
+    let generics = add_trait_bounds(&input.generics, &trait_path);
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let field_checks = assert_fields_impl(&generics, &fields, &trait_path);
+
+    let field_layouts = fields.iter().enumerate().map(|(i, field)| {
+        let member = match &field.ident {
+            Some(ident) => quote!(#ident),
+            None => {
+                let index = syn::Index::from(i);
+                quote!(#index)
+            }
+        };
+        let field_name = member.to_string();
+        let ty = &field.ty;
+        let ty_name = quote!(#ty).to_string();
+        quote! {
+            ::cudarc::driver::FieldLayout {
+                name: #field_name,
+                ty: #ty_name,
+                offset: ::core::mem::offset_of!(Self, #member),
+                size: ::core::mem::size_of::<#ty>(),
+                align: ::core::mem::align_of::<#ty>(),
+            }
+        }
+    });
+    let struct_name = name.to_string();
+
+    Ok(quote! {
+        unsafe impl #impl_generics #trait_path for #name #ty_generics #where_clause {}
+
+        impl #impl_generics ::cudarc::driver::DeviceLayout for #name #ty_generics #where_clause {
+            const LAYOUT: ::cudarc::driver::StructLayout = ::cudarc::driver::StructLayout {
+                name: #struct_name,
+                size: ::core::mem::size_of::<Self>(),
+                align: ::core::mem::align_of::<Self>(),
+                fields: &[#(#field_layouts),*],
+            };
+        }
+
+        #field_checks
+    })
+}
+
+fn expand_valid_as_zero_bits(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let trait_path = quote!(::cudarc::driver::ValidAsZeroBits);
+    let fields = struct_fields(&input, "ValidAsZeroBits")?;
+
+    let name = &input.ident;
+    let generics = add_trait_bounds(&input.generics, &trait_path);
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let field_checks = assert_fields_impl(&generics, &fields, &trait_path);
+
+    Ok(quote! {
+        unsafe impl #impl_generics #trait_path for #name #ty_generics #where_clause {}
+
+        #field_checks
+    })
+}
+
+/// Returns the fields of `input`, or an error if it is not a struct with a
+/// `#[repr(C)]` or `#[repr(transparent)]` attribute.
+fn struct_fields(input: &DeriveInput, trait_name: &str) -> syn::Result<Vec<syn::Field>> {
+    let fields = match &input.data {
+        Data::Struct(data) => &data.fields,
+        Data::Enum(data) => {
+            return Err(Error::new(
+                data.enum_token.span,
+                format!("{trait_name} can only be derived for structs"),
+            ))
+        }
+        Data::Union(data) => {
+            return Err(Error::new(
+                data.union_token.span,
+                format!("{trait_name} can only be derived for structs"),
+            ))
+        }
+    };
+
+    let has_repr = repr_hints(input)?
+        .iter()
+        .any(|hint| hint.is_ident("C") || hint.is_ident("transparent"));
+    if !has_repr {
+        return Err(Error::new(
+            Span::call_site(),
+            format!(
+                "{trait_name} can only be derived for structs with #[repr(C)] or #[repr(transparent)]"
+            ),
+        ));
+    }
+
+    Ok(match fields {
+        Fields::Named(fields) => fields.named.iter().cloned().collect(),
+        Fields::Unnamed(fields) => fields.unnamed.iter().cloned().collect(),
+        Fields::Unit => Vec::new(),
+    })
+}
+
+/// Returns the hints of every `#[repr(...)]` attribute on `input`, e.g. `C` and `align`
+/// for `#[repr(C, align(8))]`.
+fn repr_hints(input: &DeriveInput) -> syn::Result<Vec<syn::Path>> {
+    let mut hints = Vec::new();
+    for attr in input.attrs.iter().filter(|a| a.path().is_ident("repr")) {
+        attr.parse_nested_meta(|meta| {
+            // skip the arguments of e.g. `align(8)`
+            if meta.input.peek(syn::token::Paren) {
+                let content;
+                syn::parenthesized!(content in meta.input);
+                content.parse::<TokenStream2>()?;
+            }
+            hints.push(meta.path);
+            Ok(())
+        })?;
+    }
+    Ok(hints)
+}
+
+/// Adds `T: trait_path` to every type parameter.
+fn add_trait_bounds(generics: &Generics, trait_path: &TokenStream2) -> Generics {
+    let mut generics = generics.clone();
+    for param in generics.type_params_mut() {
+        param.bounds.push(parse_quote!(#trait_path));
+    }
+    generics
+}
+
+/// Generates a function that only compiles if every field implements `trait_path`,
+/// with the error pointing at the offending field.
+fn assert_fields_impl(
+    generics: &Generics,
+    fields: &[syn::Field],
+    trait_path: &TokenStream2,
+) -> TokenStream2 {
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+    let checks = fields.iter().map(|field| {
+        let ty = &field.ty;
+        quote_spanned! {ty.span()=> assert_impl::<#ty>();}
+    });
+    quote! {
+        const _: () = {
+            #[allow(unused)]
+            fn assert_fields #impl_generics () #where_clause {
+                fn assert_impl<T: #trait_path + ?Sized>() {}
+                #(#checks)*
+            }
+        };
+    }
+}
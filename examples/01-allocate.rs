@@ -1,6 +1,6 @@
-use cudarc::driver::{CudaDevice, CudaSlice, DriverError};
+use cudarc::driver::{CudaDevice, CudaSlice};
 
-fn main() -> Result<(), DriverError> {
+fn main() -> Result<(), Box<dyn std::error::Error>> {
     let dev = CudaDevice::new(0)?;
 
     // unsafe initialization of unset memory
@@ -1,6 +1,6 @@
-use cudarc::driver::{CudaDevice, CudaSlice, DriverError};
+use cudarc::driver::{CudaDevice, CudaSlice};
 
-fn main() -> Result<(), DriverError> {
+fn main() -> Result<(), Box<dyn std::error::Error>> {
     let dev = CudaDevice::new(0)?;
 
     let a: CudaSlice<f64> = dev.alloc_zeros::<f64>(10)?;
@@ -0,0 +1,6 @@
+fn main() {
+    // compiles kernels/sin.cu to $OUT_DIR/kernels/sin.ptx
+    if let Err(err) = cudarc::nvrtc::build::compile_kernels() {
+        panic!("{err}");
+    }
+}
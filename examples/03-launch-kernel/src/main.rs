@@ -1,13 +1,10 @@
-use cudarc::{
-    driver::{CudaDevice, DriverError, LaunchAsync, LaunchConfig},
-    nvrtc::Ptx,
-};
+use cudarc::driver::{CudaDevice, LaunchAsync, LaunchConfig};
 
-fn main() -> Result<(), DriverError> {
+fn main() -> Result<(), Box<dyn std::error::Error>> {
     let dev = CudaDevice::new(0)?;
 
-    // You can load a function from a pre-compiled PTX like so:
-    dev.load_ptx(Ptx::from_file("./examples/sin.ptx"), "sin", &["sin_kernel"])?;
+    // `build.rs` compiled `kernels/sin.cu` ahead of time, so it can be embedded by name
+    dev.load_ptx(cudarc::include_ptx!("sin"), "sin", &["sin_kernel"])?;
 
     // and then retrieve the function with `get_func`
     let f = dev.get_func("sin", "sin_kernel").unwrap();
@@ -1,11 +1,20 @@
 use cudarc::{
-    driver::{CudaDevice, DriverError, LaunchAsync, LaunchConfig},
-    nvrtc::Ptx,
+    driver::{CudaDevice, LaunchAsync, LaunchConfig},
+    nvrtc::compile_ptx,
 };
 
-fn main() -> Result<(), DriverError> {
+const SIN_KERNEL: &str = "
+extern \"C\" __global__ void sin_kernel(float *out, const float *inp, int numel) {
+    int i = blockIdx.x * blockDim.x + threadIdx.x;
+    if (i < numel) {
+        out[i] = sin(inp[i]);
+    }
+}";
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
     let dev = CudaDevice::new(0)?;
-    dev.load_ptx(Ptx::from_file("./examples/sin.ptx"), "sin", &["sin_kernel"])?;
+    let ptx = compile_ptx(SIN_KERNEL)?;
+    dev.load_ptx(ptx, "sin", &["sin_kernel"])?;
 
     let n = 3;
     let cfg = LaunchConfig::for_num_elems(n);
@@ -11,6 +11,9 @@ struct MyCoolRustStruct {
 }
 
 /// We have to implement this to send it to cuda!
+///
+/// With the `derive` feature, `#[derive(DeviceRepr)]` does this for you,
+/// and checks that the struct is `#[repr(C)]` and that all of its fields are [DeviceRepr].
 unsafe impl DeviceRepr for MyCoolRustStruct {}
 
 const PTX_SRC: &str = "
@@ -29,7 +32,7 @@ extern \"C\" __global__ void my_custom_kernel(MyCoolStruct thing) {
 }
 ";
 
-fn main() -> Result<(), DriverError> {
+fn main() -> Result<(), Box<dyn std::error::Error>> {
     let dev = CudaDevice::new(0)?;
 
     let ptx = compile_ptx(PTX_SRC).unwrap();
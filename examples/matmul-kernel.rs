@@ -1,4 +1,4 @@
-use cudarc::driver::{CudaDevice, DriverError, LaunchAsync, LaunchConfig};
+use cudarc::driver::{CudaDevice, LaunchAsync, LaunchConfig};
 use cudarc::nvrtc::compile_ptx;
 
 const PTX_SRC: &str = "
@@ -19,7 +19,7 @@ extern \"C\" __global__ void matmul(float* A, float* B, float* C, int N) {
 }
 ";
 
-fn main() -> Result<(), DriverError> {
+fn main() -> Result<(), Box<dyn std::error::Error>> {
     let start = std::time::Instant::now();
 
     let ptx = compile_ptx(PTX_SRC).unwrap();
@@ -1,11 +1,31 @@
 //! Wrappers around the [cublas API](https://docs.nvidia.com/cuda/cublas/index.html),
 //! in three levels. See crate documentation for description of each.
 
-#[cfg(feature = "f16")]
+#[cfg(all(feature = "f16", not(feature = "dynamic-loading")))]
 pub mod half;
+#[cfg(all(feature = "f16", feature = "dynamic-loading"))]
+#[allow(warnings)]
+pub mod half {
+    include!(concat!(env!("OUT_DIR"), "/cublas_half.rs"));
+}
 pub mod result;
 pub mod safe;
+#[cfg(not(feature = "dynamic-loading"))]
 #[allow(warnings)]
 pub mod sys;
+#[cfg(feature = "dynamic-loading")]
+#[allow(warnings)]
+pub mod sys {
+    include!(concat!(env!("OUT_DIR"), "/cublas_sys.rs"));
+    include!(concat!(env!("OUT_DIR"), "/cublas_required.rs"));
+
+    #[cfg(not(windows))]
+    const FILE_NAMES: &[&str] = &["libcublas.so", "libcublas.so.12", "libcublas.so.11"];
+    #[cfg(windows)]
+    const FILE_NAMES: &[&str] = &["cublas64_12.dll", "cublas64_11.dll"];
+
+    pub static LIBRARY: crate::dynamic_loading::DynamicLibrary =
+        crate::dynamic_loading::DynamicLibrary::new("cublas", FILE_NAMES, REQUIRED_SYMBOLS);
+}
 
 pub use safe::*;
@@ -2,8 +2,12 @@
 #![allow(clippy::too_many_arguments)]
 
 use super::{result, result::CublasError, sys};
-use crate::driver::{CudaDevice, CudaStream, DevicePtr, DevicePtrMut};
+use crate::driver::{
+    safe::error::defer_error, CudaDevice, CudaStream, CudaView, CudaViewMut, DevicePtr,
+    DevicePtrMut, StridedView, StridedViewMut,
+};
 use core::ffi::{c_int, c_longlong};
+use core::marker::PhantomData;
 use std::sync::Arc;
 
 /// Wrapper around [sys::cublasHandle_t]
@@ -25,7 +29,9 @@ unsafe impl Sync for CudaBlas {}
 
 impl CudaBlas {
     /// Creates a new cublas handle and sets the stream to the `device`'s stream.
-    pub fn new(device: Arc<CudaDevice>) -> Result<Self, CublasError> {
+    pub fn new(device: Arc<CudaDevice>) -> Result<Self, CublasInitError> {
+        #[cfg(feature = "dynamic-loading")]
+        sys::LIBRARY.load().map_err(CublasInitError::LoadError)?;
         let handle = result::create_handle()?;
         let blas = Self { handle, device };
         unsafe { result::set_stream(handle, blas.device.stream as *mut _) }?;
@@ -46,11 +52,38 @@ impl CudaBlas {
     }
 }
 
+/// An error from [CudaBlas::new()].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CublasInitError {
+    /// The cublas library could not be loaded
+    #[cfg(feature = "dynamic-loading")]
+    LoadError(crate::dynamic_loading::LoadError),
+    Cublas(CublasError),
+}
+
+impl From<CublasError> for CublasInitError {
+    fn from(value: CublasError) -> Self {
+        Self::Cublas(value)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::fmt::Display for CublasInitError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for CublasInitError {}
+
 impl Drop for CudaBlas {
     fn drop(&mut self) {
         let handle = std::mem::replace(&mut self.handle, std::ptr::null_mut());
         if !handle.is_null() {
-            unsafe { result::destroy_handle(handle) }.unwrap();
+            if let Err(e) = unsafe { result::destroy_handle(handle) } {
+                defer_error(Some(&self.device), e);
+            }
         }
     }
 }
@@ -188,6 +221,83 @@ pub trait Gemm<T> {
         b: &B,
         c: &mut C,
     ) -> Result<(), CublasError>;
+
+    /// Matrix multiplication of 2d views, `c = alpha * a @ b + beta * c`. Each of them can be
+    /// a sub-matrix or a transpose of a row or column major matrix, see
+    /// [StridedView::matrix()] for which views work.
+    ///
+    /// # Panics
+    /// If a view isn't a matrix cuBLAS can use, the shapes don't match, or a dimension
+    /// doesn't fit in a `c_int`.
+    fn gemm_views(
+        &self,
+        alpha: T,
+        a: &StridedView<T>,
+        b: &StridedView<T>,
+        beta: T,
+        c: &mut StridedViewMut<T>,
+    ) -> Result<(), CublasError> {
+        let a_mat = a.matrix().expect("a isn't a matrix cuBLAS can use");
+        let b_mat = b.matrix().expect("b isn't a matrix cuBLAS can use");
+        let c_mat = c.matrix().expect("c isn't a matrix cuBLAS can use");
+        assert_eq!(a_mat.cols, b_mat.rows);
+        assert_eq!((c_mat.rows, c_mat.cols), (a_mat.rows, b_mat.cols));
+        let int = |x: usize| c_int::try_from(x).unwrap();
+        let op = |transposed: bool| match transposed {
+            true => sys::cublasOperation_t::CUBLAS_OP_T,
+            false => sys::cublasOperation_t::CUBLAS_OP_N,
+        };
+        // the memory each view can reach, which is what cuBLAS reads & writes
+        let a = CudaView {
+            ptr: a.as_raw_parts().0,
+            len: a.layout().extent(),
+            marker: PhantomData,
+        };
+        let b = CudaView {
+            ptr: b.as_raw_parts().0,
+            len: b.layout().extent(),
+            marker: PhantomData,
+        };
+        let c = &mut CudaViewMut {
+            ptr: c.as_raw_parts().0,
+            len: c.layout().extent(),
+            marker: PhantomData,
+        };
+        // Safety: the views can't reach past the memory they're made from, and
+        // `matrix()` only allows leading dimensions that keep the matrices inside of them
+        unsafe {
+            if !c_mat.transposed {
+                let cfg = GemmConfig {
+                    transa: op(a_mat.transposed),
+                    transb: op(b_mat.transposed),
+                    m: int(c_mat.rows),
+                    n: int(c_mat.cols),
+                    k: int(a_mat.cols),
+                    alpha,
+                    lda: int(a_mat.ld),
+                    ldb: int(b_mat.ld),
+                    beta,
+                    ldc: int(c_mat.ld),
+                };
+                self.gemm(cfg, &a, &b, c)
+            } else {
+                // c is stored as its transpose, so compute `c^T = b^T @ a^T` instead
+                let cfg = GemmConfig {
+                    transa: op(!b_mat.transposed),
+                    transb: op(!a_mat.transposed),
+                    m: int(c_mat.cols),
+                    n: int(c_mat.rows),
+                    k: int(a_mat.cols),
+                    alpha,
+                    lda: int(b_mat.ld),
+                    ldb: int(a_mat.ld),
+                    beta,
+                    ldc: int(c_mat.ld),
+                };
+                self.gemm(cfg, &b, &a, c)
+            }
+        }
+    }
 }
 
 #[cfg(feature = "f16")]
@@ -721,4 +831,55 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_sgemm_views() {
+        let dev = CudaDevice::new(0).unwrap();
+        let blas = CudaBlas::new(dev.clone()).unwrap();
+        const M: usize = 3;
+        const K: usize = 4;
+        const N: usize = 5;
+        // `a` is the bottom right of a row major 4x6 matrix
+        let a_host: Vec<f32> = (0..24).map(|x| x as f32 * 0.25 - 2.0).collect();
+        // `b` is the transpose of a row major 5x4 matrix
+        let b_host: Vec<f32> = (0..20).map(|x| 1.5 - x as f32 * 0.125).collect();
+        let mut a = [[0.0; K]; M];
+        let mut b = [[0.0; N]; K];
+        for m in 0..M {
+            for k in 0..K {
+                a[m][k] = a_host[(m + 1) * 6 + k + 2];
+            }
+        }
+        for k in 0..K {
+            for n in 0..N {
+                b[k][n] = b_host[n * 4 + k];
+            }
+        }
+        let mut c = [[0.0; N]; M];
+        gemm_truth(1.0, &a, &b, 0.0, &mut c);
+
+        let a_dev = dev.htod_sync_copy(&a_host).unwrap();
+        let b_dev = dev.htod_sync_copy(&b_host).unwrap();
+        // `c` is the middle columns of a row major 3x7 matrix
+        let mut c_dev = dev.alloc_zeros::<f32>(M * 7).unwrap();
+        let a_view = StridedView::row_major(&a_dev, &[4, 6])
+            .unwrap()
+            .slice(0, 1..)
+            .slice(1, 2..);
+        let b_view = StridedView::row_major(&b_dev, &[5, 4]).unwrap().t();
+        let mut c_view = StridedViewMut::row_major(&mut c_dev, &[3, 7])
+            .unwrap()
+            .slice(1, 1..6);
+        blas.gemm_views(1.0, &a_view, &b_view, 0.0, &mut c_view)
+            .unwrap();
+
+        let c_host = dev.sync_reclaim(c_dev).unwrap();
+        for m in 0..M {
+            assert_eq!(c_host[m * 7], 0.0);
+            assert_eq!(c_host[m * 7 + 6], 0.0);
+            for n in 0..N {
+                assert!((c_host[m * 7 + n + 1] - c[m][n]).abs() <= 1e-5);
+            }
+        }
+    }
 }
@@ -3,7 +3,22 @@
 
 pub mod result;
 pub mod safe;
+#[cfg(not(feature = "dynamic-loading"))]
 #[allow(warnings)]
 pub mod sys;
+#[cfg(feature = "dynamic-loading")]
+#[allow(warnings)]
+pub mod sys {
+    include!(concat!(env!("OUT_DIR"), "/curand_sys.rs"));
+    include!(concat!(env!("OUT_DIR"), "/curand_required.rs"));
+
+    #[cfg(not(windows))]
+    const FILE_NAMES: &[&str] = &["libcurand.so", "libcurand.so.10"];
+    #[cfg(windows)]
+    const FILE_NAMES: &[&str] = &["curand64_10.dll"];
+
+    pub static LIBRARY: crate::dynamic_loading::DynamicLibrary =
+        crate::dynamic_loading::DynamicLibrary::new("curand", FILE_NAMES, REQUIRED_SYMBOLS);
+}
 
 pub use safe::*;
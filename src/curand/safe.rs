@@ -1,7 +1,7 @@
 //! Safe abstractions around [crate::curand::result] with [CudaRng].
 
 use super::{result, sys};
-use crate::driver::{CudaDevice, CudaSlice, DeviceSlice};
+use crate::driver::{safe::error::defer_error, CudaDevice, CudaSlice, DeviceSlice};
 use std::sync::Arc;
 
 /// Host side RNG that can fill [CudaSlice] with random values.
@@ -30,9 +30,36 @@ pub struct CudaRng {
     pub(crate) device: Arc<CudaDevice>,
 }
 
+/// An error from [CudaRng::new()].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CurandInitError {
+    /// The curand library could not be loaded
+    #[cfg(feature = "dynamic-loading")]
+    LoadError(crate::dynamic_loading::LoadError),
+    Curand(result::CurandError),
+}
+
+impl From<result::CurandError> for CurandInitError {
+    fn from(value: result::CurandError) -> Self {
+        Self::Curand(value)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::fmt::Display for CurandInitError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for CurandInitError {}
+
 impl CudaRng {
     /// Constructs the RNG with the given `seed`. Requires the stream from [CudaDevice] to submit kernels.
-    pub fn new(seed: u64, device: Arc<CudaDevice>) -> Result<Self, result::CurandError> {
+    pub fn new(seed: u64, device: Arc<CudaDevice>) -> Result<Self, CurandInitError> {
+        #[cfg(feature = "dynamic-loading")]
+        sys::LIBRARY.load().map_err(CurandInitError::LoadError)?;
         let gen = result::create_generator()?;
         let mut rng = Self { gen, device };
         rng.set_seed(seed)?;
@@ -86,7 +113,9 @@ impl Drop for CudaRng {
     fn drop(&mut self) {
         let gen = std::mem::replace(&mut self.gen, std::ptr::null_mut());
         if !gen.is_null() {
-            unsafe { result::destroy_generator(gen) }.unwrap();
+            if let Err(e) = unsafe { result::destroy_generator(gen) } {
+                defer_error(Some(&self.device), e);
+            }
         }
     }
 }
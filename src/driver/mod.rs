@@ -3,7 +3,22 @@
 
 pub mod result;
 pub mod safe;
+#[cfg(not(feature = "dynamic-loading"))]
 #[allow(warnings)]
 pub mod sys;
+#[cfg(feature = "dynamic-loading")]
+#[allow(warnings)]
+pub mod sys {
+    include!(concat!(env!("OUT_DIR"), "/driver_sys.rs"));
+    include!(concat!(env!("OUT_DIR"), "/driver_required.rs"));
+
+    #[cfg(not(windows))]
+    const FILE_NAMES: &[&str] = &["libcuda.so.1", "libcuda.so"];
+    #[cfg(windows)]
+    const FILE_NAMES: &[&str] = &["nvcuda.dll"];
+
+    pub static LIBRARY: crate::dynamic_loading::DynamicLibrary =
+        crate::dynamic_loading::DynamicLibrary::new("cuda", FILE_NAMES, REQUIRED_SYMBOLS);
+}
 
 pub use safe::*;
@@ -12,7 +12,7 @@
 //! turns into [stream::create()], where [stream] is a module.
 
 use super::sys;
-use core::ffi::{c_uchar, c_uint, c_void, CStr};
+use core::ffi::{c_uchar, c_uint, c_ushort, c_void, CStr};
 use std::mem::{size_of, MaybeUninit};
 
 /// Wrapper around [sys::CUresult]. See
@@ -55,11 +55,12 @@ impl DriverError {
 
 impl std::fmt::Debug for DriverError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let err_str = self.error_string().unwrap();
-        f.debug_tuple("DriverError")
-            .field(&self.0)
-            .field(&err_str)
-            .finish()
+        let mut f = f.debug_tuple("DriverError");
+        f.field(&self.0);
+        if let Ok(err_str) = self.error_string() {
+            f.field(&err_str);
+        }
+        f.finish()
     }
 }
 
@@ -81,6 +82,18 @@ pub fn init() -> Result<(), DriverError> {
     unsafe { sys::cuInit(0).result() }
 }
 
+/// The (major, minor) version of cuda the driver supports, e.g. `(12, 2)`.
+///
+/// See [cuDriverGetVersion() docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__VERSION.html#group__CUDA__VERSION)
+pub fn version() -> Result<(i32, i32), DriverError> {
+    let mut version = MaybeUninit::uninit();
+    unsafe {
+        sys::cuDriverGetVersion(version.as_mut_ptr()).result()?;
+        let version = version.assume_init();
+        Ok((version / 1000, (version % 1000) / 10))
+    }
+}
+
 pub mod device {
     //! Device management functions (`cuDevice*`).
     //!
@@ -121,6 +134,21 @@ pub mod device {
         sys::cuDeviceTotalMem_v2(bytes.as_mut_ptr(), dev).result()?;
         Ok(bytes.assume_init())
     }
+
+    /// Returns information about the device.
+    ///
+    /// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__DEVICE.html#group__CUDA__DEVICE)
+    ///
+    /// # Safety
+    /// Must be a device returned from [get].
+    pub unsafe fn get_attribute(
+        dev: sys::CUdevice,
+        attrib: sys::CUdevice_attribute,
+    ) -> Result<c_int, DriverError> {
+        let mut value = MaybeUninit::uninit();
+        sys::cuDeviceGetAttribute(value.as_mut_ptr(), attrib, dev).result()?;
+        Ok(value.assume_init())
+    }
 }
 
 pub mod primary_ctx {
@@ -163,6 +191,7 @@ pub mod ctx {
     //! See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__CTX.html#group__CUDA__CTX)
 
     use super::{sys, DriverError};
+    use std::mem::MaybeUninit;
 
     /// Binds the specified CUDA context to the calling CPU thread.
     ///
@@ -176,6 +205,49 @@ pub mod ctx {
     pub unsafe fn set_current(ctx: sys::CUcontext) -> Result<(), DriverError> {
         sys::cuCtxSetCurrent(ctx).result()
     }
+
+    /// Returns the value of `limit` for the current context.
+    ///
+    /// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__CTX.html#group__CUDA__CTX_1g9f2d47d1745752aa16da7ed0d111b6a8)
+    pub fn get_limit(limit: sys::CUlimit) -> Result<usize, DriverError> {
+        let mut value = MaybeUninit::uninit();
+        unsafe {
+            sys::cuCtxGetLimit(value.as_mut_ptr(), limit).result()?;
+            Ok(value.assume_init())
+        }
+    }
+
+    /// Sets `limit` to `value` for the current context.
+    ///
+    /// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__CTX.html#group__CUDA__CTX_1g0651954dfb9788173e60a9af7201e65a)
+    pub fn set_limit(limit: sys::CUlimit, value: usize) -> Result<(), DriverError> {
+        unsafe { sys::cuCtxSetLimit(limit, value) }.result()
+    }
+
+    /// Returns the preferred cache configuration for the current context.
+    ///
+    /// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__CTX.html#group__CUDA__CTX_1g40b6b141698f76744dea6e39b9a25360)
+    pub fn get_cache_config() -> Result<sys::CUfunc_cache, DriverError> {
+        let mut config = MaybeUninit::uninit();
+        unsafe {
+            sys::cuCtxGetCacheConfig(config.as_mut_ptr()).result()?;
+            Ok(config.assume_init())
+        }
+    }
+
+    /// Sets the preferred cache configuration for the current context.
+    ///
+    /// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__CTX.html#group__CUDA__CTX_1g54699acf7e2ef27279d013ca2095f4a3)
+    pub fn set_cache_config(config: sys::CUfunc_cache) -> Result<(), DriverError> {
+        unsafe { sys::cuCtxSetCacheConfig(config) }.result()
+    }
+
+    /// Resets all persisting lines in cache to normal status.
+    ///
+    /// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__CTX.html#group__CUDA__CTX_1gb529532b5b1aef808295a6d1d18a0823)
+    pub fn reset_persisting_l2_cache() -> Result<(), DriverError> {
+        unsafe { sys::cuCtxResetPersistingL2Cache() }.result()
+    }
 }
 
 pub mod stream {
@@ -241,6 +313,22 @@ pub mod stream {
         sys::cuStreamSynchronize(stream).result()
     }
 
+    /// Returns `Ok(true)` if all work on `stream` has completed, and `Ok(false)` if some
+    /// is still running. Also returns any error from previously launched asynchronous work.
+    ///
+    /// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__STREAM.html#group__CUDA__STREAM)
+    ///
+    /// # Safety
+    ///
+    /// This should only be called with stream created by [create] and not already
+    /// destroyed. This follows default stream semantics, see relevant cuda docs.
+    pub unsafe fn query(stream: sys::CUstream) -> Result<bool, DriverError> {
+        match sys::cuStreamQuery(stream) {
+            sys::CUresult::CUDA_ERROR_NOT_READY => Ok(false),
+            status => status.result().map(|_| true),
+        }
+    }
+
     /// Destroys a stream.
     ///
     /// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__STREAM.html#group__CUDA__STREAM_1g244c8833de4596bcd31a06cdf21ee758)
@@ -297,6 +385,18 @@ pub unsafe fn free_async(dptr: sys::CUdeviceptr, stream: sys::CUstream) -> Resul
     sys::cuMemFreeAsync(dptr, stream).result()
 }
 
+/// Frees memory that was not allocated with stream ordered semantics,
+/// such as buffers mapped from [external_memory::get_mapped_buffer].
+///
+/// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__MEM.html#group__CUDA__MEM)
+///
+/// # Safety
+/// 1. The memory should not have been freed already (double free)
+/// 2. No work that uses the memory should be pending on any stream.
+pub unsafe fn free_sync(dptr: sys::CUdeviceptr) -> Result<(), DriverError> {
+    sys::cuMemFree_v2(dptr).result()
+}
+
 /// Sets device memory with stream ordered semantics.
 ///
 /// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__MEM.html#group__CUDA__MEM_1gaef08a7ccd61112f94e82f2b30d43627)
@@ -314,6 +414,42 @@ pub unsafe fn memset_d8_async(
     sys::cuMemsetD8Async(dptr, uc, num_bytes, stream).result()
 }
 
+/// Sets device memory to `num_elements` copies of the 16 bit value `us`, with stream
+/// ordered semantics. `dptr` must be 2 byte aligned.
+///
+/// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__MEM.html#group__CUDA__MEM)
+///
+/// # Safety
+/// 1. The resulting memory pattern may not be valid for `T`.
+/// 2. The device pointer should not have been freed already (double free)
+/// 3. The stream should be the stream the memory was allocated on.
+pub unsafe fn memset_d16_async(
+    dptr: sys::CUdeviceptr,
+    us: c_ushort,
+    num_elements: usize,
+    stream: sys::CUstream,
+) -> Result<(), DriverError> {
+    sys::cuMemsetD16Async(dptr, us, num_elements, stream).result()
+}
+
+/// Sets device memory to `num_elements` copies of the 32 bit value `ui`, with stream
+/// ordered semantics. `dptr` must be 4 byte aligned.
+///
+/// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__MEM.html#group__CUDA__MEM)
+///
+/// # Safety
+/// 1. The resulting memory pattern may not be valid for `T`.
+/// 2. The device pointer should not have been freed already (double free)
+/// 3. The stream should be the stream the memory was allocated on.
+pub unsafe fn memset_d32_async(
+    dptr: sys::CUdeviceptr,
+    ui: c_uint,
+    num_elements: usize,
+    stream: sys::CUstream,
+) -> Result<(), DriverError> {
+    sys::cuMemsetD32Async(dptr, ui, num_elements, stream).result()
+}
+
 /// Copies memory from Host to Device with stream ordered semantics.
 ///
 /// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__MEM.html#group__CUDA__MEM_1g4d32266788c440b0220b1a9ba5795169)
@@ -431,6 +567,18 @@ pub mod module {
         Ok(module.assume_init())
     }
 
+    /// Loads a fatbin, picking the cubin or ptx in it that best matches the current context's device.
+    ///
+    /// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__MODULE.html#group__CUDA__MODULE)
+    ///
+    /// # Safety
+    /// `fat_cubin` must point to a properly formed fatbin.
+    pub unsafe fn load_fat_binary(fat_cubin: *const c_void) -> Result<sys::CUmodule, DriverError> {
+        let mut module = MaybeUninit::uninit();
+        sys::cuModuleLoadFatBinary(module.as_mut_ptr(), fat_cubin).result()?;
+        Ok(module.assume_init())
+    }
+
     /// Returns a function handle from the given module.
     ///
     /// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__MODULE.html#group__CUDA__MODULE_1ga52be009b0d4045811b30c965e1cb2cf)
@@ -458,6 +606,291 @@ pub mod module {
     }
 }
 
+pub mod link {
+    //! JIT linking functions (`cuLink*`), e.g. of LTO-IR into a cubin.
+    //!
+    //! See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__MODULE.html#group__CUDA__MODULE)
+
+    use super::{sys, DriverError};
+    use core::ffi::{c_void, CStr};
+    use std::mem::MaybeUninit;
+
+    /// Creates a pending linker invocation with `options` set to `values`.
+    ///
+    /// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__MODULE.html#group__CUDA__MODULE)
+    ///
+    /// # Safety
+    /// `values` must be valid for each of `options`, see [sys::CUjit_option].
+    pub unsafe fn create(
+        options: &mut [sys::CUjit_option],
+        values: &mut [*mut c_void],
+    ) -> Result<sys::CUlinkState, DriverError> {
+        assert_eq!(options.len(), values.len());
+        let mut state = MaybeUninit::uninit();
+        sys::cuLinkCreate_v2(
+            options.len() as _,
+            options.as_mut_ptr(),
+            values.as_mut_ptr(),
+            state.as_mut_ptr(),
+        )
+        .result()?;
+        Ok(state.assume_init())
+    }
+
+    /// Adds `data` of kind `input_type` to the link. `name` is used in log messages.
+    ///
+    /// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__MODULE.html#group__CUDA__MODULE)
+    ///
+    /// # Safety
+    /// `state` must be created with [create()] and not destroyed yet.
+    pub unsafe fn add_data(
+        state: sys::CUlinkState,
+        input_type: sys::CUjitInputType,
+        data: &[u8],
+        name: &CStr,
+    ) -> Result<(), DriverError> {
+        sys::cuLinkAddData_v2(
+            state,
+            input_type,
+            data.as_ptr() as *mut _,
+            data.len(),
+            name.as_ptr(),
+            0,
+            core::ptr::null_mut(),
+            core::ptr::null_mut(),
+        )
+        .result()
+    }
+
+    /// Completes the link, returning a pointer to the linked cubin & its size in bytes.
+    ///
+    /// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__MODULE.html#group__CUDA__MODULE)
+    ///
+    /// # Safety
+    /// `state` must be created with [create()] and not destroyed yet. The cubin is owned by
+    /// `state`, so it must not be used after [destroy()].
+    pub unsafe fn complete(state: sys::CUlinkState) -> Result<(*mut c_void, usize), DriverError> {
+        let mut cubin = MaybeUninit::uninit();
+        let mut size = MaybeUninit::uninit();
+        sys::cuLinkComplete(state, cubin.as_mut_ptr(), size.as_mut_ptr()).result()?;
+        Ok((cubin.assume_init(), size.assume_init()))
+    }
+
+    /// Destroys a linker invocation, and the cubin it output.
+    ///
+    /// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__MODULE.html#group__CUDA__MODULE)
+    ///
+    /// # Safety
+    /// `state` must be created with [create()] and not destroyed yet.
+    pub unsafe fn destroy(state: sys::CUlinkState) -> Result<(), DriverError> {
+        sys::cuLinkDestroy(state).result()
+    }
+}
+
+pub mod function {
+    //! Function management functions (`cuFunc*`).
+    //!
+    //! See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__EXEC.html#group__CUDA__EXEC)
+
+    use super::{sys, DriverError};
+    use core::ffi::c_int;
+    use std::mem::MaybeUninit;
+
+    /// Returns information about a function.
+    ///
+    /// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__EXEC.html#group__CUDA__EXEC_1g5e92a1b0d8d1b82cb00dcfb2de15961b)
+    ///
+    /// # Safety
+    /// `f` must be a valid function handle from a module that was not unloaded.
+    pub unsafe fn get_attribute(
+        f: sys::CUfunction,
+        attribute: sys::CUfunction_attribute,
+    ) -> Result<c_int, DriverError> {
+        let mut value = MaybeUninit::uninit();
+        sys::cuFuncGetAttribute(value.as_mut_ptr(), attribute, f).result()?;
+        Ok(value.assume_init())
+    }
+
+    /// Sets information about a function.
+    ///
+    /// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__EXEC.html#group__CUDA__EXEC_1g0e37dce0173bc883aa1e5b14dd747f26)
+    ///
+    /// # Safety
+    /// `f` must be a valid function handle from a module that was not unloaded.
+    pub unsafe fn set_attribute(
+        f: sys::CUfunction,
+        attribute: sys::CUfunction_attribute,
+        value: c_int,
+    ) -> Result<(), DriverError> {
+        sys::cuFuncSetAttribute(f, attribute, value).result()
+    }
+
+    /// Sets the preferred cache configuration for a function.
+    ///
+    /// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__EXEC.html#group__CUDA__EXEC_1g40f8c11e81def95dc0072a375f965681)
+    ///
+    /// # Safety
+    /// `f` must be a valid function handle from a module that was not unloaded.
+    pub unsafe fn set_cache_config(
+        f: sys::CUfunction,
+        config: sys::CUfunc_cache,
+    ) -> Result<(), DriverError> {
+        sys::cuFuncSetCacheConfig(f, config).result()
+    }
+}
+
+pub mod external_memory {
+    //! External memory interop functions (`cuImportExternalMemory`, `cuExternalMemory*`).
+    //!
+    //! See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__EXTRES__INTEROP.html#group__CUDA__EXTRES__INTEROP)
+
+    use super::{sys, DriverError};
+    use core::ffi::c_int;
+    use std::mem::MaybeUninit;
+
+    /// Imports an external memory object described by an opaque file descriptor.
+    ///
+    /// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__EXTRES__INTEROP.html#group__CUDA__EXTRES__INTEROP)
+    ///
+    /// # Safety
+    /// 1. `fd` must be a valid file descriptor of a memory object exported by another API.
+    /// 2. On success, ownership of `fd` is transferred to the driver, so it must not be
+    ///    used or closed afterwards.
+    pub unsafe fn import_opaque_fd(
+        fd: c_int,
+        size: u64,
+    ) -> Result<sys::CUexternalMemory, DriverError> {
+        let mut external_memory = MaybeUninit::uninit();
+        let handle_description = sys::CUDA_EXTERNAL_MEMORY_HANDLE_DESC {
+            type_: sys::CUexternalMemoryHandleType::CU_EXTERNAL_MEMORY_HANDLE_TYPE_OPAQUE_FD,
+            handle: sys::CUDA_EXTERNAL_MEMORY_HANDLE_DESC_st__bindgen_ty_1 { fd },
+            size,
+            flags: 0,
+            reserved: [0; 16],
+        };
+        sys::cuImportExternalMemory(external_memory.as_mut_ptr(), &handle_description).result()?;
+        Ok(external_memory.assume_init())
+    }
+
+    /// Destroys an external memory object.
+    ///
+    /// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__EXTRES__INTEROP.html#group__CUDA__EXTRES__INTEROP)
+    ///
+    /// # Safety
+    /// 1. `external_memory` must have been created by [import_opaque_fd] and not destroyed already.
+    /// 2. All buffers mapped from it must have been freed.
+    pub unsafe fn destroy(external_memory: sys::CUexternalMemory) -> Result<(), DriverError> {
+        sys::cuDestroyExternalMemory(external_memory).result()
+    }
+
+    /// Maps a buffer of `size` bytes at `offset` onto an imported memory object.
+    /// The buffer must be freed with [super::free_sync].
+    ///
+    /// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__EXTRES__INTEROP.html#group__CUDA__EXTRES__INTEROP)
+    ///
+    /// # Safety
+    /// 1. `external_memory` must have been created by [import_opaque_fd] and not destroyed already.
+    /// 2. `offset + size` must not exceed the size of the memory object.
+    pub unsafe fn get_mapped_buffer(
+        external_memory: sys::CUexternalMemory,
+        offset: u64,
+        size: u64,
+    ) -> Result<sys::CUdeviceptr, DriverError> {
+        let mut device_ptr = MaybeUninit::uninit();
+        let buffer_description = sys::CUDA_EXTERNAL_MEMORY_BUFFER_DESC {
+            offset,
+            size,
+            ..Default::default()
+        };
+        sys::cuExternalMemoryGetMappedBuffer(
+            device_ptr.as_mut_ptr(),
+            external_memory,
+            &buffer_description,
+        )
+        .result()?;
+        Ok(device_ptr.assume_init())
+    }
+}
+
+pub mod external_semaphore {
+    //! External semaphore interop functions (`cu*ExternalSemaphore*`).
+    //!
+    //! See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__EXTRES__INTEROP.html#group__CUDA__EXTRES__INTEROP)
+
+    use super::{sys, DriverError};
+    use core::ffi::c_int;
+    use std::mem::{zeroed, MaybeUninit};
+
+    /// Imports an external semaphore described by a file descriptor.
+    ///
+    /// `kind` should be one of the `*_FD` variants of [sys::CUexternalSemaphoreHandleType].
+    ///
+    /// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__EXTRES__INTEROP.html#group__CUDA__EXTRES__INTEROP)
+    ///
+    /// # Safety
+    /// 1. `fd` must be a valid file descriptor of a semaphore exported by another API.
+    /// 2. On success, ownership of `fd` is transferred to the driver, so it must not be
+    ///    used or closed afterwards.
+    pub unsafe fn import_fd(
+        fd: c_int,
+        kind: sys::CUexternalSemaphoreHandleType,
+    ) -> Result<sys::CUexternalSemaphore, DriverError> {
+        let mut semaphore = MaybeUninit::uninit();
+        let handle_description = sys::CUDA_EXTERNAL_SEMAPHORE_HANDLE_DESC {
+            type_: kind,
+            handle: sys::CUDA_EXTERNAL_SEMAPHORE_HANDLE_DESC_st__bindgen_ty_1 { fd },
+            flags: 0,
+            reserved: [0; 16],
+        };
+        sys::cuImportExternalSemaphore(semaphore.as_mut_ptr(), &handle_description).result()?;
+        Ok(semaphore.assume_init())
+    }
+
+    /// Enqueues a signal of `semaphore` with `value` onto `stream`. `value` is
+    /// ignored for binary semaphores.
+    ///
+    /// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__EXTRES__INTEROP.html#group__CUDA__EXTRES__INTEROP)
+    ///
+    /// # Safety
+    /// `semaphore` and `stream` must be valid and not destroyed already.
+    pub unsafe fn signal_async(
+        semaphore: sys::CUexternalSemaphore,
+        value: u64,
+        stream: sys::CUstream,
+    ) -> Result<(), DriverError> {
+        let mut params: sys::CUDA_EXTERNAL_SEMAPHORE_SIGNAL_PARAMS = zeroed();
+        params.params.fence.value = value;
+        sys::cuSignalExternalSemaphoresAsync(&semaphore, &params, 1, stream).result()
+    }
+
+    /// Enqueues a wait on `semaphore` reaching `value` onto `stream`. `value` is
+    /// ignored for binary semaphores.
+    ///
+    /// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__EXTRES__INTEROP.html#group__CUDA__EXTRES__INTEROP)
+    ///
+    /// # Safety
+    /// `semaphore` and `stream` must be valid and not destroyed already.
+    pub unsafe fn wait_async(
+        semaphore: sys::CUexternalSemaphore,
+        value: u64,
+        stream: sys::CUstream,
+    ) -> Result<(), DriverError> {
+        let mut params: sys::CUDA_EXTERNAL_SEMAPHORE_WAIT_PARAMS = zeroed();
+        params.params.fence.value = value;
+        sys::cuWaitExternalSemaphoresAsync(&semaphore, &params, 1, stream).result()
+    }
+
+    /// Destroys an external semaphore.
+    ///
+    /// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__EXTRES__INTEROP.html#group__CUDA__EXTRES__INTEROP)
+    ///
+    /// # Safety
+    /// `semaphore` must have been created by [import_fd] and not destroyed already.
+    pub unsafe fn destroy(semaphore: sys::CUexternalSemaphore) -> Result<(), DriverError> {
+        sys::cuDestroyExternalSemaphore(semaphore).result()
+    }
+}
+
 pub mod event {
     use super::{sys, DriverError};
     use std::mem::MaybeUninit;
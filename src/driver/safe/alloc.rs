@@ -2,6 +2,7 @@ use crate::driver::{result, sys};
 
 use super::core::{CudaDevice, CudaSlice, CudaView, CudaViewMut};
 use super::device_ptr::{DevicePtr, DevicePtrMut, DeviceSlice};
+use super::strided::{StridedView, StridedViewMut};
 
 use std::{marker::Unpin, pin::Pin, sync::Arc, vec::Vec};
 
@@ -18,6 +19,45 @@ pub unsafe trait DeviceRepr {
     fn as_kernel_param(&self) -> *mut std::ffi::c_void {
         self as *const Self as *mut _
     }
+
+    /// The size in bytes of the value [DeviceRepr::as_kernel_param()] points to. In debug
+    /// builds this is checked against the kernel's declared parameter sizes on launch, see
+    /// [CudaFunction::check_param_sizes()](crate::driver::CudaFunction::check_param_sizes()).
+    ///
+    /// Must be overridden along with [DeviceRepr::as_kernel_param()] if that doesn't point to `self`.
+    #[inline(always)]
+    fn kernel_param_size(&self) -> usize {
+        std::mem::size_of_val(self)
+    }
+}
+
+/// The memory layout of a struct as a kernel will see it. Implemented by
+/// `#[derive(DeviceRepr)]` (with the `derive` feature), so the layout can be compared
+/// against the struct declared in the kernel's source.
+pub trait DeviceLayout {
+    const LAYOUT: StructLayout;
+}
+
+/// The size & alignment of a struct, and the position of each of its fields.
+/// See [DeviceLayout].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StructLayout {
+    pub name: &'static str,
+    pub size: usize,
+    pub align: usize,
+    pub fields: &'static [FieldLayout],
+}
+
+/// A single field of a [StructLayout]. Fields of tuple structs are named by their index.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FieldLayout {
+    pub name: &'static str,
+    /// The rust type of the field, as written in the struct definition
+    pub ty: &'static str,
+    /// Offset in bytes from the start of the struct
+    pub offset: usize,
+    pub size: usize,
+    pub align: usize,
 }
 
 unsafe impl DeviceRepr for bool {}
@@ -45,6 +85,11 @@ unsafe impl<T: DeviceRepr> DeviceRepr for &mut CudaSlice<T> {
     fn as_kernel_param(&self) -> *mut std::ffi::c_void {
         (&self.cu_device_ptr) as *const sys::CUdeviceptr as *mut std::ffi::c_void
     }
+
+    #[inline(always)]
+    fn kernel_param_size(&self) -> usize {
+        std::mem::size_of::<sys::CUdeviceptr>()
+    }
 }
 
 unsafe impl<T: DeviceRepr> DeviceRepr for &CudaSlice<T> {
@@ -52,6 +97,11 @@ unsafe impl<T: DeviceRepr> DeviceRepr for &CudaSlice<T> {
     fn as_kernel_param(&self) -> *mut std::ffi::c_void {
         (&self.cu_device_ptr) as *const sys::CUdeviceptr as *mut std::ffi::c_void
     }
+
+    #[inline(always)]
+    fn kernel_param_size(&self) -> usize {
+        std::mem::size_of::<sys::CUdeviceptr>()
+    }
 }
 
 unsafe impl<'a, T: DeviceRepr> DeviceRepr for &CudaView<'a, T> {
@@ -59,6 +109,11 @@ unsafe impl<'a, T: DeviceRepr> DeviceRepr for &CudaView<'a, T> {
     fn as_kernel_param(&self) -> *mut std::ffi::c_void {
         (&self.ptr) as *const sys::CUdeviceptr as *mut std::ffi::c_void
     }
+
+    #[inline(always)]
+    fn kernel_param_size(&self) -> usize {
+        std::mem::size_of::<sys::CUdeviceptr>()
+    }
 }
 
 unsafe impl<'a, T: DeviceRepr> DeviceRepr for &mut CudaViewMut<'a, T> {
@@ -66,6 +121,37 @@ unsafe impl<'a, T: DeviceRepr> DeviceRepr for &mut CudaViewMut<'a, T> {
     fn as_kernel_param(&self) -> *mut std::ffi::c_void {
         (&self.ptr) as *const sys::CUdeviceptr as *mut std::ffi::c_void
     }
+
+    #[inline(always)]
+    fn kernel_param_size(&self) -> usize {
+        std::mem::size_of::<sys::CUdeviceptr>()
+    }
+}
+
+/// Passed as the whole view, see [super::strided::STRIDED_VIEW_SRC].
+unsafe impl<'a, T: DeviceRepr> DeviceRepr for &StridedView<'a, T> {
+    #[inline(always)]
+    fn as_kernel_param(&self) -> *mut std::ffi::c_void {
+        (*self) as *const StridedView<'a, T> as *mut std::ffi::c_void
+    }
+
+    #[inline(always)]
+    fn kernel_param_size(&self) -> usize {
+        std::mem::size_of::<StridedView<'a, T>>()
+    }
+}
+
+/// Passed as the whole view, see [super::strided::STRIDED_VIEW_SRC].
+unsafe impl<'a, T: DeviceRepr> DeviceRepr for &mut StridedViewMut<'a, T> {
+    #[inline(always)]
+    fn as_kernel_param(&self) -> *mut std::ffi::c_void {
+        (&**self) as *const StridedViewMut<'a, T> as *mut std::ffi::c_void
+    }
+
+    #[inline(always)]
+    fn kernel_param_size(&self) -> usize {
+        std::mem::size_of::<StridedViewMut<'a, T>>()
+    }
 }
 
 impl CudaDevice {
@@ -328,10 +414,76 @@ macro_rules! impl_tuples {
 }
 impl_tuples!(A, B, C, D, E, F, G, H, I, J, K, L);
 
+/// Marker trait to indicate that every bit pattern is a valid value of the type,
+/// and that it has no padding bytes, so memory can be freely viewed as it
+/// (see [CudaSlice::reinterpret()]).
+///
+/// `bool` and tuples are [ValidAsZeroBits] but not this, since not every byte is a valid `bool`
+/// and tuples can have padding:
+/// ```compile_fail
+/// # use cudarc::driver::*;
+/// let dev = CudaDevice::new(0).unwrap();
+/// let a = dev.htod_copy(vec![2u8]).unwrap();
+/// let b: CudaView<bool> = a.reinterpret().unwrap();
+/// ```
+///
+/// # Safety
+/// The type must be valid for any bits, and have no padding.
+pub unsafe trait ValidAsAnyBits: ValidAsZeroBits {}
+unsafe impl ValidAsAnyBits for i8 {}
+unsafe impl ValidAsAnyBits for i16 {}
+unsafe impl ValidAsAnyBits for i32 {}
+unsafe impl ValidAsAnyBits for i64 {}
+unsafe impl ValidAsAnyBits for i128 {}
+unsafe impl ValidAsAnyBits for isize {}
+unsafe impl ValidAsAnyBits for u8 {}
+unsafe impl ValidAsAnyBits for u16 {}
+unsafe impl ValidAsAnyBits for u32 {}
+unsafe impl ValidAsAnyBits for u64 {}
+unsafe impl ValidAsAnyBits for u128 {}
+unsafe impl ValidAsAnyBits for usize {}
+unsafe impl ValidAsAnyBits for f32 {}
+unsafe impl ValidAsAnyBits for f64 {}
+#[cfg(feature = "f16")]
+unsafe impl ValidAsAnyBits for half::f16 {}
+#[cfg(feature = "f16")]
+unsafe impl ValidAsAnyBits for half::bf16 {}
+unsafe impl<T: ValidAsAnyBits, const M: usize> ValidAsAnyBits for [T; M] {}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[cfg(feature = "derive")]
+    #[test]
+    fn test_derive_layout() {
+        #[derive(crate::driver::DeviceRepr, crate::driver::ValidAsZeroBits)]
+        #[repr(C)]
+        struct Params {
+            a: f32,
+            b: f64,
+            c: u16,
+            d: u8,
+        }
+
+        #[derive(crate::driver::DeviceRepr, crate::driver::ValidAsZeroBits)]
+        #[repr(transparent)]
+        struct Wrapper<T>(T);
+
+        let layout = Params::LAYOUT;
+        assert_eq!(layout.name, "Params");
+        assert_eq!(layout.size, 24);
+        assert_eq!(layout.align, 8);
+        let offsets: Vec<_> = layout.fields.iter().map(|f| (f.name, f.offset)).collect();
+        assert_eq!(offsets, [("a", 0), ("b", 8), ("c", 16), ("d", 18)]);
+        assert_eq!(layout.fields[2].ty, "u16");
+        assert_eq!(layout.fields[2].size, 2);
+
+        let layout = Wrapper::<Params>::LAYOUT;
+        assert_eq!(layout.size, 24);
+        assert_eq!(layout.fields[0].name, "0");
+    }
+
     #[test]
     fn test_post_build_arc_count() {
         let device = CudaDevice::new(0).unwrap();
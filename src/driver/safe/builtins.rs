@@ -0,0 +1,300 @@
+//! Built-in operations on device memory: [CudaDevice::fill()], [CudaDevice::arange()],
+//! [CudaDevice::cast()] and [CudaDevice::copy_strided()].
+
+use crate::driver::result;
+
+use super::alloc::DeviceRepr;
+use super::core::CudaDevice;
+use super::device_ptr::{DevicePtr, DevicePtrMut};
+use super::elementwise::{grid_stride_cfg, grid_stride_src, includes, DType};
+use super::launch::{KernelArgs, LaunchAsync};
+use super::ptx::CompileAndLoadError;
+
+use std::{
+    format,
+    string::{String, ToString},
+    sync::Arc,
+    vec::Vec,
+};
+
+impl CudaDevice {
+    /// Sets every element of `dst` to `value`.
+    ///
+    /// Types of 1, 2 or 4 bytes are set with `cuMemsetD8/D16/D32Async`, and 8 or 16 byte
+    /// types with a kernel that is compiled on first use.
+    ///
+    /// ```rust
+    /// # use cudarc::driver::*;
+    /// let dev = CudaDevice::new(0).unwrap();
+    /// let mut a = dev.alloc_zeros::<f32>(3).unwrap();
+    /// dev.fill(&mut a, 1.5).unwrap();
+    /// assert_eq!(dev.dtoh_sync_copy(&a).unwrap(), [1.5; 3]);
+    /// ```
+    ///
+    /// # Panics
+    /// If `T` isn't 1, 2, 4, 8 or 16 bytes.
+    pub fn fill<T: DeviceRepr, Dst: DevicePtrMut<T>>(
+        self: &Arc<Self>,
+        dst: &mut Dst,
+        value: T,
+    ) -> Result<(), CompileAndLoadError> {
+        let len = dst.len();
+        let ptr = *dst.device_ptr_mut();
+        // Safety: `value` is a valid `T`, so every element will be too
+        match std::mem::size_of::<T>() {
+            1 => unsafe {
+                let value = std::mem::transmute_copy::<T, u8>(&value);
+                result::memset_d8_async(ptr, value, len, self.stream)?;
+            },
+            2 => unsafe {
+                let value = std::mem::transmute_copy::<T, u16>(&value);
+                result::memset_d16_async(ptr, value, len, self.stream)?;
+            },
+            4 => unsafe {
+                let value = std::mem::transmute_copy::<T, u32>(&value);
+                result::memset_d32_async(ptr, value, len, self.stream)?;
+            },
+            size @ (8 | 16) => {
+                let func = self.generated_func("fill", fill_src(size), false)?;
+                if len > 0 {
+                    let args = KernelArgs::new().arg(len).arg(ptr).arg(value);
+                    unsafe { func.launch(grid_stride_cfg(len), args) }?;
+                }
+            }
+            size => panic!("fill is not supported for types of {size} bytes"),
+        }
+        Ok(())
+    }
+
+    /// Sets `dst[i] = start + i * step`.
+    ///
+    /// ```rust
+    /// # use cudarc::driver::*;
+    /// let dev = CudaDevice::new(0).unwrap();
+    /// let mut a = dev.alloc_zeros::<i32>(4).unwrap();
+    /// dev.arange(&mut a, 10, -2).unwrap();
+    /// assert_eq!(dev.dtoh_sync_copy(&a).unwrap(), [10, 8, 6, 4]);
+    /// ```
+    pub fn arange<T: DType, Dst: DevicePtrMut<T>>(
+        self: &Arc<Self>,
+        dst: &mut Dst,
+        start: T,
+        step: T,
+    ) -> Result<(), CompileAndLoadError> {
+        let func = self.generated_func("arange", arange_src::<T>(), T::HEADER.is_some())?;
+        let len = dst.len();
+        if len > 0 {
+            let args = KernelArgs::new()
+                .arg(len)
+                .arg(*dst.device_ptr_mut())
+                .arg(start)
+                .arg(step);
+            unsafe { func.launch(grid_stride_cfg(len), args) }?;
+        }
+        Ok(())
+    }
+
+    /// Converts each element of `src` to `D`, like an `as` cast in cuda. Conversions to or
+    /// from `f16`/`bf16` go through `f32`.
+    ///
+    /// ```rust
+    /// # use cudarc::driver::*;
+    /// let dev = CudaDevice::new(0).unwrap();
+    /// let a = dev.htod_copy(vec![1.5f32, -2.0, 3.25]).unwrap();
+    /// let mut b = dev.alloc_zeros::<i32>(3).unwrap();
+    /// dev.cast(&a, &mut b).unwrap();
+    /// assert_eq!(dev.dtoh_sync_copy(&b).unwrap(), [1, -2, 3]);
+    /// ```
+    ///
+    /// # Panics
+    /// If `src` and `dst` have different lengths.
+    pub fn cast<S: DType, D: DType, Src: DevicePtr<S>, Dst: DevicePtrMut<D>>(
+        self: &Arc<Self>,
+        src: &Src,
+        dst: &mut Dst,
+    ) -> Result<(), CompileAndLoadError> {
+        assert_eq!(src.len(), dst.len());
+        let needs_headers = S::HEADER.is_some() || D::HEADER.is_some();
+        let func = self.generated_func("cast", cast_src::<S, D>(), needs_headers)?;
+        let len = dst.len();
+        if len > 0 {
+            let args = KernelArgs::new()
+                .arg(len)
+                .arg(*dst.device_ptr_mut())
+                .arg(*src.device_ptr());
+            unsafe { func.launch(grid_stride_cfg(len), args) }?;
+        }
+        Ok(())
+    }
+
+    /// Gathers the elements of `src` at `shape` with `strides` (in elements) into the
+    /// contiguous `dst`. A stride of 0 repeats `src` along that dimension.
+    ///
+    /// ```rust
+    /// # use cudarc::driver::*;
+    /// let dev = CudaDevice::new(0).unwrap();
+    /// let a = dev.htod_copy(vec![1u32, 2, 3, 4, 5, 6]).unwrap();
+    /// let mut a_t = dev.alloc_zeros::<u32>(6).unwrap();
+    /// // transpose the 2x3 `a`
+    /// dev.copy_strided(&a, &[3, 2], &[1, 3], &mut a_t).unwrap();
+    /// assert_eq!(dev.dtoh_sync_copy(&a_t).unwrap(), [1, 4, 2, 5, 3, 6]);
+    /// ```
+    ///
+    /// # Panics
+    /// If `dst`'s length isn't the number of elements in `shape`, or `strides` reach past the
+    /// end of `src`.
+    pub fn copy_strided<T: DType, Src: DevicePtr<T>, Dst: DevicePtrMut<T>>(
+        self: &Arc<Self>,
+        src: &Src,
+        shape: &[usize],
+        strides: &[usize],
+        dst: &mut Dst,
+    ) -> Result<(), CompileAndLoadError> {
+        // Safety: the expression only reads the input
+        unsafe { self.elementwise_strided("a", shape, &[(src, strides)], dst) }
+    }
+}
+
+/// Declares `bytes16`, see [opaque_type()].
+pub(crate) const BYTES16: &str = "struct __align__(8) bytes16 { unsigned long long x[2]; };\n\n";
+
+/// A cuda type of `size` bytes to move values around as, without knowing their type.
+/// `bytes16` has to be declared with [BYTES16] first.
+pub(crate) fn opaque_type(size: usize) -> Option<&'static str> {
+    match size {
+        1 => Some("unsigned char"),
+        2 => Some("unsigned short"),
+        4 => Some("unsigned int"),
+        8 => Some("unsigned long long"),
+        16 => Some("bytes16"),
+        _ => None,
+    }
+}
+
+/// A kernel that sets every element to a value of `size` bytes, without knowing its type.
+fn fill_src(size: usize) -> String {
+    let ty = opaque_type(size).unwrap();
+    let prelude = if size == 16 { BYTES16 } else { "" };
+    let params = [
+        "const size_t numel".to_string(),
+        format!("{ty} *out"),
+        format!("const {ty} value"),
+    ];
+    grid_stride_src(prelude, "fill", &params, "        out[i] = value;\n")
+}
+
+fn arange_src<T: DType>() -> String {
+    let ty = T::C_TYPE;
+    let params = [
+        "const size_t numel".to_string(),
+        format!("{ty} *out"),
+        format!("const {ty} start"),
+        format!("const {ty} step"),
+    ];
+    let i = convert::<u64, T>("i");
+    let body = format!("        out[i] = start + {i} * step;\n");
+    grid_stride_src(&includes(&[T::HEADER]), "arange", &params, &body)
+}
+
+fn cast_src<S: DType, D: DType>() -> String {
+    let params = [
+        "const size_t numel".to_string(),
+        format!("{} *out", D::C_TYPE),
+        format!("const {} *inp", S::C_TYPE),
+    ];
+    let body = format!("        out[i] = {};\n", convert::<S, D>("inp[i]"));
+    let headers: Vec<Option<&str>> = match S::HEADER == D::HEADER {
+        true => std::vec![S::HEADER],
+        false => std::vec![S::HEADER, D::HEADER],
+    };
+    grid_stride_src(&includes(&headers), "cast", &params, &body)
+}
+
+/// Converts the C expression `expr` of type `S` to `D`. The half types only have
+/// conversions to and from `float` that work in both directions, so go through that.
+fn convert<S: DType, D: DType>(expr: &str) -> String {
+    if S::C_TYPE == D::C_TYPE {
+        format!("({expr})")
+    } else if S::HEADER.is_some() || D::HEADER.is_some() {
+        format!("(({})(float)({expr}))", D::C_TYPE)
+    } else {
+        format!("(({})({expr}))", D::C_TYPE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fill_src() {
+        assert!(fill_src(8).contains("unsigned long long *out, const unsigned long long value"));
+        let src = fill_src(16);
+        assert!(src.starts_with("struct __align__(8) bytes16"));
+        assert!(src.contains("out[i] = value;"));
+    }
+
+    #[test]
+    fn test_cast_src() {
+        let src = cast_src::<f32, u8>();
+        assert!(src.contains("unsigned char *out, const float *inp"));
+        assert!(src.contains("out[i] = ((unsigned char)(inp[i]));"));
+        assert!(!src.contains("#include"));
+        assert!(arange_src::<f64>().contains("out[i] = start + ((double)(i)) * step;"));
+    }
+
+    #[cfg(feature = "f16")]
+    #[test]
+    fn test_cast_half_src() {
+        let src = cast_src::<half::f16, half::bf16>();
+        assert!(src.starts_with("#include \"cuda_fp16.h\"\n#include \"cuda_bf16.h\"\n\n"));
+        assert!(src.contains("out[i] = ((__nv_bfloat16)(float)(inp[i]));"));
+    }
+
+    #[test]
+    fn test_fill() {
+        let dev = CudaDevice::new(0).unwrap();
+        let mut a = dev.alloc_zeros::<u8>(5).unwrap();
+        dev.fill(&mut a, 7).unwrap();
+        assert_eq!(dev.dtoh_sync_copy(&a).unwrap(), [7; 5]);
+
+        let mut b = dev.alloc_zeros::<i16>(5).unwrap();
+        dev.fill(&mut b.slice_mut(1..4), -3).unwrap();
+        assert_eq!(dev.dtoh_sync_copy(&b).unwrap(), [0, -3, -3, -3, 0]);
+
+        let mut c = dev.alloc_zeros::<f64>(5).unwrap();
+        dev.fill(&mut c, 0.25).unwrap();
+        assert_eq!(dev.dtoh_sync_copy(&c).unwrap(), [0.25; 5]);
+
+        let mut d = dev.alloc_zeros::<u128>(5).unwrap();
+        dev.fill(&mut d, u128::MAX - 1).unwrap();
+        assert_eq!(dev.dtoh_sync_copy(&d).unwrap(), [u128::MAX - 1; 5]);
+    }
+
+    #[test]
+    fn test_arange_and_cast() {
+        let dev = CudaDevice::new(0).unwrap();
+        let mut a = dev.alloc_zeros::<f32>(4).unwrap();
+        dev.arange(&mut a, 0.5, 0.25).unwrap();
+        assert_eq!(dev.dtoh_sync_copy(&a).unwrap(), [0.5, 0.75, 1.0, 1.25]);
+
+        let mut b = dev.alloc_zeros::<u64>(4).unwrap();
+        dev.cast(&a, &mut b).unwrap();
+        assert_eq!(dev.dtoh_sync_copy(&b).unwrap(), [0, 0, 1, 1]);
+    }
+
+    #[cfg(feature = "f16")]
+    #[test]
+    fn test_cast_half() {
+        let dev = CudaDevice::new(0).unwrap();
+        let a = dev.htod_copy(std::vec![1.5f32, -2.0, 65504.0]).unwrap();
+        let mut b = dev.alloc_zeros::<half::f16>(3).unwrap();
+        dev.cast(&a, &mut b).unwrap();
+        let b_host = dev.dtoh_sync_copy(&b).unwrap();
+        assert_eq!(b_host, [1.5, -2.0, 65504.0].map(half::f16::from_f32));
+
+        let mut c = dev.alloc_zeros::<i32>(3).unwrap();
+        dev.cast(&b, &mut c).unwrap();
+        assert_eq!(dev.dtoh_sync_copy(&c).unwrap(), [1, -2, 65504]);
+    }
+}
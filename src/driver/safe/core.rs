@@ -1,7 +1,11 @@
 use crate::driver::{result, sys};
 
-use super::{alloc::DeviceRepr, device_ptr::DeviceSlice};
+use super::{
+    alloc::{DeviceRepr, ValidAsAnyBits},
+    error::{defer_error, DeferredError},
+};
 
+use core::marker::PhantomData;
 use core::ops::{Bound, RangeBounds};
 
 #[cfg(feature = "no-std")]
@@ -9,7 +13,7 @@ use spin::RwLock;
 #[cfg(not(feature = "no-std"))]
 use std::sync::RwLock;
 
-use std::{collections::BTreeMap, marker::Unpin, pin::Pin, sync::Arc, vec::Vec};
+use std::{collections::BTreeMap, marker::Unpin, pin::Pin, string::String, sync::Arc, vec::Vec};
 
 /// A wrapper around [sys::CUdevice], [sys::CUcontext], [sys::CUstream],
 /// and [CudaFunction].
@@ -33,7 +37,10 @@ pub struct CudaDevice {
     pub(crate) stream: sys::CUstream,
     /// Used to synchronize with stream
     pub(crate) event: sys::CUevent,
-    pub(crate) modules: RwLock<BTreeMap<&'static str, CudaModule>>,
+    /// Modules that can be looked up by name, see [CudaDevice::get_module()]
+    pub(crate) modules: RwLock<BTreeMap<String, Arc<LoadedModule>>>,
+    /// The most recent error that couldn't be returned, see [CudaDevice::last_error()]
+    pub(crate) last_error: RwLock<Option<DeferredError>>,
 }
 
 unsafe impl Send for CudaDevice {}
@@ -41,54 +48,107 @@ unsafe impl Sync for CudaDevice {}
 
 impl CudaDevice {
     /// Creates a new [CudaDevice] on device index `ordinal`.
-    pub fn new(ordinal: usize) -> Result<Arc<Self>, result::DriverError> {
-        result::init().unwrap();
+    ///
+    /// Returns an error if the driver can't be loaded or initialized (e.g. there is no gpu),
+    /// or `ordinal` is not a valid device index.
+    pub fn new(ordinal: usize) -> Result<Arc<Self>, DeviceInitError> {
+        #[cfg(feature = "dynamic-loading")]
+        sys::LIBRARY.load().map_err(DeviceInitError::LoadError)?;
+        result::init()?;
 
-        let cu_device = result::device::get(ordinal as i32).unwrap();
+        let cu_device = result::device::get(ordinal as i32)?;
 
         // primary context initialization, can fail with OOM
         let cu_primary_ctx = unsafe { result::primary_ctx::retain(cu_device) }?;
 
-        unsafe { result::ctx::set_current(cu_primary_ctx) }.unwrap();
-
-        // can fail with OOM
-        let event = result::event::create(sys::CUevent_flags::CU_EVENT_DISABLE_TIMING)?;
-
-        let device = CudaDevice {
+        // from here on, dropping `device` on error releases the primary context
+        let mut device = CudaDevice {
             cu_device,
             cu_primary_ctx,
             stream: std::ptr::null_mut(),
-            event,
+            event: std::ptr::null_mut(),
             modules: RwLock::new(BTreeMap::new()),
+            last_error: RwLock::new(None),
         };
+
+        device.bind_to_thread()?;
+
+        // can fail with OOM
+        device.event = result::event::create(sys::CUevent_flags::CU_EVENT_DISABLE_TIMING)?;
+
         Ok(Arc::new(device))
     }
 }
 
+/// An error from [CudaDevice::new()].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeviceInitError {
+    /// The cuda driver library could not be loaded
+    #[cfg(feature = "dynamic-loading")]
+    LoadError(crate::dynamic_loading::LoadError),
+    Driver(result::DriverError),
+}
+
+impl From<result::DriverError> for DeviceInitError {
+    fn from(value: result::DriverError) -> Self {
+        Self::Driver(value)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::fmt::Display for DeviceInitError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for DeviceInitError {}
+
+impl CudaDevice {
+    /// Returns the value of `attrib` for this device.
+    pub fn attribute(&self, attrib: sys::CUdevice_attribute) -> Result<i32, result::DriverError> {
+        unsafe { result::device::get_attribute(self.cu_device, attrib) }
+    }
+
+    /// The (major, minor) compute capability of this device, e.g. `(8, 6)`.
+    pub fn compute_capability(&self) -> Result<(i32, i32), result::DriverError> {
+        use sys::CUdevice_attribute::*;
+        let major = self.attribute(CU_DEVICE_ATTRIBUTE_COMPUTE_CAPABILITY_MAJOR)?;
+        let minor = self.attribute(CU_DEVICE_ATTRIBUTE_COMPUTE_CAPABILITY_MINOR)?;
+        Ok((major, minor))
+    }
+}
+
 impl Drop for CudaDevice {
     fn drop(&mut self) {
+        // no handles to the modules are left, since they would keep `self` alive,
+        // so this unloads all of them
         let modules = RwLock::get_mut(&mut self.modules);
         #[cfg(not(feature = "no-std"))]
-        let modules = modules.unwrap();
-
-        for (_, module) in modules.iter() {
-            unsafe { result::module::unload(module.cu_module) }.unwrap();
-        }
+        let modules = modules.unwrap_or_else(|e| e.into_inner());
         modules.clear();
 
+        // errors can't be recorded on a device that is being dropped, so only the hook sees them
         let stream = std::mem::replace(&mut self.stream, std::ptr::null_mut());
         if !stream.is_null() {
-            unsafe { result::stream::destroy(stream) }.unwrap();
+            if let Err(e) = unsafe { result::stream::destroy(stream) } {
+                defer_error(None, e);
+            }
         }
 
         let event = std::mem::replace(&mut self.event, std::ptr::null_mut());
         if !event.is_null() {
-            unsafe { result::event::destroy(event) }.unwrap();
+            if let Err(e) = unsafe { result::event::destroy(event) } {
+                defer_error(None, e);
+            }
         }
 
         let ctx = std::mem::replace(&mut self.cu_primary_ctx, std::ptr::null_mut());
         if !ctx.is_null() {
-            unsafe { result::primary_ctx::release(self.cu_device) }.unwrap();
+            if let Err(e) = unsafe { result::primary_ctx::release(self.cu_device) } {
+                defer_error(None, e);
+            }
         }
     }
 }
@@ -132,8 +192,8 @@ unsafe impl<T: Sync> Sync for CudaSlice<T> {}
 
 impl<T> Drop for CudaSlice<T> {
     fn drop(&mut self) {
-        unsafe {
-            result::free_async(self.cu_device_ptr, self.device.stream).unwrap();
+        if let Err(e) = unsafe { result::free_async(self.cu_device_ptr, self.device.stream) } {
+            defer_error(Some(&self.device), e);
         }
     }
 }
@@ -163,21 +223,58 @@ impl<T: Clone + Default + DeviceRepr + Unpin> TryFrom<CudaSlice<T>> for Vec<T> {
 /// Wrapper around [sys::CUmodule] that also contains
 /// the loaded [CudaFunction] associated with this module.
 ///
-/// See [CudaModule::get_fn()] for retrieving function handles.
+/// Shared by the [CudaModule] handles & [CudaFunction]s of the module, and
+/// unloaded when the last of them is dropped.
 #[derive(Debug)]
-pub(crate) struct CudaModule {
+pub(crate) struct LoadedModule {
+    pub(crate) name: String,
     pub(crate) cu_module: sys::CUmodule,
-    pub(crate) functions: BTreeMap<&'static str, sys::CUfunction>,
+    /// The context `cu_module` was loaded into, which outlives this since every
+    /// handle to the module also holds the [CudaDevice].
+    pub(crate) cu_primary_ctx: sys::CUcontext,
+    pub(crate) functions: BTreeMap<String, sys::CUfunction>,
+    /// Byte size of each parameter of the functions, if known from the ptx.
+    pub(crate) param_sizes: BTreeMap<String, Arc<[usize]>>,
+}
+
+unsafe impl Send for LoadedModule {}
+unsafe impl Sync for LoadedModule {}
+
+impl Drop for LoadedModule {
+    fn drop(&mut self) {
+        let unload = unsafe {
+            result::ctx::set_current(self.cu_primary_ctx)
+                .and_then(|_| result::module::unload(self.cu_module))
+        };
+        if let Err(e) = unload {
+            defer_error(None, e);
+        }
+    }
 }
 
-unsafe impl Send for CudaModule {}
-unsafe impl Sync for CudaModule {}
+/// A module loaded onto a [CudaDevice] with e.g. [CudaDevice::load_ptx()].
+///
+/// The module stays loaded while this handle, or any [CudaFunction] retrieved from it, is
+/// alive, even after [CudaDevice::unload_module()] removes it from the device's registry.
+///
+/// See [CudaModule::get_func()] for retrieving function handles.
+#[derive(Debug, Clone)]
+pub struct CudaModule {
+    // dropped before `device`, which owns the context the module is unloaded from
+    pub(crate) module: Arc<LoadedModule>,
+    pub(crate) device: Arc<CudaDevice>,
+}
 
 /// Wrapper around [sys::CUfunction]. Used by [crate::driver::LaunchAsync].
+///
+/// Keeps the module it was loaded from alive.
 #[derive(Debug, Clone)]
 pub struct CudaFunction {
     pub(crate) cu_function: sys::CUfunction,
+    pub(crate) module: Arc<LoadedModule>,
     pub(crate) device: Arc<CudaDevice>,
+    /// See [CudaFunction::check_param_sizes()]
+    pub(crate) param_sizes: Option<Arc<[usize]>>,
 }
 
 unsafe impl Send for CudaFunction {}
@@ -259,9 +356,11 @@ impl CudaStream {
 
 impl Drop for CudaStream {
     fn drop(&mut self) {
-        self.device.wait_for(self).unwrap();
-        unsafe {
-            result::stream::destroy(self.stream).unwrap();
+        if let Err(e) = self.device.wait_for(self) {
+            defer_error(Some(&self.device), e);
+        }
+        if let Err(e) = unsafe { result::stream::destroy(self.stream) } {
+            defer_error(Some(&self.device), e);
         }
     }
 }
@@ -269,13 +368,20 @@ impl Drop for CudaStream {
 /// A immutable sub-view into a [CudaSlice] created by [CudaSlice::try_slice()].
 ///
 /// See module docstring for more details.
-#[allow(unused)]
 pub struct CudaView<'a, T> {
-    pub(crate) slice: &'a CudaSlice<T>,
     pub(crate) ptr: sys::CUdeviceptr,
     pub(crate) len: usize,
+    pub(crate) marker: PhantomData<&'a [T]>,
+}
+
+impl<'a, T> Clone for CudaView<'a, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
 }
 
+impl<'a, T> Copy for CudaView<'a, T> {}
+
 impl<T> CudaSlice<T> {
     /// Creates a [CudaView] at the specified offset from the start of `self`.
     ///
@@ -288,10 +394,114 @@ impl<T> CudaSlice<T> {
 
     /// Fallible version of [CudaSlice::slice]
     pub fn try_slice(&self, range: impl RangeBounds<usize>) -> Option<CudaView<'_, T>> {
-        range.bounds(..self.len()).map(|(start, end)| CudaView {
-            ptr: self.cu_device_ptr + (start * std::mem::size_of::<T>()) as u64,
-            slice: self,
+        self.as_view().try_slice(range)
+    }
+
+    /// A [CudaView] of all of `self`.
+    pub fn as_view(&self) -> CudaView<'_, T> {
+        CudaView {
+            ptr: self.cu_device_ptr,
+            len: self.len,
+            marker: PhantomData,
+        }
+    }
+
+    /// See [CudaView::split_at()].
+    pub fn split_at(&self, mid: usize) -> (CudaView<'_, T>, CudaView<'_, T>) {
+        self.as_view().split_at(mid)
+    }
+
+    /// See [CudaView::chunks()].
+    pub fn chunks(&self, chunk_size: usize) -> Chunks<'_, T> {
+        self.as_view().chunks(chunk_size)
+    }
+
+    /// See [CudaView::reinterpret()].
+    pub fn reinterpret<U: ValidAsAnyBits>(&self) -> Option<CudaView<'_, U>>
+    where
+        T: ValidAsAnyBits,
+    {
+        self.as_view().reinterpret()
+    }
+}
+
+impl<'a, T> CudaView<'a, T> {
+    /// A sub-view of `self`, which borrows the same [CudaSlice] as `self`.
+    ///
+    /// # Panics
+    /// Like [CudaSlice::slice()].
+    pub fn slice(&self, range: impl RangeBounds<usize>) -> CudaView<'a, T> {
+        self.try_slice(range).unwrap()
+    }
+
+    /// Fallible version of [CudaView::slice]
+    pub fn try_slice(&self, range: impl RangeBounds<usize>) -> Option<CudaView<'a, T>> {
+        range.bounds(..self.len).map(|(start, end)| CudaView {
+            ptr: offset_ptr::<T>(self.ptr, start),
             len: 1 + end - start,
+            marker: PhantomData,
+        })
+    }
+
+    /// Splits into the views of `..mid` and `mid..`, either of which may be empty.
+    ///
+    /// # Panics
+    /// If `mid > self.len()`.
+    pub fn split_at(&self, mid: usize) -> (CudaView<'a, T>, CudaView<'a, T>) {
+        assert!(mid <= self.len, "mid > len");
+        let (left, right) = split_ptr::<T>(self.ptr, self.len, mid);
+        (
+            CudaView {
+                ptr: left.0,
+                len: left.1,
+                marker: PhantomData,
+            },
+            CudaView {
+                ptr: right.0,
+                len: right.1,
+                marker: PhantomData,
+            },
+        )
+    }
+
+    /// An iterator over views of `chunk_size` elements, except for the last one which has
+    /// the remainder.
+    ///
+    /// # Panics
+    /// If `chunk_size` is 0.
+    pub fn chunks(&self, chunk_size: usize) -> Chunks<'a, T> {
+        assert!(chunk_size != 0, "chunk_size must not be 0");
+        Chunks {
+            ptr: self.ptr,
+            len: self.len,
+            chunk_size,
+            marker: PhantomData,
+        }
+    }
+
+    /// Views the same memory as elements of `U`. Both `T` and `U` have to be
+    /// [ValidAsAnyBits], so every element of the new view is a valid `U`.
+    ///
+    /// Returns `None` if the view isn't a whole number of `U`s, or isn't aligned for `U`.
+    ///
+    /// ```rust
+    /// # use cudarc::driver::*;
+    /// let dev = CudaDevice::new(0).unwrap();
+    /// let a = dev.alloc_zeros::<f32>(6).unwrap();
+    /// let bits: CudaView<u32> = a.reinterpret().unwrap();
+    /// assert_eq!(bits.len(), 6);
+    /// let pairs: CudaView<[f32; 2]> = a.reinterpret().unwrap();
+    /// assert_eq!(pairs.len(), 3);
+    /// assert!(a.slice(1..).reinterpret::<f64>().is_none());
+    /// ```
+    pub fn reinterpret<U: ValidAsAnyBits>(self) -> Option<CudaView<'a, U>>
+    where
+        T: ValidAsAnyBits,
+    {
+        reinterpret_len::<T, U>(self.ptr, self.len).map(|len| CudaView {
+            ptr: self.ptr,
+            len,
+            marker: PhantomData,
         })
     }
 }
@@ -299,11 +509,10 @@ impl<T> CudaSlice<T> {
 /// A mutable sub-view into a [CudaSlice] created by [CudaSlice::try_slice_mut()].
 ///
 /// See module docstring for more details.
-#[allow(unused)]
 pub struct CudaViewMut<'a, T> {
-    pub(crate) slice: &'a mut CudaSlice<T>,
     pub(crate) ptr: sys::CUdeviceptr,
     pub(crate) len: usize,
+    pub(crate) marker: PhantomData<&'a mut [T]>,
 }
 
 impl<T> CudaSlice<T> {
@@ -318,15 +527,250 @@ impl<T> CudaSlice<T> {
 
     /// Fallible version of [CudaSlice::slice_mut]
     pub fn try_slice_mut(&mut self, range: impl RangeBounds<usize>) -> Option<CudaViewMut<'_, T>> {
-        range.bounds(..self.len()).map(|(start, end)| CudaViewMut {
-            ptr: self.cu_device_ptr + (start * std::mem::size_of::<T>()) as u64,
-            slice: self,
+        self.as_view_mut().try_slice_into(range)
+    }
+
+    /// A [CudaViewMut] of all of `self`.
+    pub fn as_view_mut(&mut self) -> CudaViewMut<'_, T> {
+        CudaViewMut {
+            ptr: self.cu_device_ptr,
+            len: self.len,
+            marker: PhantomData,
+        }
+    }
+
+    /// See [CudaViewMut::split_at_mut()].
+    pub fn split_at_mut(&mut self, mid: usize) -> (CudaViewMut<'_, T>, CudaViewMut<'_, T>) {
+        self.as_view_mut().split_at_mut(mid)
+    }
+
+    /// See [CudaViewMut::chunks_mut()].
+    pub fn chunks_mut(&mut self, chunk_size: usize) -> ChunksMut<'_, T> {
+        self.as_view_mut().chunks_mut(chunk_size)
+    }
+
+    /// See [CudaViewMut::reinterpret_mut()].
+    pub fn reinterpret_mut<U: ValidAsAnyBits>(&mut self) -> Option<CudaViewMut<'_, U>>
+    where
+        T: ValidAsAnyBits,
+    {
+        self.as_view_mut().reinterpret_mut()
+    }
+}
+
+impl<'a, T> CudaViewMut<'a, T> {
+    /// An immutable sub-view of `self`.
+    ///
+    /// # Panics
+    /// Like [CudaSlice::slice()].
+    pub fn slice(&self, range: impl RangeBounds<usize>) -> CudaView<'_, T> {
+        self.try_slice(range).unwrap()
+    }
+
+    /// Fallible version of [CudaViewMut::slice]
+    pub fn try_slice(&self, range: impl RangeBounds<usize>) -> Option<CudaView<'_, T>> {
+        self.as_view().try_slice(range)
+    }
+
+    /// A mutable sub-view of `self`.
+    ///
+    /// # Panics
+    /// Like [CudaSlice::slice_mut()].
+    pub fn slice_mut(&mut self, range: impl RangeBounds<usize>) -> CudaViewMut<'_, T> {
+        self.try_slice_mut(range).unwrap()
+    }
+
+    /// Fallible version of [CudaViewMut::slice_mut]
+    pub fn try_slice_mut(&mut self, range: impl RangeBounds<usize>) -> Option<CudaViewMut<'_, T>> {
+        self.reborrow().try_slice_into(range)
+    }
+
+    /// An immutable view of all of `self`.
+    pub fn as_view(&self) -> CudaView<'_, T> {
+        CudaView {
+            ptr: self.ptr,
+            len: self.len,
+            marker: PhantomData,
+        }
+    }
+
+    /// A shorter lived [CudaViewMut] of all of `self`, so `self` can be used again after it.
+    pub fn reborrow(&mut self) -> CudaViewMut<'_, T> {
+        CudaViewMut {
+            ptr: self.ptr,
+            len: self.len,
+            marker: PhantomData,
+        }
+    }
+
+    fn try_slice_into(self, range: impl RangeBounds<usize>) -> Option<CudaViewMut<'a, T>> {
+        range.bounds(..self.len).map(|(start, end)| CudaViewMut {
+            ptr: offset_ptr::<T>(self.ptr, start),
             len: 1 + end - start,
+            marker: PhantomData,
         })
     }
+
+    /// Splits into the disjoint views of `..mid` and `mid..`, either of which may be
+    /// empty. Both can be mutated at once, e.g. passed to the same kernel.
+    ///
+    /// ```rust
+    /// # use cudarc::driver::*;
+    /// let dev = CudaDevice::new(0).unwrap();
+    /// let mut a = dev.alloc_zeros::<f32>(10).unwrap();
+    /// let (mut left, mut right) = a.split_at_mut(4);
+    /// assert_eq!((left.len(), right.len()), (4, 6));
+    /// dev.fill(&mut left, 1.0).unwrap();
+    /// dev.fill(&mut right, 2.0).unwrap();
+    /// ```
+    ///
+    /// # Panics
+    /// If `mid > self.len()`.
+    pub fn split_at_mut(self, mid: usize) -> (CudaViewMut<'a, T>, CudaViewMut<'a, T>) {
+        assert!(mid <= self.len, "mid > len");
+        let (left, right) = split_ptr::<T>(self.ptr, self.len, mid);
+        (
+            CudaViewMut {
+                ptr: left.0,
+                len: left.1,
+                marker: PhantomData,
+            },
+            CudaViewMut {
+                ptr: right.0,
+                len: right.1,
+                marker: PhantomData,
+            },
+        )
+    }
+
+    /// An iterator over disjoint views of `chunk_size` elements, except for the last one
+    /// which has the remainder.
+    ///
+    /// ```rust
+    /// # use cudarc::driver::*;
+    /// let dev = CudaDevice::new(0).unwrap();
+    /// let mut a = dev.alloc_zeros::<u32>(10).unwrap();
+    /// for (i, mut chunk) in a.chunks_mut(4).enumerate() {
+    ///     dev.fill(&mut chunk, i as u32).unwrap();
+    /// }
+    /// assert_eq!(dev.dtoh_sync_copy(&a).unwrap(), [0, 0, 0, 0, 1, 1, 1, 1, 2, 2]);
+    /// ```
+    ///
+    /// # Panics
+    /// If `chunk_size` is 0.
+    pub fn chunks_mut(self, chunk_size: usize) -> ChunksMut<'a, T> {
+        assert!(chunk_size != 0, "chunk_size must not be 0");
+        ChunksMut {
+            ptr: self.ptr,
+            len: self.len,
+            chunk_size,
+            marker: PhantomData,
+        }
+    }
+
+    /// Views the same memory as elements of `U`, see [CudaView::reinterpret()].
+    pub fn reinterpret_mut<U: ValidAsAnyBits>(self) -> Option<CudaViewMut<'a, U>>
+    where
+        T: ValidAsAnyBits,
+    {
+        reinterpret_len::<T, U>(self.ptr, self.len).map(|len| CudaViewMut {
+            ptr: self.ptr,
+            len,
+            marker: PhantomData,
+        })
+    }
+}
+
+/// Iterator over [CudaView]s of a view, created by [CudaView::chunks()].
+pub struct Chunks<'a, T> {
+    ptr: sys::CUdeviceptr,
+    len: usize,
+    chunk_size: usize,
+    marker: PhantomData<&'a [T]>,
+}
+
+impl<'a, T> Iterator for Chunks<'a, T> {
+    type Item = CudaView<'a, T>;
+    fn next(&mut self) -> Option<Self::Item> {
+        let (chunk, rest) = next_chunk::<T>(self.ptr, self.len, self.chunk_size)?;
+        (self.ptr, self.len) = rest;
+        Some(CudaView {
+            ptr: chunk.0,
+            len: chunk.1,
+            marker: PhantomData,
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let n = self.len.div_ceil(self.chunk_size);
+        (n, Some(n))
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Chunks<'a, T> {}
+
+/// Iterator over disjoint [CudaViewMut]s of a view, created by [CudaViewMut::chunks_mut()].
+pub struct ChunksMut<'a, T> {
+    ptr: sys::CUdeviceptr,
+    len: usize,
+    chunk_size: usize,
+    marker: PhantomData<&'a mut [T]>,
+}
+
+impl<'a, T> Iterator for ChunksMut<'a, T> {
+    type Item = CudaViewMut<'a, T>;
+    fn next(&mut self) -> Option<Self::Item> {
+        let (chunk, rest) = next_chunk::<T>(self.ptr, self.len, self.chunk_size)?;
+        (self.ptr, self.len) = rest;
+        Some(CudaViewMut {
+            ptr: chunk.0,
+            len: chunk.1,
+            marker: PhantomData,
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let n = self.len.div_ceil(self.chunk_size);
+        (n, Some(n))
+    }
 }
 
-trait RangeHelper<T: PartialOrd> {
+impl<'a, T> ExactSizeIterator for ChunksMut<'a, T> {}
+
+/// `ptr` moved forward by `n` elements of `T`.
+fn offset_ptr<T>(ptr: sys::CUdeviceptr, n: usize) -> sys::CUdeviceptr {
+    ptr + (n * std::mem::size_of::<T>()) as u64
+}
+
+/// The (ptr, len) of `..mid` and `mid..` of `len` elements at `ptr`.
+#[allow(clippy::type_complexity)]
+fn split_ptr<T>(
+    ptr: sys::CUdeviceptr,
+    len: usize,
+    mid: usize,
+) -> ((sys::CUdeviceptr, usize), (sys::CUdeviceptr, usize)) {
+    ((ptr, mid), (offset_ptr::<T>(ptr, mid), len - mid))
+}
+
+/// The next chunk of at most `chunk_size` elements, and what's left after it.
+#[allow(clippy::type_complexity)]
+fn next_chunk<T>(
+    ptr: sys::CUdeviceptr,
+    len: usize,
+    chunk_size: usize,
+) -> Option<((sys::CUdeviceptr, usize), (sys::CUdeviceptr, usize))> {
+    (len > 0).then(|| split_ptr::<T>(ptr, len, chunk_size.min(len)))
+}
+
+/// How many `U`s the `len` `T`s at `ptr` are, if they're a whole number of aligned `U`s.
+fn reinterpret_len<T, U>(ptr: sys::CUdeviceptr, len: usize) -> Option<usize> {
+    let num_bytes = len * std::mem::size_of::<T>();
+    let size = std::mem::size_of::<U>();
+    let aligned = ptr.is_multiple_of(std::mem::align_of::<U>() as u64);
+    (size != 0 && num_bytes.is_multiple_of(size) && aligned).then(|| num_bytes / size)
+}
+
+pub(crate) trait RangeHelper<T: PartialOrd> {
     fn inclusive_start(&self, valid: &impl RangeBounds<T>) -> Option<T>;
     fn inclusive_end(&self, valid: &impl RangeBounds<T>) -> Option<T>;
     fn bounds(&self, valid: impl RangeBounds<T>) -> Option<(T, T)> {
@@ -368,4 +812,53 @@ mod tests {
         assert_eq!((2..=2usize).bounds(0..=1), None);
         assert_eq!((2..2usize).bounds(0..=usize::MAX), None);
     }
+
+    fn view_mut<'a, T>(ptr: sys::CUdeviceptr, len: usize) -> CudaViewMut<'a, T> {
+        CudaViewMut {
+            ptr,
+            len,
+            marker: PhantomData,
+        }
+    }
+
+    #[test]
+    fn test_split_and_slice_views() {
+        let (left, mut right) = view_mut::<f32>(256, 10).split_at_mut(4);
+        assert_eq!((left.ptr, left.len), (256, 4));
+        assert_eq!((right.ptr, right.len), (272, 6));
+        let sub = right.slice_mut(1..3);
+        assert_eq!((sub.ptr, sub.len), (276, 2));
+        let sub = right.as_view().slice(2..).slice(..=1);
+        assert_eq!((sub.ptr, sub.len), (280, 2));
+        assert!(right.try_slice(6..).is_none());
+
+        let (empty, all) = right.as_view().split_at(0);
+        assert_eq!((empty.len, all.ptr, all.len), (0, 272, 6));
+    }
+
+    #[test]
+    fn test_chunks_views() {
+        let chunks: Vec<_> = view_mut::<u16>(256, 7)
+            .chunks_mut(3)
+            .map(|c| (c.ptr, c.len))
+            .collect();
+        assert_eq!(chunks, [(256, 3), (262, 3), (268, 1)]);
+        assert_eq!(view_mut::<u16>(256, 6).as_view().chunks(3).len(), 2);
+        assert_eq!(view_mut::<u16>(256, 0).as_view().chunks(3).count(), 0);
+    }
+
+    #[test]
+    fn test_reinterpret_views() {
+        let bytes = view_mut::<u8>(256, 12);
+        assert_eq!(bytes.as_view().reinterpret::<u32>().map(|v| v.len), Some(3));
+        assert_eq!(
+            bytes.as_view().reinterpret::<[u16; 3]>().map(|v| v.len),
+            Some(2)
+        );
+        assert!(bytes.as_view().reinterpret::<u64>().is_none());
+        assert!(bytes.as_view().slice(2..10).reinterpret::<u32>().is_none());
+        assert!(bytes.as_view().reinterpret::<[u32; 0]>().is_none());
+        let halves = bytes.reinterpret_mut::<u16>().unwrap();
+        assert_eq!((halves.ptr, halves.len), (256, 6));
+    }
 }
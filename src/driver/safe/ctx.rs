@@ -0,0 +1,171 @@
+use crate::driver::{result, sys};
+
+use super::core::CudaDevice;
+
+use std::sync::Arc;
+
+/// A resource limit of the [CudaDevice]'s context. Used with [CudaDevice::get_limit()]
+/// and [CudaDevice::set_limit()].
+///
+/// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__CTX.html#group__CUDA__CTX_1g0651954dfb9788173e60a9af7201e65a)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ContextLimit {
+    /// Stack size in bytes of each GPU thread.
+    StackSize,
+
+    /// Size in bytes of the FIFO used by the `printf()` device system call.
+    PrintfFifoSize,
+
+    /// Size in bytes of the heap used by the `malloc()` and `free()` device system calls.
+    MallocHeapSize,
+
+    /// Maximum grid depth at which a thread can issue the device runtime call
+    /// `cudaDeviceSynchronize()` to wait on child grid launches to complete.
+    DevRuntimeSyncDepth,
+
+    /// Maximum number of outstanding device runtime launches that can be made from this context.
+    DevRuntimePendingLaunchCount,
+
+    /// L2 cache fetch granularity in bytes.
+    MaxL2FetchGranularity,
+
+    /// Persisting L2 cache size in bytes.
+    PersistingL2CacheSize,
+}
+
+impl From<ContextLimit> for sys::CUlimit {
+    fn from(value: ContextLimit) -> Self {
+        match value {
+            ContextLimit::StackSize => sys::CUlimit::CU_LIMIT_STACK_SIZE,
+            ContextLimit::PrintfFifoSize => sys::CUlimit::CU_LIMIT_PRINTF_FIFO_SIZE,
+            ContextLimit::MallocHeapSize => sys::CUlimit::CU_LIMIT_MALLOC_HEAP_SIZE,
+            ContextLimit::DevRuntimeSyncDepth => sys::CUlimit::CU_LIMIT_DEV_RUNTIME_SYNC_DEPTH,
+            ContextLimit::DevRuntimePendingLaunchCount => {
+                sys::CUlimit::CU_LIMIT_DEV_RUNTIME_PENDING_LAUNCH_COUNT
+            }
+            ContextLimit::MaxL2FetchGranularity => sys::CUlimit::CU_LIMIT_MAX_L2_FETCH_GRANULARITY,
+            ContextLimit::PersistingL2CacheSize => sys::CUlimit::CU_LIMIT_PERSISTING_L2_CACHE_SIZE,
+        }
+    }
+}
+
+/// Preference between shared memory and L1 cache. Used with [CudaDevice::set_cache_config()].
+///
+/// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__CTX.html#group__CUDA__CTX_1g54699acf7e2ef27279d013ca2095f4a3)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CacheConfig {
+    /// No preference for shared memory or L1 (default)
+    PreferNone,
+
+    /// Prefer larger shared memory and smaller L1 cache
+    PreferShared,
+
+    /// Prefer larger L1 cache and smaller shared memory
+    PreferL1,
+
+    /// Prefer equal sized L1 cache and shared memory
+    PreferEqual,
+}
+
+impl From<CacheConfig> for sys::CUfunc_cache {
+    fn from(value: CacheConfig) -> Self {
+        match value {
+            CacheConfig::PreferNone => sys::CUfunc_cache::CU_FUNC_CACHE_PREFER_NONE,
+            CacheConfig::PreferShared => sys::CUfunc_cache::CU_FUNC_CACHE_PREFER_SHARED,
+            CacheConfig::PreferL1 => sys::CUfunc_cache::CU_FUNC_CACHE_PREFER_L1,
+            CacheConfig::PreferEqual => sys::CUfunc_cache::CU_FUNC_CACHE_PREFER_EQUAL,
+        }
+    }
+}
+
+impl From<sys::CUfunc_cache> for CacheConfig {
+    fn from(value: sys::CUfunc_cache) -> Self {
+        match value {
+            sys::CUfunc_cache::CU_FUNC_CACHE_PREFER_NONE => CacheConfig::PreferNone,
+            sys::CUfunc_cache::CU_FUNC_CACHE_PREFER_SHARED => CacheConfig::PreferShared,
+            sys::CUfunc_cache::CU_FUNC_CACHE_PREFER_L1 => CacheConfig::PreferL1,
+            sys::CUfunc_cache::CU_FUNC_CACHE_PREFER_EQUAL => CacheConfig::PreferEqual,
+        }
+    }
+}
+
+impl CudaDevice {
+    /// Binds the device's primary context to the calling thread. All
+    /// `cuCtx*` functions act on the current context, so this is called
+    /// before each of them.
+    pub(crate) fn bind_to_thread(&self) -> Result<(), result::DriverError> {
+        unsafe { result::ctx::set_current(self.cu_primary_ctx) }
+    }
+
+    /// Returns the current value of `limit` for this device's context.
+    ///
+    /// ```rust
+    /// # use cudarc::driver::*;
+    /// let dev = CudaDevice::new(0).unwrap();
+    /// let heap_size = dev.get_limit(ContextLimit::MallocHeapSize).unwrap();
+    /// ```
+    pub fn get_limit(self: &Arc<Self>, limit: ContextLimit) -> Result<usize, result::DriverError> {
+        self.bind_to_thread()?;
+        result::ctx::get_limit(limit.into())
+    }
+
+    /// Sets `limit` to `value` for this device's context. The driver may round `value`
+    /// up to a supported size; use [CudaDevice::get_limit()] to read the actual value back.
+    ///
+    /// ```rust
+    /// # use cudarc::driver::*;
+    /// let dev = CudaDevice::new(0).unwrap();
+    /// dev.set_limit(ContextLimit::PrintfFifoSize, 8 << 20).unwrap();
+    /// ```
+    pub fn set_limit(
+        self: &Arc<Self>,
+        limit: ContextLimit,
+        value: usize,
+    ) -> Result<(), result::DriverError> {
+        self.bind_to_thread()?;
+        result::ctx::set_limit(limit.into(), value)
+    }
+
+    /// Returns the preferred cache configuration of this device's context.
+    pub fn get_cache_config(self: &Arc<Self>) -> Result<CacheConfig, result::DriverError> {
+        self.bind_to_thread()?;
+        result::ctx::get_cache_config().map(Into::into)
+    }
+
+    /// Sets the preferred cache configuration of this device's context. This is only
+    /// a preference, the driver may choose a different configuration if required to
+    /// execute a kernel.
+    pub fn set_cache_config(
+        self: &Arc<Self>,
+        config: CacheConfig,
+    ) -> Result<(), result::DriverError> {
+        self.bind_to_thread()?;
+        result::ctx::set_cache_config(config.into())
+    }
+
+    /// Resets all persisting lines in the L2 cache of this device's context to normal status.
+    pub fn reset_persisting_l2_cache(self: &Arc<Self>) -> Result<(), result::DriverError> {
+        self.bind_to_thread()?;
+        result::ctx::reset_persisting_l2_cache()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set_get_malloc_heap_size() {
+        let dev = CudaDevice::new(0).unwrap();
+        dev.set_limit(ContextLimit::MallocHeapSize, 16 << 20)
+            .unwrap();
+        assert!(dev.get_limit(ContextLimit::MallocHeapSize).unwrap() >= 16 << 20);
+    }
+
+    #[test]
+    fn test_set_get_cache_config() {
+        let dev = CudaDevice::new(0).unwrap();
+        dev.set_cache_config(CacheConfig::PreferNone).unwrap();
+        assert_eq!(dev.get_cache_config().unwrap(), CacheConfig::PreferNone);
+    }
+}
@@ -0,0 +1,451 @@
+//! Generated elementwise kernels, see [Elementwise] and [CudaDevice::elementwise()].
+
+use crate::nvrtc::{cache::CacheKey, includes::cuda_include_paths, CompileOptions};
+
+use super::alloc::DeviceRepr;
+use super::core::{CudaDevice, CudaFunction};
+use super::device_ptr::{DevicePtr, DevicePtrMut};
+use super::launch::{KernelArgs, LaunchAsync, LaunchConfig};
+use super::ptx::CompileAndLoadError;
+
+use std::{
+    format,
+    string::{String, ToString},
+    sync::Arc,
+    vec::Vec,
+};
+
+/// A type that kernels can be generated for, see [Elementwise].
+pub trait DType: DeviceRepr + Copy {
+    /// The name of the type in cuda source, e.g. `float`
+    const C_TYPE: &'static str;
+    /// The header that declares the type, if it isn't builtin
+    const HEADER: Option<&'static str> = None;
+}
+
+macro_rules! dtype {
+    ($($ty:ty => $c_type:literal),* $(,)?) => {
+        $(impl DType for $ty {
+            const C_TYPE: &'static str = $c_type;
+        })*
+    };
+}
+
+dtype!(
+    f32 => "float",
+    f64 => "double",
+    i8 => "signed char",
+    i16 => "short",
+    i32 => "int",
+    i64 => "long long",
+    u8 => "unsigned char",
+    u16 => "unsigned short",
+    u32 => "unsigned int",
+    u64 => "unsigned long long",
+);
+
+#[cfg(feature = "f16")]
+impl DType for half::f16 {
+    const C_TYPE: &'static str = "__half";
+    const HEADER: Option<&'static str> = Some("cuda_fp16.h");
+}
+
+#[cfg(feature = "f16")]
+impl DType for half::bf16 {
+    const C_TYPE: &'static str = "__nv_bfloat16";
+    const HEADER: Option<&'static str> = Some("cuda_bf16.h");
+}
+
+/// The name of the kernel in [Elementwise::src()].
+pub const ELEMENTWISE_KERNEL: &str = "elementwise";
+
+/// The source of a kernel that computes `out[i] = expr` for every `i`, where `expr` is a
+/// C expression of the inputs. The inputs are named `a`, `b`, `c`, ... in order (skipping
+/// `i`, see [Elementwise::input_name()]), and `i` is the index into `out`.
+///
+/// The kernel is named [ELEMENTWISE_KERNEL], and takes these parameters:
+/// 1. `const size_t numel`, the length of `out`
+/// 2. `T *out`
+/// 3. `const T *a_ptr`, `const T *b_ptr`, ... for each input
+/// 4. if [Elementwise::strided] is `Some(rank)`: `const size_t d0`, ... the shape of `out`,
+///    then `a_s0`, ..., `b_s0`, ... the strides of each input, in elements. A stride of 0
+///    broadcasts the input along that dimension.
+///
+/// Use [CudaDevice::elementwise()] or [CudaDevice::elementwise_strided()] to run one, or
+/// [CudaDevice::elementwise_func()] to launch it yourself.
+///
+/// ```rust
+/// # use cudarc::driver::*;
+/// let kernel = Elementwise::new::<f32>("a * b + c", 3);
+/// assert!(kernel.src().contains("out[i] = (a * b + c);"));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Elementwise {
+    /// The type of the inputs & output, see [DType::C_TYPE]
+    pub c_type: &'static str,
+    /// Included at the top of the source, see [DType::HEADER]
+    pub header: Option<&'static str>,
+    pub expr: String,
+    /// At most 25
+    pub num_inputs: usize,
+    /// `Some(rank)` to index the inputs with strides over a `rank` dimensional `out`,
+    /// `None` if they are contiguous.
+    pub strided: Option<usize>,
+}
+
+impl Elementwise {
+    /// A kernel for contiguous inputs of type `T`.
+    pub fn new<T: DType>(expr: impl Into<String>, num_inputs: usize) -> Self {
+        assert!(num_inputs <= 25, "at most 25 inputs are supported");
+        Self {
+            c_type: T::C_TYPE,
+            header: T::HEADER,
+            expr: expr.into(),
+            num_inputs,
+            strided: None,
+        }
+    }
+
+    /// Indexes the inputs with strides over a `rank` dimensional output instead.
+    pub fn strided(self, rank: usize) -> Self {
+        Self {
+            strided: Some(rank),
+            ..self
+        }
+    }
+
+    /// The name of input `i` in `expr`: `a`, `b`, ..., `h`, `j`, ..., `z`. `i` is skipped
+    /// since it's the index into `out`.
+    pub fn input_name(i: usize) -> char {
+        let name = b'a' + i as u8;
+        match name < b'i' {
+            true => name as char,
+            false => (name + 1) as char,
+        }
+    }
+
+    /// The module this is loaded into by [CudaDevice::elementwise_func()]. Unique to the source.
+    pub fn module_name(&self) -> String {
+        generated_module_name(ELEMENTWISE_KERNEL, &self.src())
+    }
+
+    /// Generates the cuda source of the kernel.
+    pub fn src(&self) -> String {
+        let ty = self.c_type;
+        let names: Vec<char> = (0..self.num_inputs).map(Self::input_name).collect();
+
+        let mut params = std::vec!["const size_t numel".to_string(), format!("{ty} *out")];
+        params.extend(names.iter().map(|n| format!("const {ty} *{n}_ptr")));
+        if let Some(rank) = self.strided {
+            params.extend((0..rank).map(|d| format!("const size_t d{d}")));
+            for n in names.iter() {
+                params.extend((0..rank).map(|d| format!("const size_t {n}_s{d}")));
+            }
+        }
+
+        let mut body = String::new();
+        match self.strided {
+            None => {
+                for n in names.iter() {
+                    body.push_str(&format!("        const {ty} {n} = {n}_ptr[i];\n"));
+                }
+            }
+            Some(rank) => {
+                body.push_str("        size_t rem = i;\n");
+                for d in (0..rank).rev() {
+                    body.push_str(&format!(
+                        "        const size_t i{d} = rem % d{d}; rem /= d{d};\n"
+                    ));
+                }
+                for n in names.iter() {
+                    let offset: Vec<String> =
+                        (0..rank).map(|d| format!("i{d} * {n}_s{d}")).collect();
+                    let offset = if offset.is_empty() {
+                        "0".to_string()
+                    } else {
+                        offset.join(" + ")
+                    };
+                    body.push_str(&format!("        const {ty} {n} = {n}_ptr[{offset}];\n"));
+                }
+            }
+        }
+
+        body.push_str(&format!("        out[i] = ({});\n", self.expr));
+
+        let prelude = includes(&[self.header]);
+        grid_stride_src(&prelude, ELEMENTWISE_KERNEL, &params, &body)
+    }
+}
+
+/// `#include`s for each of `headers` that is `Some`, followed by a blank line.
+pub(crate) fn includes(headers: &[Option<&str>]) -> String {
+    let mut prelude = String::new();
+    for header in headers.iter().flatten() {
+        prelude.push_str(&format!("#include \"{header}\"\n"));
+    }
+    if !prelude.is_empty() {
+        prelude.push('\n');
+    }
+    prelude
+}
+
+/// The source of a kernel named `name` that runs `body` for each `i` in `0..numel` with a
+/// grid-stride loop. `params` must declare `numel`, and `body` is indented by 8 spaces.
+pub(crate) fn grid_stride_src(prelude: &str, name: &str, params: &[String], body: &str) -> String {
+    format!(
+        "{prelude}extern \"C\" __global__ void {name}({params}) {{
+    for (size_t i = (size_t)blockIdx.x * blockDim.x + threadIdx.x; i < numel; i += (size_t)blockDim.x * gridDim.x) {{
+{body}    }}
+}}
+",
+        params = params.join(", "),
+    )
+}
+
+/// The name of the module a generated kernel is loaded into, unique to its source.
+pub(crate) fn generated_module_name(kind: &str, src: &str) -> String {
+    let key = CacheKey::new::<&str>(kind, src, &[], (0, 0));
+    format!("cudarc_{kind}_{key}")
+}
+
+/// A launch config for a kernel that loops over `n` elements with a grid-stride loop.
+pub(crate) fn grid_stride_cfg(n: usize) -> LaunchConfig {
+    const NUM_THREADS: usize = 256;
+    const MAX_BLOCKS: usize = 65535;
+    let num_blocks = n.div_ceil(NUM_THREADS).clamp(1, MAX_BLOCKS);
+    LaunchConfig {
+        grid_dim: (num_blocks as u32, 1, 1),
+        block_dim: (NUM_THREADS as u32, 1, 1),
+        shared_mem_bytes: 0,
+    }
+}
+
+impl CudaDevice {
+    /// Compiles & loads `kernel`, or returns the already loaded function if it was before,
+    /// so each kernel is only compiled once per device.
+    pub fn elementwise_func(
+        self: &Arc<Self>,
+        kernel: &Elementwise,
+    ) -> Result<CudaFunction, CompileAndLoadError> {
+        self.generated_func(ELEMENTWISE_KERNEL, kernel.src(), kernel.header.is_some())
+    }
+
+    /// Compiles & loads the generated kernel `name` from `src` into a module named by
+    /// [generated_module_name()], unless it's already loaded. If `needs_cuda_headers`,
+    /// nvrtc is pointed at the cuda toolkit's headers.
+    pub(crate) fn generated_func(
+        self: &Arc<Self>,
+        name: &str,
+        src: String,
+        needs_cuda_headers: bool,
+    ) -> Result<CudaFunction, CompileAndLoadError> {
+        let module_name = generated_module_name(name, &src);
+        if let Some(func) = self.get_func(&module_name, name) {
+            return Ok(func);
+        }
+        let opts = CompileOptions {
+            include_paths: match needs_cuda_headers {
+                true => cuda_include_paths(),
+                false => Vec::new(),
+            },
+            ..Default::default()
+        };
+        let module = self.compile_and_load_with_opts(src, opts, module_name, &[name])?;
+        Ok(module.get_func(name).unwrap())
+    }
+
+    /// Computes `out[i] = expr` for every `i`, where `expr` is a C expression of the inputs
+    /// named `a`, `b`, ... (see [Elementwise]). The kernel is compiled on first use.
+    ///
+    /// ```rust
+    /// # use cudarc::driver::*;
+    /// let dev = CudaDevice::new(0).unwrap();
+    /// let a = dev.htod_copy(vec![1.0f32, 2.0, 3.0]).unwrap();
+    /// let b = dev.htod_copy(vec![4.0f32, 5.0, 6.0]).unwrap();
+    /// let mut out = dev.alloc_zeros::<f32>(3).unwrap();
+    /// unsafe { dev.elementwise("a * b + 1.0f", &[&a, &b], &mut out) }.unwrap();
+    /// assert_eq!(dev.dtoh_sync_copy(&out).unwrap(), [5.0, 11.0, 19.0]);
+    /// ```
+    ///
+    /// # Safety
+    /// `expr` is inserted into the kernel as is, so it must only read the inputs it's given.
+    ///
+    /// # Panics
+    /// If an input's length is different from `out`'s.
+    pub unsafe fn elementwise<T: DType>(
+        self: &Arc<Self>,
+        expr: &str,
+        inputs: &[&dyn DevicePtr<T>],
+        out: &mut dyn DevicePtrMut<T>,
+    ) -> Result<(), CompileAndLoadError> {
+        for input in inputs {
+            assert_eq!(input.len(), out.len());
+        }
+        let func = self.elementwise_func(&Elementwise::new::<T>(expr, inputs.len()))?;
+        let numel = out.len();
+        if numel == 0 {
+            return Ok(());
+        }
+        let mut args = KernelArgs::new().arg(numel).arg(*out.device_ptr_mut());
+        for input in inputs {
+            args.push(*input.device_ptr());
+        }
+        func.launch(grid_stride_cfg(numel), args)?;
+        Ok(())
+    }
+
+    /// Like [CudaDevice::elementwise()], but `out` has `shape` and each input is read with
+    /// its own strides (in elements), one per dimension of `shape`. A stride of 0 broadcasts
+    /// the input along that dimension.
+    ///
+    /// ```rust
+    /// # use cudarc::driver::*;
+    /// let dev = CudaDevice::new(0).unwrap();
+    /// let a = dev.htod_copy(vec![1.0f32, 2.0, 3.0, 4.0, 5.0, 6.0]).unwrap();
+    /// let b = dev.htod_copy(vec![10.0f32, 20.0]).unwrap();
+    /// let mut out = dev.alloc_zeros::<f32>(6).unwrap();
+    /// // out is 2x3, `a` is 3x2 read transposed, and `b` is a column broadcast along the rows
+    /// let inputs: [(&dyn DevicePtr<f32>, &[usize]); 2] = [(&a, &[1, 2]), (&b, &[1, 0])];
+    /// unsafe { dev.elementwise_strided("a + b", &[2, 3], &inputs, &mut out) }.unwrap();
+    /// assert_eq!(
+    ///     dev.dtoh_sync_copy(&out).unwrap(),
+    ///     [11.0, 13.0, 15.0, 22.0, 24.0, 26.0]
+    /// );
+    /// ```
+    ///
+    /// # Safety
+    /// `expr` is inserted into the kernel as is, so it must only read the inputs it's given.
+    ///
+    /// # Panics
+    /// If `out`'s length isn't the number of elements in `shape`, or an input's strides reach
+    /// past its end.
+    pub unsafe fn elementwise_strided<T: DType>(
+        self: &Arc<Self>,
+        expr: &str,
+        shape: &[usize],
+        inputs: &[(&dyn DevicePtr<T>, &[usize])],
+        out: &mut dyn DevicePtrMut<T>,
+    ) -> Result<(), CompileAndLoadError> {
+        let numel: usize = shape.iter().product();
+        assert_eq!(numel, out.len());
+        if numel > 0 {
+            for (input, strides) in inputs {
+                assert_eq!(strides.len(), shape.len());
+                let last: usize = shape.iter().zip(*strides).map(|(d, s)| (d - 1) * s).sum();
+                assert!(last < input.len(), "strides reach past the end of an input");
+            }
+        }
+
+        let kernel = Elementwise::new::<T>(expr, inputs.len()).strided(shape.len());
+        let func = self.elementwise_func(&kernel)?;
+        if numel == 0 {
+            return Ok(());
+        }
+        let mut args = KernelArgs::new().arg(numel).arg(*out.device_ptr_mut());
+        for (input, _) in inputs {
+            args.push(*input.device_ptr());
+        }
+        for &d in shape {
+            args.push(d);
+        }
+        for (_, strides) in inputs {
+            for &s in strides.iter() {
+                args.push(s);
+            }
+        }
+        func.launch(grid_stride_cfg(numel), args)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_elementwise_src() {
+        let kernel = Elementwise::new::<f32>("a * b", 2);
+        assert_eq!(
+            kernel.src(),
+            "extern \"C\" __global__ void elementwise(const size_t numel, float *out, const float *a_ptr, const float *b_ptr) {
+    for (size_t i = (size_t)blockIdx.x * blockDim.x + threadIdx.x; i < numel; i += (size_t)blockDim.x * gridDim.x) {
+        const float a = a_ptr[i];
+        const float b = b_ptr[i];
+        out[i] = (a * b);
+    }
+}
+"
+        );
+    }
+
+    #[test]
+    fn test_elementwise_strided_src() {
+        let kernel = Elementwise::new::<i32>("a + b", 2).strided(2);
+        assert_eq!(
+            kernel.src(),
+            "extern \"C\" __global__ void elementwise(const size_t numel, int *out, const int *a_ptr, const int *b_ptr, const size_t d0, const size_t d1, const size_t a_s0, const size_t a_s1, const size_t b_s0, const size_t b_s1) {
+    for (size_t i = (size_t)blockIdx.x * blockDim.x + threadIdx.x; i < numel; i += (size_t)blockDim.x * gridDim.x) {
+        size_t rem = i;
+        const size_t i1 = rem % d1; rem /= d1;
+        const size_t i0 = rem % d0; rem /= d0;
+        const int a = a_ptr[i0 * a_s0 + i1 * a_s1];
+        const int b = b_ptr[i0 * b_s0 + i1 * b_s1];
+        out[i] = (a + b);
+    }
+}
+"
+        );
+    }
+
+    #[test]
+    fn test_elementwise_many_inputs_src() {
+        let kernel = Elementwise::new::<f32>("h + j", 9);
+        let src = kernel.src();
+        assert!(src.contains("const float *h_ptr, const float *j_ptr)"));
+        assert!(src.contains("        const float j = j_ptr[i];\n"));
+        assert!(!src.contains("i_ptr"));
+        assert_eq!(Elementwise::input_name(24), 'z');
+    }
+
+    #[cfg(feature = "f16")]
+    #[test]
+    fn test_elementwise_half_src() {
+        let src = Elementwise::new::<half::bf16>("a", 1).src();
+        assert!(src.starts_with("#include \"cuda_bf16.h\"\n\n"));
+        assert!(src.contains("__nv_bfloat16 *out"));
+    }
+
+    #[test]
+    fn test_elementwise_module_name() {
+        let a = Elementwise::new::<f32>("a * b", 2);
+        assert_eq!(a.module_name(), a.clone().module_name());
+        assert_ne!(
+            a.module_name(),
+            Elementwise::new::<f64>("a * b", 2).module_name()
+        );
+        assert_ne!(a.module_name(), a.clone().strided(1).module_name());
+    }
+
+    #[test]
+    fn test_elementwise_cached() {
+        let dev = CudaDevice::new(0).unwrap();
+        let a = dev.htod_copy(std::vec![1u32, 2, 3]).unwrap();
+        let mut out = dev.alloc_zeros::<u32>(3).unwrap();
+        unsafe { dev.elementwise("a * a + i", &[&a], &mut out) }.unwrap();
+        assert_eq!(dev.dtoh_sync_copy(&out).unwrap(), [1, 5, 11]);
+
+        let num_modules = dev.module_names().len();
+        unsafe { dev.elementwise("a * a + i", &[&a], &mut out) }.unwrap();
+        assert_eq!(dev.module_names().len(), num_modules);
+    }
+
+    #[test]
+    fn test_elementwise_large() {
+        let dev = CudaDevice::new(0).unwrap();
+        let n = 65535 * 256 + 7;
+        let a = dev.htod_copy(std::vec![1.0f64; n]).unwrap();
+        let mut out = dev.alloc_zeros::<f64>(n).unwrap();
+        unsafe { dev.elementwise("a * 2.0", &[&a], &mut out) }.unwrap();
+        assert!(dev.dtoh_sync_copy(&out).unwrap().iter().all(|&x| x == 2.0));
+    }
+}
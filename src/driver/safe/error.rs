@@ -0,0 +1,150 @@
+use crate::driver::result;
+
+use super::core::CudaDevice;
+
+#[cfg(feature = "no-std")]
+use spin::RwLock;
+#[cfg(not(feature = "no-std"))]
+use std::sync::RwLock;
+
+/// An error that could not be returned to the caller when it happened, e.g.
+/// one from freeing memory in a [Drop] impl.
+///
+/// These are recorded on the [CudaDevice] the resource belonged to (see
+/// [CudaDevice::last_error()] and [CudaDevice::check_error()]), and passed to the hook set
+/// with [set_deferred_error_hook()]. If no hook is set they are printed to stderr.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeferredError {
+    Driver(result::DriverError),
+    Nvrtc(crate::nvrtc::result::NvrtcError),
+    #[cfg(feature = "cublas")]
+    Cublas(crate::cublas::result::CublasError),
+    #[cfg(feature = "curand")]
+    Curand(crate::curand::result::CurandError),
+}
+
+impl From<result::DriverError> for DeferredError {
+    fn from(value: result::DriverError) -> Self {
+        Self::Driver(value)
+    }
+}
+
+impl From<crate::nvrtc::result::NvrtcError> for DeferredError {
+    fn from(value: crate::nvrtc::result::NvrtcError) -> Self {
+        Self::Nvrtc(value)
+    }
+}
+
+#[cfg(feature = "cublas")]
+impl From<crate::cublas::result::CublasError> for DeferredError {
+    fn from(value: crate::cublas::result::CublasError) -> Self {
+        Self::Cublas(value)
+    }
+}
+
+#[cfg(feature = "curand")]
+impl From<crate::curand::result::CurandError> for DeferredError {
+    fn from(value: crate::curand::result::CurandError) -> Self {
+        Self::Curand(value)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::fmt::Display for DeferredError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for DeferredError {}
+
+static HOOK: RwLock<Option<fn(DeferredError)>> = RwLock::new(None);
+
+/// Sets a function to call with every [DeferredError], replacing the previous one.
+/// Pass `None` to go back to printing them to stderr.
+///
+/// The hook is called from [Drop] impls, so it must not panic.
+pub fn set_deferred_error_hook(hook: Option<fn(DeferredError)>) {
+    #[cfg(not(feature = "no-std"))]
+    let mut lock = HOOK.write().unwrap_or_else(|e| e.into_inner());
+    #[cfg(feature = "no-std")]
+    let mut lock = HOOK.write();
+    *lock = hook;
+}
+
+/// Records `err` on `device` (if there is one), and reports it to the hook.
+pub(crate) fn defer_error<E: Into<DeferredError>>(device: Option<&CudaDevice>, err: E) {
+    let err = err.into();
+
+    if let Some(device) = device {
+        #[cfg(not(feature = "no-std"))]
+        let mut last_error = device.last_error.write().unwrap_or_else(|e| e.into_inner());
+        #[cfg(feature = "no-std")]
+        let mut last_error = device.last_error.write();
+        *last_error = Some(err);
+    }
+
+    let hook = HOOK.read();
+    #[cfg(not(feature = "no-std"))]
+    let hook = hook.unwrap_or_else(|e| e.into_inner());
+    match *hook {
+        Some(hook) => hook(err),
+        #[cfg(not(feature = "no-std"))]
+        None => std::eprintln!("cudarc: error in drop: {err:?}"),
+        #[cfg(feature = "no-std")]
+        None => {}
+    }
+}
+
+impl CudaDevice {
+    /// Returns the most recent [DeferredError] recorded for this device, without clearing it.
+    pub fn last_error(&self) -> Option<DeferredError> {
+        let last_error = self.last_error.read();
+        #[cfg(not(feature = "no-std"))]
+        let last_error = last_error.unwrap_or_else(|e| e.into_inner());
+        *last_error
+    }
+
+    /// Returns and clears the most recent [DeferredError] recorded for this device. If there
+    /// is none, checks the device's stream for an error from previously launched work (e.g. a
+    /// kernel fault). Does not block on that work completing.
+    ///
+    /// ```rust
+    /// # use cudarc::driver::*;
+    /// let dev = CudaDevice::new(0).unwrap();
+    /// let a = dev.alloc_zeros::<f32>(10).unwrap();
+    /// drop(a);
+    /// dev.check_error().unwrap();
+    /// ```
+    pub fn check_error(&self) -> Result<(), DeferredError> {
+        #[cfg(not(feature = "no-std"))]
+        let mut last_error = self.last_error.write().unwrap_or_else(|e| e.into_inner());
+        #[cfg(feature = "no-std")]
+        let mut last_error = self.last_error.write();
+        if let Some(err) = last_error.take() {
+            return Err(err);
+        }
+        self.bind_to_thread()?;
+        unsafe { result::stream::query(self.stream) }?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_error_clears_last_error() {
+        let dev = CudaDevice::new(0).unwrap();
+        dev.check_error().unwrap();
+        assert_eq!(dev.last_error(), None);
+
+        let err = result::DriverError(crate::driver::sys::CUresult::CUDA_ERROR_INVALID_VALUE);
+        defer_error(Some(&dev), err);
+        assert_eq!(dev.last_error(), Some(DeferredError::Driver(err)));
+        assert_eq!(dev.check_error(), Err(DeferredError::Driver(err)));
+        assert_eq!(dev.last_error(), None);
+    }
+}
@@ -0,0 +1,287 @@
+use crate::driver::{result, sys};
+
+use super::core::{CudaDevice, CudaStream, CudaView, CudaViewMut};
+use super::error::defer_error;
+
+use core::marker::PhantomData;
+use core::ops::Range;
+use std::fs::File;
+use std::os::unix::io::{AsRawFd, IntoRawFd};
+use std::sync::Arc;
+
+/// An external memory object (e.g. a Vulkan or OpenGL allocation) imported
+/// with [CudaDevice::import_external_memory()].
+///
+/// Use [ExternalMemory::map_all()] or [ExternalMemory::map_range()] to access
+/// it as device memory. The memory object is destroyed on [Drop].
+#[derive(Debug)]
+pub struct ExternalMemory {
+    external_memory: sys::CUexternalMemory,
+    size: u64,
+    device: Arc<CudaDevice>,
+}
+
+unsafe impl Send for ExternalMemory {}
+unsafe impl Sync for ExternalMemory {}
+
+/// A buffer mapped onto an [ExternalMemory]. Access its bytes with
+/// [MappedBuffer::as_view()] and [MappedBuffer::as_view_mut()], which can be used like
+/// any other [CudaView]/[CudaViewMut], e.g. passed to kernels as a `uint8_t *`.
+///
+/// The buffer is freed on [Drop], followed by the [ExternalMemory] it was mapped from.
+#[derive(Debug)]
+pub struct MappedBuffer {
+    pub(crate) device_ptr: sys::CUdeviceptr,
+    pub(crate) len: usize,
+    external_memory: ExternalMemory,
+}
+
+/// An external semaphore (e.g. a Vulkan semaphore) imported with
+/// [CudaDevice::import_external_semaphore()].
+///
+/// Signal or wait on it with [CudaStream::signal_external_semaphore()] and
+/// [CudaStream::wait_external_semaphore()]. The semaphore is destroyed on [Drop].
+#[derive(Debug)]
+pub struct ExternalSemaphore {
+    semaphore: sys::CUexternalSemaphore,
+    device: Arc<CudaDevice>,
+}
+
+unsafe impl Send for ExternalSemaphore {}
+unsafe impl Sync for ExternalSemaphore {}
+
+/// The kind of semaphore exported by the other API.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ExternalSemaphoreKind {
+    /// A binary semaphore, e.g. a `VkSemaphore` exported with
+    /// `VK_EXTERNAL_SEMAPHORE_HANDLE_TYPE_OPAQUE_FD_BIT`.
+    OpaqueFd,
+
+    /// A timeline semaphore, e.g. a `VkSemaphore` of type
+    /// `VK_SEMAPHORE_TYPE_TIMELINE` exported as an opaque fd.
+    TimelineSemaphoreFd,
+}
+
+impl From<ExternalSemaphoreKind> for sys::CUexternalSemaphoreHandleType {
+    fn from(value: ExternalSemaphoreKind) -> Self {
+        match value {
+            ExternalSemaphoreKind::OpaqueFd => {
+                sys::CUexternalSemaphoreHandleType::CU_EXTERNAL_SEMAPHORE_HANDLE_TYPE_OPAQUE_FD
+            }
+            ExternalSemaphoreKind::TimelineSemaphoreFd => {
+                sys::CUexternalSemaphoreHandleType::CU_EXTERNAL_SEMAPHORE_HANDLE_TYPE_TIMELINE_SEMAPHORE_FD
+            }
+        }
+    }
+}
+
+impl CudaDevice {
+    /// Imports `size` bytes of external memory from an opaque file descriptor, such as one
+    /// returned by `vkGetMemoryFdKHR`.
+    ///
+    /// On success, ownership of `file` is transferred to the driver. On failure `file`
+    /// is closed.
+    ///
+    /// # Safety
+    /// 1. `file` must be an exported memory object of at least `size` bytes.
+    /// 2. The other API may read and write the memory concurrently to this device, so all
+    ///    accesses must be synchronized (e.g. with [ExternalSemaphore]).
+    pub unsafe fn import_external_memory(
+        self: &Arc<Self>,
+        file: File,
+        size: u64,
+    ) -> Result<ExternalMemory, result::DriverError> {
+        self.bind_to_thread()?;
+        let external_memory = result::external_memory::import_opaque_fd(file.as_raw_fd(), size)?;
+        let _ = file.into_raw_fd();
+        Ok(ExternalMemory {
+            external_memory,
+            size,
+            device: self.clone(),
+        })
+    }
+
+    /// Imports an external semaphore of `kind` from a file descriptor, such as one returned
+    /// by `vkGetSemaphoreFdKHR`.
+    ///
+    /// On success, ownership of `file` is transferred to the driver. On failure `file`
+    /// is closed.
+    ///
+    /// # Safety
+    /// `file` must be an exported semaphore of type `kind`.
+    pub unsafe fn import_external_semaphore(
+        self: &Arc<Self>,
+        file: File,
+        kind: ExternalSemaphoreKind,
+    ) -> Result<ExternalSemaphore, result::DriverError> {
+        self.bind_to_thread()?;
+        let semaphore = result::external_semaphore::import_fd(file.as_raw_fd(), kind.into())?;
+        let _ = file.into_raw_fd();
+        Ok(ExternalSemaphore {
+            semaphore,
+            device: self.clone(),
+        })
+    }
+}
+
+impl ExternalMemory {
+    /// The size in bytes of the memory object.
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// Maps the entire memory object as a [MappedBuffer].
+    pub fn map_all(self) -> Result<MappedBuffer, result::DriverError> {
+        let size = usize::try_from(self.size)
+            .map_err(|_| result::DriverError(sys::CUresult::CUDA_ERROR_INVALID_VALUE))?;
+        self.map_range(0..size)
+    }
+
+    /// Maps the bytes in `range` of the memory object as a [MappedBuffer].
+    ///
+    /// Returns `CUDA_ERROR_INVALID_VALUE` if `range` is not within [ExternalMemory::size()].
+    pub fn map_range(self, range: Range<usize>) -> Result<MappedBuffer, result::DriverError> {
+        if !in_bounds(&range, self.size) {
+            return Err(result::DriverError(sys::CUresult::CUDA_ERROR_INVALID_VALUE));
+        }
+        let device_ptr = unsafe {
+            result::external_memory::get_mapped_buffer(
+                self.external_memory,
+                range.start as u64,
+                range.len() as u64,
+            )
+        }?;
+        Ok(MappedBuffer {
+            device_ptr,
+            len: range.len(),
+            external_memory: self,
+        })
+    }
+}
+
+fn in_bounds(range: &Range<usize>, size: u64) -> bool {
+    range.start <= range.end && u64::try_from(range.end).is_ok_and(|end| end <= size)
+}
+
+impl MappedBuffer {
+    /// The length in bytes of the buffer.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether the buffer is 0 bytes long.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The buffer's bytes as a [CudaView].
+    pub fn as_view(&self) -> CudaView<'_, u8> {
+        CudaView {
+            ptr: self.device_ptr,
+            len: self.len,
+            marker: PhantomData,
+        }
+    }
+
+    /// The buffer's bytes as a [CudaViewMut].
+    pub fn as_view_mut(&mut self) -> CudaViewMut<'_, u8> {
+        CudaViewMut {
+            ptr: self.device_ptr,
+            len: self.len,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl Drop for ExternalMemory {
+    fn drop(&mut self) {
+        let external_memory = std::mem::replace(&mut self.external_memory, std::ptr::null_mut());
+        if !external_memory.is_null() {
+            if let Err(e) = unsafe { result::external_memory::destroy(external_memory) } {
+                defer_error(Some(&self.device), e);
+            }
+        }
+    }
+}
+
+impl Drop for MappedBuffer {
+    fn drop(&mut self) {
+        // the mapped buffer may still be used by work on the device's stream
+        let device = &self.external_memory.device;
+        if let Err(e) = device.synchronize() {
+            defer_error(Some(device), e);
+        }
+        if let Err(e) = unsafe { result::free_sync(self.device_ptr) } {
+            defer_error(Some(device), e);
+        }
+    }
+}
+
+impl Drop for ExternalSemaphore {
+    fn drop(&mut self) {
+        let semaphore = std::mem::replace(&mut self.semaphore, std::ptr::null_mut());
+        if !semaphore.is_null() {
+            if let Err(e) = unsafe { result::external_semaphore::destroy(semaphore) } {
+                defer_error(Some(&self.device), e);
+            }
+        }
+    }
+}
+
+impl CudaStream {
+    /// Enqueues a signal of `semaphore` onto this stream, which happens once all
+    /// previously enqueued work on this stream completes.
+    ///
+    /// `value` is the value to set a timeline semaphore to, and is ignored for binary semaphores.
+    pub fn signal_external_semaphore(
+        &self,
+        semaphore: &ExternalSemaphore,
+        value: u64,
+    ) -> Result<(), result::DriverError> {
+        unsafe { result::external_semaphore::signal_async(semaphore.semaphore, value, self.stream) }
+    }
+
+    /// Enqueues a wait on `semaphore` onto this stream. Work enqueued afterwards on this
+    /// stream will not start until the semaphore is signaled.
+    ///
+    /// `value` is the value a timeline semaphore must reach, and is ignored for binary semaphores.
+    pub fn wait_external_semaphore(
+        &self,
+        semaphore: &ExternalSemaphore,
+        value: u64,
+    ) -> Result<(), result::DriverError> {
+        unsafe { result::external_semaphore::wait_async(semaphore.semaphore, value, self.stream) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_map_range_in_bounds() {
+        assert!(in_bounds(&(0..0), 0));
+        assert!(in_bounds(&(0..16), 16));
+        assert!(in_bounds(&(4..8), 16));
+        assert!(in_bounds(&(16..16), 16));
+        assert!(!in_bounds(&(0..17), 16));
+        assert!(!in_bounds(&(17..17), 16));
+        #[allow(clippy::reversed_empty_ranges)]
+        let reversed = 8..4;
+        assert!(!in_bounds(&reversed, 16));
+    }
+
+    #[test]
+    fn test_import_external_memory_bad_fd() {
+        let dev = CudaDevice::new(0).unwrap();
+        // a regular file isn't an exported memory object
+        let file = File::open("/dev/null").unwrap();
+        let result = unsafe { dev.import_external_memory(file, 1024) };
+        assert!(result.is_err());
+
+        let file = File::open("/dev/null").unwrap();
+        let result =
+            unsafe { dev.import_external_semaphore(file, ExternalSemaphoreKind::OpaqueFd) };
+        assert!(result.is_err());
+    }
+}
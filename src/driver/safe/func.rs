@@ -0,0 +1,139 @@
+use crate::driver::{result, sys};
+
+use super::core::CudaFunction;
+use super::ctx::CacheConfig;
+
+use sys::CUfunction_attribute as Attr;
+
+impl CudaFunction {
+    fn get_attribute(&self, attribute: Attr) -> Result<i32, result::DriverError> {
+        unsafe { result::function::get_attribute(self.cu_function, attribute) }
+    }
+
+    fn set_attribute(&self, attribute: Attr, value: i32) -> Result<(), result::DriverError> {
+        unsafe { result::function::set_attribute(self.cu_function, attribute, value) }
+    }
+
+    /// The maximum number of threads per block beyond which a launch of this function
+    /// would fail. Depends on both the function and the device.
+    pub fn max_threads_per_block(&self) -> Result<i32, result::DriverError> {
+        self.get_attribute(Attr::CU_FUNC_ATTRIBUTE_MAX_THREADS_PER_BLOCK)
+    }
+
+    /// The size in bytes of statically-allocated shared memory required by this function.
+    /// This does not include dynamically-allocated shared memory requested at launch
+    /// via [crate::driver::LaunchConfig::shared_mem_bytes].
+    pub fn shared_size_bytes(&self) -> Result<i32, result::DriverError> {
+        self.get_attribute(Attr::CU_FUNC_ATTRIBUTE_SHARED_SIZE_BYTES)
+    }
+
+    /// The size in bytes of user-allocated constant memory required by this function.
+    pub fn const_size_bytes(&self) -> Result<i32, result::DriverError> {
+        self.get_attribute(Attr::CU_FUNC_ATTRIBUTE_CONST_SIZE_BYTES)
+    }
+
+    /// The size in bytes of local memory used by each thread of this function.
+    pub fn local_size_bytes(&self) -> Result<i32, result::DriverError> {
+        self.get_attribute(Attr::CU_FUNC_ATTRIBUTE_LOCAL_SIZE_BYTES)
+    }
+
+    /// The number of registers used by each thread of this function.
+    pub fn num_regs(&self) -> Result<i32, result::DriverError> {
+        self.get_attribute(Attr::CU_FUNC_ATTRIBUTE_NUM_REGS)
+    }
+
+    /// The PTX virtual architecture version this function was compiled for,
+    /// as `major * 10 + minor` (e.g. `80` for `compute_80`).
+    pub fn ptx_version(&self) -> Result<i32, result::DriverError> {
+        self.get_attribute(Attr::CU_FUNC_ATTRIBUTE_PTX_VERSION)
+    }
+
+    /// The binary architecture version this function was compiled for,
+    /// as `major * 10 + minor` (e.g. `80` for `sm_80`).
+    pub fn binary_version(&self) -> Result<i32, result::DriverError> {
+        self.get_attribute(Attr::CU_FUNC_ATTRIBUTE_BINARY_VERSION)
+    }
+
+    /// The maximum size in bytes of dynamically-allocated shared memory that can be
+    /// requested when launching this function.
+    pub fn max_dynamic_shared_size_bytes(&self) -> Result<i32, result::DriverError> {
+        self.get_attribute(Attr::CU_FUNC_ATTRIBUTE_MAX_DYNAMIC_SHARED_SIZE_BYTES)
+    }
+
+    /// Opts this function into using up to `bytes` of dynamically-allocated shared memory.
+    ///
+    /// Kernels that need more than 48KB of dynamic shared memory must call this
+    /// before being launched with a [crate::driver::LaunchConfig::shared_mem_bytes]
+    /// above that. `bytes` plus [CudaFunction::shared_size_bytes()] must not exceed the
+    /// device's maximum shared memory per block.
+    pub fn set_max_dynamic_shared_size_bytes(&self, bytes: i32) -> Result<(), result::DriverError> {
+        self.set_attribute(Attr::CU_FUNC_ATTRIBUTE_MAX_DYNAMIC_SHARED_SIZE_BYTES, bytes)
+    }
+
+    /// The preferred shared memory carveout of this function, as a percentage
+    /// of the maximum available shared memory. `-1` means no preference.
+    pub fn preferred_shared_memory_carveout(&self) -> Result<i32, result::DriverError> {
+        self.get_attribute(Attr::CU_FUNC_ATTRIBUTE_PREFERRED_SHARED_MEMORY_CARVEOUT)
+    }
+
+    /// Sets the preferred shared memory carveout of this function, as a percentage
+    /// (0 to 100) of the maximum available shared memory, or `-1` for no preference.
+    ///
+    /// This is only a hint, the driver may choose a different configuration.
+    pub fn set_preferred_shared_memory_carveout(
+        &self,
+        percent: i32,
+    ) -> Result<(), result::DriverError> {
+        self.set_attribute(
+            Attr::CU_FUNC_ATTRIBUTE_PREFERRED_SHARED_MEMORY_CARVEOUT,
+            percent,
+        )
+    }
+
+    /// Sets the preferred cache configuration of this function. This takes precedence
+    /// over [crate::driver::CudaDevice::set_cache_config()] when launching this function.
+    pub fn set_cache_config(&self, config: CacheConfig) -> Result<(), result::DriverError> {
+        unsafe { result::function::set_cache_config(self.cu_function, config.into()) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{driver::CudaDevice, nvrtc::compile_ptx};
+
+    use super::*;
+
+    const SHMEM_CU: &str = "
+extern \"C\" __global__ void shmem_kernel(float *out) {
+    __shared__ float tile[256];
+    extern __shared__ float dynamic[];
+    tile[threadIdx.x] = dynamic[threadIdx.x];
+    out[threadIdx.x] = tile[threadIdx.x];
+}";
+
+    #[test]
+    fn test_function_attributes() {
+        let ptx = compile_ptx(SHMEM_CU).unwrap();
+        let dev = CudaDevice::new(0).unwrap();
+        dev.load_ptx(ptx, "shmem", &["shmem_kernel"]).unwrap();
+        let f = dev.get_func("shmem", "shmem_kernel").unwrap();
+
+        assert_eq!(f.shared_size_bytes().unwrap(), 256 * 4);
+        assert!(f.num_regs().unwrap() > 0);
+        assert!(f.max_threads_per_block().unwrap() > 0);
+        assert!(f.ptx_version().unwrap() > 0);
+        assert!(f.binary_version().unwrap() > 0);
+    }
+
+    #[test]
+    fn test_set_max_dynamic_shared_size() {
+        let ptx = compile_ptx(SHMEM_CU).unwrap();
+        let dev = CudaDevice::new(0).unwrap();
+        dev.load_ptx(ptx, "shmem", &["shmem_kernel"]).unwrap();
+        let f = dev.get_func("shmem", "shmem_kernel").unwrap();
+
+        f.set_max_dynamic_shared_size_bytes(1024).unwrap();
+        assert_eq!(f.max_dynamic_shared_size_bytes().unwrap(), 1024);
+        f.set_cache_config(CacheConfig::PreferShared).unwrap();
+    }
+}
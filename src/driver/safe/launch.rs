@@ -3,7 +3,7 @@ use crate::driver::{result, sys};
 use super::alloc::DeviceRepr;
 use super::core::{CudaDevice, CudaFunction, CudaModule, CudaStream};
 
-use std::sync::Arc;
+use std::{boxed::Box, string::String, sync::Arc, vec::Vec};
 
 impl CudaDevice {
     /// Whether a module and function are currently loaded into the device.
@@ -14,53 +14,184 @@ impl CudaDevice {
 
         modules
             .get(module_name)
-            .map_or(false, |module| module.has_func(func_name))
+            .map_or(false, |module| module.functions.contains_key(func_name))
     }
 
     /// Retrieves a [CudaFunction] that was registered under `module_name` and `func_name`.
     pub fn get_func(self: &Arc<Self>, module_name: &str, func_name: &str) -> Option<CudaFunction> {
+        self.get_module(module_name)?.get_func(func_name)
+    }
+
+    /// Retrieves the [CudaModule] that was registered under `module_name`.
+    pub fn get_module(self: &Arc<Self>, module_name: &str) -> Option<CudaModule> {
         let modules = self.modules.read();
         #[cfg(not(feature = "no-std"))]
         let modules = modules.unwrap();
 
-        modules
-            .get(module_name)
-            .and_then(|m| m.get_func(func_name))
-            .map(|cu_function| CudaFunction {
-                cu_function,
-                device: self.clone(),
-            })
+        modules.get(module_name).map(|module| CudaModule {
+            module: module.clone(),
+            device: self.clone(),
+        })
+    }
+
+    /// The names of all modules currently registered with the device.
+    pub fn module_names(&self) -> Vec<String> {
+        let modules = self.modules.read();
+        #[cfg(not(feature = "no-std"))]
+        let modules = modules.unwrap();
+
+        modules.keys().cloned().collect()
+    }
+
+    /// Removes the module registered under `module_name`, returning whether there was one.
+    ///
+    /// The module is unloaded from the device once every [CudaModule] and [CudaFunction]
+    /// referring to it has been dropped, so functions retrieved before this can still be launched.
+    ///
+    /// ```rust
+    /// # use cudarc::{driver::*, nvrtc::*};
+    /// let ptx = compile_ptx("extern \"C\" __global__ void f(float *out) { }").unwrap();
+    /// let dev = CudaDevice::new(0).unwrap();
+    /// dev.load_ptx(ptx, "module", &["f"]).unwrap();
+    /// assert!(dev.unload_module("module"));
+    /// assert!(!dev.has_func("module", "f"));
+    /// ```
+    pub fn unload_module(&self, module_name: &str) -> bool {
+        #[allow(unused_mut)]
+        let mut modules = self.modules.write();
+        #[cfg(not(feature = "no-std"))]
+        let mut modules = modules.unwrap();
+
+        modules.remove(module_name).is_some()
     }
 }
 
 impl CudaModule {
-    /// Returns reference to function with `name`. If function
-    /// was not already loaded into CudaModule, then `None`
-    /// is returned.
-    pub(crate) fn get_func(&self, name: &str) -> Option<sys::CUfunction> {
-        self.functions.get(name).cloned()
+    /// The name the module was loaded under.
+    pub fn name(&self) -> &str {
+        &self.module.name
+    }
+
+    /// The names of the functions loaded from the module.
+    pub fn func_names(&self) -> impl Iterator<Item = &str> {
+        self.module.functions.keys().map(String::as_str)
     }
 
-    pub(crate) fn has_func(&self, name: &str) -> bool {
-        self.functions.contains_key(name)
+    /// Whether `func_name` was loaded from the module.
+    pub fn has_func(&self, func_name: &str) -> bool {
+        self.module.functions.contains_key(func_name)
+    }
+
+    /// Retrieves the function `func_name`. If it was not loaded
+    /// from the module, then `None` is returned.
+    pub fn get_func(&self, func_name: &str) -> Option<CudaFunction> {
+        let cu_function = *self.module.functions.get(func_name)?;
+        Some(CudaFunction {
+            cu_function,
+            module: self.module.clone(),
+            device: self.device.clone(),
+            param_sizes: self.module.param_sizes.get(func_name).cloned(),
+        })
+    }
+}
+
+/// The sizes in bytes of the params of a launch don't match the ones the kernel declares.
+/// See [CudaFunction::check_param_sizes()].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParamSizeMismatch {
+    pub expected: Vec<usize>,
+    pub actual: Vec<usize>,
+}
+
+#[cfg(feature = "std")]
+impl std::fmt::Display for ParamSizeMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Sizes in bytes of kernel params {:?} don't match the kernel's declaration {:?}",
+            self.actual, self.expected
+        )
     }
 }
 
+#[cfg(feature = "std")]
+impl std::error::Error for ParamSizeMismatch {}
+
+/// An error from [LaunchAsync::launch()] or [LaunchAsync::launch_on_stream()].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LaunchError {
+    Driver(result::DriverError),
+    /// Only checked in debug builds, see [CudaFunction::check_param_sizes()].
+    ParamSizes(ParamSizeMismatch),
+}
+
+impl From<result::DriverError> for LaunchError {
+    fn from(value: result::DriverError) -> Self {
+        Self::Driver(value)
+    }
+}
+
+impl From<ParamSizeMismatch> for LaunchError {
+    fn from(value: ParamSizeMismatch) -> Self {
+        Self::ParamSizes(value)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::fmt::Display for LaunchError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for LaunchError {}
+
 impl CudaFunction {
+    /// The module this function was loaded from.
+    pub fn module(&self) -> CudaModule {
+        CudaModule {
+            module: self.module.clone(),
+            device: self.device.clone(),
+        }
+    }
+
+    /// The sizes in bytes of the params declared for this function, if it was loaded from
+    /// [crate::nvrtc::Ptx] whose params could all be parsed. See [crate::nvrtc::Ptx::entries()].
+    pub fn param_sizes(&self) -> Option<&[usize]> {
+        self.param_sizes.as_deref()
+    }
+
+    /// Checks that `sizes` (e.g. [KernelArgs::param_sizes()]) match
+    /// [CudaFunction::param_sizes()], which catches e.g. passing a `u32` for a `size_t`.
+    /// Always succeeds if the declared sizes aren't known.
+    ///
+    /// In debug builds, [LaunchAsync::launch()] & [LaunchAsync::launch_on_stream()] check
+    /// this before launching, and return [LaunchError::ParamSizes] on a mismatch.
+    pub fn check_param_sizes(&self, sizes: &[usize]) -> Result<(), ParamSizeMismatch> {
+        match self.param_sizes() {
+            Some(expected) if expected != sizes => Err(ParamSizeMismatch {
+                expected: expected.to_vec(),
+                actual: sizes.to_vec(),
+            }),
+            _ => Ok(()),
+        }
+    }
+
     #[inline]
     unsafe fn launch_async_impl(
         self,
         cfg: LaunchConfig,
         params: &mut [*mut std::ffi::c_void],
-    ) -> Result<(), result::DriverError> {
-        result::launch_kernel(
+    ) -> Result<(), LaunchError> {
+        Ok(result::launch_kernel(
             self.cu_function,
             cfg.grid_dim,
             cfg.block_dim,
             cfg.shared_mem_bytes,
             self.device.stream,
             params,
-        )
+        )?)
     }
 
     #[inline]
@@ -69,15 +200,15 @@ impl CudaFunction {
         stream: &CudaStream,
         cfg: LaunchConfig,
         params: &mut [*mut std::ffi::c_void],
-    ) -> Result<(), result::DriverError> {
-        result::launch_kernel(
+    ) -> Result<(), LaunchError> {
+        Ok(result::launch_kernel(
             self.cu_function,
             cfg.grid_dim,
             cfg.block_dim,
             cfg.shared_mem_bytes,
             stream.stream,
             params,
-        )
+        )?)
     }
 }
 
@@ -173,7 +304,12 @@ pub unsafe trait LaunchAsync<Params> {
     ///
     /// **If you launch a kernel or drop a value on a different stream
     /// this may not hold**
-    unsafe fn launch(self, cfg: LaunchConfig, params: Params) -> Result<(), result::DriverError>;
+    ///
+    /// ## Param sizes
+    ///
+    /// In debug builds, the sizes of `params` are checked against the ones declared in the
+    /// ptx the function was loaded from, see [CudaFunction::check_param_sizes()].
+    unsafe fn launch(self, cfg: LaunchConfig, params: Params) -> Result<(), LaunchError>;
 
     /// Launch the function on a stream concurrent to the device's default
     /// work stream.
@@ -189,7 +325,7 @@ pub unsafe trait LaunchAsync<Params> {
         stream: &CudaStream,
         cfg: LaunchConfig,
         params: Params,
-    ) -> Result<(), result::DriverError>;
+    ) -> Result<(), LaunchError>;
 }
 
 macro_rules! impl_launch {
@@ -199,7 +335,9 @@ unsafe impl<$($Vars: DeviceRepr),*> LaunchAsync<($($Vars, )*)> for CudaFunction
         self,
         cfg: LaunchConfig,
         args: ($($Vars, )*)
-    ) -> Result<(), result::DriverError> {
+    ) -> Result<(), LaunchError> {
+        #[cfg(debug_assertions)]
+        self.check_param_sizes(&[$(args.$Idx.kernel_param_size(), )*])?;
         let params = &mut [$(args.$Idx.as_kernel_param(), )*];
         self.launch_async_impl(cfg, params)
     }
@@ -209,7 +347,9 @@ unsafe impl<$($Vars: DeviceRepr),*> LaunchAsync<($($Vars, )*)> for CudaFunction
         stream: &CudaStream,
         cfg: LaunchConfig,
         args: ($($Vars, )*)
-    ) -> Result<(), result::DriverError> {
+    ) -> Result<(), LaunchError> {
+        #[cfg(debug_assertions)]
+        self.check_param_sizes(&[$(args.$Idx.kernel_param_size(), )*])?;
         let params = &mut [$(args.$Idx.as_kernel_param(), )*];
         self.par_launch_async_impl(stream, cfg, params)
     }
@@ -239,14 +379,119 @@ impl_launch!(
     [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11]
 );
 
+/// Kernel params built up one at a time, for kernels with more params than
+/// [LaunchAsync] has tuple impls for, or whose params are only known at runtime.
+///
+/// Each pushed value is boxed, so its [DeviceRepr::as_kernel_param()] pointer stays valid
+/// until the args are dropped. References (e.g. `&mut CudaSlice<T>`) are borrowed for
+/// `'a`, so the slices can't be freed or moved while the args exist.
+///
+/// ```rust
+/// # use cudarc::{driver::*, nvrtc::*};
+/// # let ptx = compile_ptx("extern \"C\" __global__ void my_function(float *out, size_t n) { }").unwrap();
+/// # let device = CudaDevice::new(0).unwrap();
+/// # device.load_ptx(ptx, "module_name", &["my_function"]).unwrap();
+/// let f = device.get_func("module_name", "my_function").unwrap();
+/// let mut a = device.alloc_zeros::<f32>(10).unwrap();
+/// let args = KernelArgs::new().arg(&mut a).arg(10usize);
+/// unsafe { f.launch(LaunchConfig::for_num_elems(10), args) }.unwrap();
+/// ```
+#[derive(Default)]
+pub struct KernelArgs<'a> {
+    args: Vec<Box<dyn DeviceRepr + 'a>>,
+    params: Vec<*mut std::ffi::c_void>,
+}
+
+impl<'a> KernelArgs<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds `arg` as the next param, builder style. See [KernelArgs::push()].
+    pub fn arg<T: DeviceRepr + 'a>(mut self, arg: T) -> Self {
+        self.push(arg);
+        self
+    }
+
+    /// Adds `arg` as the next param.
+    pub fn push<T: DeviceRepr + 'a>(&mut self, arg: T) {
+        self.args.push(Box::new(arg));
+    }
+
+    /// The number of params pushed so far.
+    pub fn len(&self) -> usize {
+        self.args.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.args.is_empty()
+    }
+
+    /// The sizes in bytes of each param, see [DeviceRepr::kernel_param_size()].
+    pub fn param_sizes(&self) -> Vec<usize> {
+        self.args.iter().map(|a| a.kernel_param_size()).collect()
+    }
+
+    /// Pointers to each param, as passed to `cuLaunchKernel`. Valid until `self` is
+    /// dropped or pushed to.
+    pub fn as_kernel_params(&mut self) -> &mut [*mut std::ffi::c_void] {
+        self.params.clear();
+        self.params
+            .extend(self.args.iter().map(|a| a.as_kernel_param()));
+        &mut self.params
+    }
+
+    /// Fills in the params for a kernel node of a cuda graph, for use with
+    /// [sys::cuGraphAddKernelNode()] or [sys::cuGraphExecKernelNodeSetParams()].
+    /// The driver copies the params when the node is added, so `self` only needs
+    /// to live until then.
+    ///
+    /// The returned struct points into `self`, so it must not be used after `self` is
+    /// dropped or pushed to.
+    pub fn kernel_node_params(
+        &mut self,
+        func: &CudaFunction,
+        cfg: LaunchConfig,
+    ) -> sys::CUDA_KERNEL_NODE_PARAMS {
+        sys::CUDA_KERNEL_NODE_PARAMS {
+            func: func.cu_function,
+            gridDimX: cfg.grid_dim.0,
+            gridDimY: cfg.grid_dim.1,
+            gridDimZ: cfg.grid_dim.2,
+            blockDimX: cfg.block_dim.0,
+            blockDimY: cfg.block_dim.1,
+            blockDimZ: cfg.block_dim.2,
+            sharedMemBytes: cfg.shared_mem_bytes,
+            kernelParams: self.as_kernel_params().as_mut_ptr(),
+            extra: std::ptr::null_mut(),
+        }
+    }
+}
+
+unsafe impl<'a> LaunchAsync<KernelArgs<'a>> for CudaFunction {
+    unsafe fn launch(self, cfg: LaunchConfig, mut args: KernelArgs<'a>) -> Result<(), LaunchError> {
+        #[cfg(debug_assertions)]
+        self.check_param_sizes(&args.param_sizes())?;
+        self.launch_async_impl(cfg, args.as_kernel_params())
+    }
+
+    unsafe fn launch_on_stream(
+        self,
+        stream: &CudaStream,
+        cfg: LaunchConfig,
+        mut args: KernelArgs<'a>,
+    ) -> Result<(), LaunchError> {
+        #[cfg(debug_assertions)]
+        self.check_param_sizes(&args.param_sizes())?;
+        self.par_launch_async_impl(stream, cfg, args.as_kernel_params())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::{time::Instant, vec::Vec};
 
-    use crate::{
-        driver::{DeviceSlice, DriverError},
-        nvrtc::compile_ptx_with_opts,
-    };
+    use crate::{driver::DeviceSlice, nvrtc::compile_ptx_with_opts};
 
     use super::*;
 
@@ -310,6 +555,154 @@ extern \"C\" __global__ void sin_kernel(float *out, const float *inp, size_t num
         drop(a_dev);
     }
 
+    #[test]
+    fn test_check_param_sizes() {
+        let ptx = compile_ptx_with_opts(SIN_CU, Default::default()).unwrap();
+        let dev = CudaDevice::new(0).unwrap();
+        dev.load_ptx(ptx, "sin", &["sin_kernel"]).unwrap();
+        let sin_kernel = dev.get_func("sin", "sin_kernel").unwrap();
+        assert_eq!(sin_kernel.param_sizes(), Some(&[8, 8, 8][..]));
+        assert!(sin_kernel.check_param_sizes(&[8, 8, 8]).is_ok());
+        // numel is a size_t, but this passes a u32
+        assert_eq!(
+            sin_kernel.check_param_sizes(&[8, 8, 4]),
+            Err(ParamSizeMismatch {
+                expected: std::vec![8, 8, 8],
+                actual: std::vec![8, 8, 4],
+            })
+        );
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    fn test_launch_with_wrong_param_size() {
+        let ptx = compile_ptx_with_opts(SIN_CU, Default::default()).unwrap();
+        let dev = CudaDevice::new(0).unwrap();
+        dev.load_ptx(ptx, "sin", &["sin_kernel"]).unwrap();
+        let sin_kernel = dev.get_func("sin", "sin_kernel").unwrap();
+        let a_dev = dev.alloc_zeros::<f32>(10).unwrap();
+        let mut b_dev = a_dev.clone();
+        let cfg = LaunchConfig::for_num_elems(10);
+        let expected = Err(LaunchError::ParamSizes(ParamSizeMismatch {
+            expected: std::vec![8, 8, 8],
+            actual: std::vec![8, 8, 4],
+        }));
+        // numel is a size_t, but this passes a u32
+        let result = unsafe { sin_kernel.clone().launch(cfg, (&mut b_dev, &a_dev, 10u32)) };
+        assert_eq!(result, expected);
+        let args = KernelArgs::new().arg(&mut b_dev).arg(&a_dev).arg(10u32);
+        assert_eq!(unsafe { sin_kernel.launch(cfg, args) }, expected);
+    }
+
+    #[cfg(feature = "derive")]
+    #[test]
+    fn test_launch_typed_kernel() {
+        crate::driver::kernels!(
+            "
+extern \"C\" __global__ void sin_kernel(float *out, const float *inp, size_t numel) {
+    size_t i = blockIdx.x * blockDim.x + threadIdx.x;
+    if (i < numel) {
+        out[i] = sin(inp[i]);
+    }
+}"
+        );
+
+        let ptx = compile_ptx_with_opts(SIN_CU, Default::default()).unwrap();
+        let dev = CudaDevice::new(0).unwrap();
+        dev.load_ptx(ptx, "sin", &[SinKernel::NAME]).unwrap();
+        let sin_kernel = SinKernel::get(&dev, "sin").unwrap();
+
+        let a_host = [-1.0f32, -0.8, -0.6, -0.4, -0.2, 0.0, 0.2, 0.4, 0.6, 0.8];
+        let a_dev = dev.htod_copy(a_host.to_vec()).unwrap();
+        let mut b_dev = dev.alloc_zeros::<f32>(10).unwrap();
+
+        let cfg = LaunchConfig::for_num_elems(10);
+        unsafe { sin_kernel.launch(cfg, &mut b_dev, &a_dev, 10) }.unwrap();
+
+        let b_host = dev.sync_reclaim(b_dev).unwrap();
+        for (a_i, b_i) in a_host.iter().zip(b_host.iter()) {
+            assert!((b_i - a_i.sin()).abs() <= 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_launch_with_kernel_args() {
+        let ptx = compile_ptx_with_opts(SIN_CU, Default::default()).unwrap();
+        let dev = CudaDevice::new(0).unwrap();
+        dev.load_ptx(ptx, "sin", &["sin_kernel"]).unwrap();
+        let sin_kernel = dev.get_func("sin", "sin_kernel").unwrap();
+
+        let a_host = [-1.0f32, -0.8, -0.6, -0.4, -0.2, 0.0, 0.2, 0.4, 0.6, 0.8];
+        let a_dev = dev.htod_copy(a_host.to_vec()).unwrap();
+        let mut b_dev = dev.alloc_zeros::<f32>(10).unwrap();
+
+        let args = KernelArgs::new().arg(&mut b_dev).arg(&a_dev).arg(10usize);
+        assert_eq!(args.len(), 3);
+        unsafe { sin_kernel.launch(LaunchConfig::for_num_elems(10), args) }.unwrap();
+
+        let b_host = dev.sync_reclaim(b_dev).unwrap();
+        for (a_i, b_i) in a_host.iter().zip(b_host.iter()) {
+            assert!((b_i - a_i.sin()).abs() <= 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_launch_with_many_kernel_args() {
+        let params: Vec<_> = (0..16).map(|i| std::format!("int x{i}")).collect();
+        let sum: Vec<_> = (0..16).map(|i| std::format!("x{i}")).collect();
+        let src = std::format!(
+            "extern \"C\" __global__ void sum16(int *out, {}) {{ *out = {}; }}",
+            params.join(", "),
+            sum.join(" + ")
+        );
+        let ptx = compile_ptx_with_opts(src, Default::default()).unwrap();
+        let dev = CudaDevice::new(0).unwrap();
+        dev.load_ptx(ptx, "sum16", &["sum16"]).unwrap();
+        let f = dev.get_func("sum16", "sum16").unwrap();
+
+        let mut out = dev.alloc_zeros::<i32>(1).unwrap();
+        let mut args = KernelArgs::new();
+        args.push(&mut out);
+        for i in 0..16i32 {
+            args.push(i);
+        }
+        let stream = dev.fork_default_stream().unwrap();
+        unsafe { f.launch_on_stream(&stream, LaunchConfig::for_num_elems(1), args) }.unwrap();
+        dev.wait_for(&stream).unwrap();
+
+        assert_eq!(dev.sync_reclaim(out).unwrap(), [(0..16).sum::<i32>()]);
+    }
+
+    #[test]
+    fn test_launch_after_unload_module() {
+        let ptx = compile_ptx_with_opts(SIN_CU, Default::default()).unwrap();
+        let dev = CudaDevice::new(0).unwrap();
+        let module = dev
+            .load_ptx(ptx, std::format!("sin_{}", 0), &["sin_kernel"])
+            .unwrap();
+        assert_eq!(module.name(), "sin_0");
+        assert_eq!(module.func_names().collect::<Vec<_>>(), ["sin_kernel"]);
+        assert_eq!(dev.module_names(), ["sin_0"]);
+
+        let sin_kernel = module.get_func("sin_kernel").unwrap();
+        drop(module);
+        assert!(dev.unload_module("sin_0"));
+        assert!(!dev.unload_module("sin_0"));
+        assert!(dev.get_module("sin_0").is_none());
+        assert!(dev.module_names().is_empty());
+
+        // the function keeps the module loaded
+        assert_eq!(sin_kernel.module().name(), "sin_0");
+        let a_dev = dev.htod_copy(std::vec![1.0f32; 10]).unwrap();
+        let mut b_dev = dev.alloc_zeros::<f32>(10).unwrap();
+        let cfg = LaunchConfig::for_num_elems(10);
+        unsafe { sin_kernel.launch(cfg, (&mut b_dev, &a_dev, 10usize)) }.unwrap();
+        for v in dev.sync_reclaim(b_dev).unwrap() {
+            assert_eq!(v, 0.841471);
+        }
+        dev.check_error().unwrap();
+    }
+
     #[test]
     fn test_large_launches() {
         let ptx = compile_ptx_with_opts(SIN_CU, Default::default()).unwrap();
@@ -497,7 +890,7 @@ extern \"C\" __global__ void halfs(__half h) {
             HALF_KERNELS,
             CompileOptions {
                 include_paths: std::vec!["/usr/include".into()],
-                arch: Some("compute_53"),
+                arch: Some("compute_53".into()),
                 ..Default::default()
             },
         )
@@ -526,7 +919,7 @@ extern \"C\" __global__ void slow_worker(const float *data, const size_t len, fl
 ";
 
     #[test]
-    fn test_par_launch() -> Result<(), DriverError> {
+    fn test_par_launch() -> Result<(), LaunchError> {
         let ptx = compile_ptx_with_opts(SLOW_KERNELS, Default::default()).unwrap();
         let dev = CudaDevice::new(0).unwrap();
         dev.load_ptx(ptx, "tests", &["slow_worker"]).unwrap();
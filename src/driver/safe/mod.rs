@@ -81,9 +81,16 @@
 //! on each of the items in the batch.
 //!
 //! Use [CudaSlice::try_slice()] and [CudaSlice::try_slice_mut()] for this. The returned
-//! views ([CudaView] and [CudaViewMut] hold references to the owning [CudaSlice],
+//! views ([CudaView] and [CudaViewMut] borrow the owning [CudaSlice],
 //! so rust's ownership system handles safety here.
 //!
+//! Like with `&mut [T]`, [CudaSlice::split_at_mut()] and [CudaSlice::chunks_mut()] give
+//! several disjoint [CudaViewMut]s at once, and [CudaSlice::reinterpret()] views the memory
+//! as another type.
+//!
+//! [StridedView] and [StridedViewMut] are N-d views with a shape & strides, e.g. a sub-matrix
+//! or a transpose.
+//!
 //! These view structs can be used with [CudaFunction].
 //!
 //! ```rust
@@ -158,16 +165,46 @@
 //! to be unsafe in a multi stream context though.
 
 pub(crate) mod alloc;
+pub(crate) mod builtins;
 pub(crate) mod core;
+pub(crate) mod ctx;
 pub(crate) mod device_ptr;
+pub(crate) mod elementwise;
+pub(crate) mod error;
+#[cfg(all(unix, not(feature = "no-std")))]
+pub(crate) mod external_memory;
+pub(crate) mod func;
 pub(crate) mod launch;
 pub(crate) mod profile;
 pub(crate) mod ptx;
+pub(crate) mod reduce;
+pub(crate) mod sort;
+pub(crate) mod strided;
 
-pub use self::alloc::{DeviceRepr, ValidAsZeroBits};
-pub use self::core::{CudaDevice, CudaFunction, CudaSlice, CudaStream, CudaView, CudaViewMut};
+pub use self::alloc::{
+    DeviceLayout, DeviceRepr, FieldLayout, StructLayout, ValidAsAnyBits, ValidAsZeroBits,
+};
+pub use self::core::{
+    Chunks, ChunksMut, CudaDevice, CudaFunction, CudaModule, CudaSlice, CudaStream, CudaView,
+    CudaViewMut, DeviceInitError,
+};
+pub use self::ctx::{CacheConfig, ContextLimit};
 pub use self::device_ptr::{DevicePtr, DevicePtrMut, DeviceSlice};
-pub use self::launch::{LaunchAsync, LaunchConfig};
+pub use self::elementwise::{DType, Elementwise, ELEMENTWISE_KERNEL};
+pub use self::error::{set_deferred_error_hook, DeferredError};
+#[cfg(all(unix, not(feature = "no-std")))]
+pub use self::external_memory::{
+    ExternalMemory, ExternalSemaphore, ExternalSemaphoreKind, MappedBuffer,
+};
+pub use self::launch::{KernelArgs, LaunchAsync, LaunchConfig, LaunchError, ParamSizeMismatch};
 pub use self::profile::{profiler_start, profiler_stop};
+pub use self::ptx::{CompileAndLoadError, ModuleImage};
+pub use self::reduce::ReduceOp;
+pub use self::sort::RadixKey;
+pub use self::strided::{
+    MatrixLayout, StridedLayout, StridedView, StridedViewMut, MAX_DIMS, STRIDED_VIEW_SRC,
+};
+#[cfg(feature = "derive")]
+pub use cudarc_derive::{kernels, DeviceRepr, ValidAsZeroBits};
 
 pub use crate::driver::result::DriverError;
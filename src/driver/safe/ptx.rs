@@ -1,55 +1,566 @@
+#[cfg(feature = "cuda-12")]
+use crate::nvrtc::LtoIr;
 use crate::{
-    driver::result,
-    nvrtc::{Ptx, PtxKind},
+    driver::{result, sys},
+    nvrtc::{
+        compile_cubin, compile_ptx_with_opts, supported_archs, version, BinaryKind, CompileError,
+        CompileOptions, Cubin, Fatbin, Ptx, PtxEntry, PtxKind, TargetArch,
+    },
 };
 
-use super::core::{CudaDevice, CudaModule};
+use super::core::{CudaDevice, CudaModule, LoadedModule};
+use super::launch::{LaunchError, ParamSizeMismatch};
 
 use std::ffi::CString;
-use std::{collections::BTreeMap, sync::Arc};
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    string::{String, ToString},
+    sync::Arc,
+    vec::Vec,
+};
+
+/// A compiled module that can be loaded with [CudaDevice::load_module()].
+///
+/// Can be created from a [Ptx], [Cubin] or [Fatbin] (or `LtoIr` with the `cuda-12` feature)
+/// with [From], or from the contents of a file of unknown kind with
+/// [ModuleImage::from_bytes()].
+#[derive(Debug, Clone)]
+pub enum ModuleImage {
+    Ptx(Ptx),
+    Cubin(Cubin),
+    Fatbin(Fatbin),
+    /// Linked into a cubin by the driver with JIT LTO when loaded
+    #[cfg(feature = "cuda-12")]
+    LtoIr(LtoIr),
+}
+
+impl ModuleImage {
+    /// Detects whether `bytes` are a cubin (an ELF file) or a fatbin from their magic number,
+    /// and otherwise treats them as ptx source.
+    ///
+    /// ```rust
+    /// # use cudarc::driver::ModuleImage;
+    /// let image = ModuleImage::from_bytes(b"\x7fELF...".to_vec());
+    /// assert!(matches!(image, ModuleImage::Cubin(_)));
+    /// ```
+    pub fn from_bytes(bytes: Vec<u8>) -> Self {
+        const ELF_MAGIC: &[u8] = b"\x7fELF";
+        const FATBIN_MAGIC: &[u8] = &0xBA55ED50u32.to_le_bytes();
+        if bytes.starts_with(ELF_MAGIC) {
+            Self::Cubin(Cubin::from_bytes(bytes))
+        } else if bytes.starts_with(FATBIN_MAGIC) {
+            Self::Fatbin(Fatbin::from_bytes(bytes))
+        } else {
+            Self::Ptx(Ptx::from_src(String::from_utf8_lossy(&bytes)))
+        }
+    }
+}
+
+impl From<Ptx> for ModuleImage {
+    fn from(value: Ptx) -> Self {
+        Self::Ptx(value)
+    }
+}
+
+impl From<Cubin> for ModuleImage {
+    fn from(value: Cubin) -> Self {
+        Self::Cubin(value)
+    }
+}
+
+impl From<Fatbin> for ModuleImage {
+    fn from(value: Fatbin) -> Self {
+        Self::Fatbin(value)
+    }
+}
+
+#[cfg(feature = "cuda-12")]
+impl From<LtoIr> for ModuleImage {
+    fn from(value: LtoIr) -> Self {
+        Self::LtoIr(value)
+    }
+}
+
+/// An error from compiling or loading a module, e.g. with [CudaDevice::load_module()] or
+/// [CudaDevice::compile_and_load()].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CompileAndLoadError {
+    Driver(result::DriverError),
+    Compile(CompileError),
+
+    /// nvrtc can't compile for a device this old, see [TargetArch::select()].
+    UnsupportedDevice {
+        compute_capability: (i32, i32),
+    },
+
+    /// The driver is too old to load what nvrtc compiles for the device, see
+    /// [TargetArch::select_for_driver()].
+    UnsupportedDriver {
+        nvrtc_version: (i32, i32),
+        driver_version: (i32, i32),
+    },
+
+    /// The sizes of a generated kernel's params didn't match, see [LaunchError::ParamSizes].
+    ParamSizes(ParamSizeMismatch),
+
+    /// The file at `path` couldn't be read. Paths that aren't valid UTF-8 or contain a NUL
+    /// can't be passed to the driver, and have `kind` [std::io::ErrorKind::InvalidInput].
+    File {
+        path: PathBuf,
+        kind: std::io::ErrorKind,
+    },
+}
+
+impl From<result::DriverError> for CompileAndLoadError {
+    fn from(value: result::DriverError) -> Self {
+        Self::Driver(value)
+    }
+}
+
+impl From<LaunchError> for CompileAndLoadError {
+    fn from(value: LaunchError) -> Self {
+        match value {
+            LaunchError::Driver(err) => Self::Driver(err),
+            LaunchError::ParamSizes(err) => Self::ParamSizes(err),
+        }
+    }
+}
+
+impl From<CompileError> for CompileAndLoadError {
+    fn from(value: CompileError) -> Self {
+        Self::Compile(value)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::fmt::Display for CompileAndLoadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for CompileAndLoadError {}
 
 impl CudaDevice {
     /// Dynamically load a set of [crate::driver::CudaFunction] from a jit compiled ptx.
     ///
     /// - `ptx` contains the compilex ptx
-    /// - `module_name` is a unique identifier used to access the module later on with [CudaDevice::get_func()]
-    /// - `func_names` is a slice of function names to load into the module during build.
-    pub fn load_ptx(
+    /// - `module_name` is a unique identifier used to access the module later on with [CudaDevice::get_func()].
+    ///   A module already registered under the same name is replaced, see [CudaDevice::unload_module()].
+    /// - `func_names` is a slice of function names to load into the module during build. These can
+    ///   also be name expressions `ptx` was compiled with, see [crate::nvrtc::compile_ptx_with_names()].
+    ///
+    /// Returns a handle to the loaded module, which can also be retrieved later with
+    /// [CudaDevice::get_module()].
+    ///
+    /// The sizes of the params declared in `ptx` are kept, see
+    /// [CudaFunction::check_param_sizes()](crate::driver::CudaFunction::check_param_sizes()).
+    pub fn load_ptx<N: AsRef<str>>(
+        self: &Arc<Self>,
+        ptx: Ptx,
+        module_name: impl Into<String>,
+        func_names: &[N],
+    ) -> Result<CudaModule, CompileAndLoadError> {
+        self.load_module(ptx, module_name, func_names)
+    }
+
+    /// Loads a set of [crate::driver::CudaFunction] from a [Ptx], [Cubin] or [Fatbin].
+    /// Ptx is jit compiled by the driver, while cubins & fatbins are loaded with
+    /// `cuModuleLoadData` & `cuModuleLoadFatBinary`. With the `cuda-12` feature, LTO-IR
+    /// is linked into a cubin with `cuLinkCreate` & `CU_JIT_LTO` first.
+    ///
+    /// See [CudaDevice::load_ptx()] for `module_name` and `func_names`. Only functions loaded
+    /// from [Ptx] know the sizes of their params.
+    ///
+    /// ```rust,no_run
+    /// # use cudarc::{driver::*, nvrtc::*};
+    /// let dev = CudaDevice::new(0).unwrap();
+    /// let module = dev.load_module(Fatbin::from_file("kernels.fatbin"), "kernels", &["sin_kernel"]).unwrap();
+    /// ```
+    pub fn load_module<N: AsRef<str>>(
+        self: &Arc<Self>,
+        image: impl Into<ModuleImage>,
+        module_name: impl Into<String>,
+        func_names: &[N],
+    ) -> Result<CudaModule, CompileAndLoadError> {
+        let image = image.into();
+        let entries = match &image {
+            ModuleImage::Ptx(ptx) => ptx.entries().unwrap_or_default(),
+            _ => Vec::new(),
+        };
+        let func_names: Vec<String> = func_names.iter().map(|f| f.as_ref().into()).collect();
+        self.load_module_image(image, module_name.into(), &func_names, &entries)
+    }
+
+    /// The best arch to compile kernels for this device with, out of the ones the installed
+    /// nvrtc supports, that the driver can load. See [TargetArch::select_for_driver()].
+    pub fn target_arch(&self) -> Result<TargetArch, CompileAndLoadError> {
+        let compute_capability = self.compute_capability()?;
+        let supported = supported_archs()?;
+        TargetArch::select(compute_capability, &supported)
+            .ok_or(CompileAndLoadError::UnsupportedDevice { compute_capability })?;
+        let nvrtc_version = version()?;
+        let driver_version = result::version()?;
+        TargetArch::select_for_driver(
+            compute_capability,
+            &supported,
+            nvrtc_version,
+            driver_version,
+        )
+        .ok_or(CompileAndLoadError::UnsupportedDriver {
+            nvrtc_version,
+            driver_version,
+        })
+    }
+
+    /// Compiles `src` for this device's [CudaDevice::target_arch()], and loads `func_names`
+    /// from it into a module named `module_name`.
+    ///
+    /// If nvrtc supports the device's real arch, `src` is compiled to a [Cubin] so the driver
+    /// doesn't need to jit compile it. Otherwise it is compiled to [Ptx] for the newest
+    /// virtual arch the device supports, if the driver isn't older than nvrtc.
+    ///
+    /// ```rust
+    /// # use cudarc::driver::*;
+    /// let dev = CudaDevice::new(0).unwrap();
+    /// let src = "extern \"C\" __global__ void my_function(float *out) { }";
+    /// dev.compile_and_load(src, "module", &["my_function"]).unwrap();
+    /// assert!(dev.has_func("module", "my_function"));
+    /// ```
+    pub fn compile_and_load<S: AsRef<str>, N: AsRef<str>>(
+        self: &Arc<Self>,
+        src: S,
+        module_name: impl Into<String>,
+        func_names: &[N],
+    ) -> Result<CudaModule, CompileAndLoadError> {
+        self.compile_and_load_with_opts(src, Default::default(), module_name, func_names)
+    }
+
+    /// [CudaDevice::compile_and_load()] with `opts`, whose `arch` is replaced by [CudaDevice::target_arch()].
+    pub(crate) fn compile_and_load_with_opts<S: AsRef<str>, N: AsRef<str>>(
+        self: &Arc<Self>,
+        src: S,
+        opts: CompileOptions,
+        module_name: impl Into<String>,
+        func_names: &[N],
+    ) -> Result<CudaModule, CompileAndLoadError> {
+        let arch = self.target_arch()?;
+        let opts = CompileOptions {
+            arch: Some(arch.to_string()),
+            ..opts
+        };
+        let image: ModuleImage = match arch {
+            TargetArch::Real(_) => compile_cubin(src, opts)?.into(),
+            TargetArch::Virtual(_) => compile_ptx_with_opts(src, opts)?.into(),
+        };
+        self.load_module(image, module_name, func_names)
+    }
+
+    /// Loads every kernel declared in `ptx` (see [Ptx::entries()]) into a module named `module_name`.
+    ///
+    /// ```rust
+    /// # use cudarc::{driver::*, nvrtc::*};
+    /// let ptx = compile_ptx("
+    /// extern \"C\" __global__ void a(float *out) { }
+    /// extern \"C\" __global__ void b(float *out) { }
+    /// ").unwrap();
+    /// let dev = CudaDevice::new(0).unwrap();
+    /// dev.load_ptx_all(ptx, "module").unwrap();
+    /// assert!(dev.has_func("module", "a"));
+    /// assert!(dev.has_func("module", "b"));
+    /// ```
+    pub fn load_ptx_all(
         self: &Arc<Self>,
         ptx: Ptx,
-        module_name: &'static str,
-        func_names: &[&'static str],
-    ) -> Result<(), result::DriverError> {
-        let cu_module = match ptx.0 {
-            PtxKind::Image(image) => unsafe {
-                result::module::load_data(image.as_ptr() as *const _)
+        module_name: impl Into<String>,
+    ) -> Result<CudaModule, CompileAndLoadError> {
+        let entries = ptx.entries().map_err(|e| match &ptx.kind {
+            PtxKind::File(path) => CompileAndLoadError::File {
+                path: path.clone(),
+                kind: e.kind(),
+            },
+            _ => unreachable!("only ptx files are read"),
+        })?;
+        let func_names: Vec<String> = entries.iter().map(|e| e.name.clone()).collect();
+        self.load_module_image(ptx.into(), module_name.into(), &func_names, &entries)
+    }
+
+    fn load_module_image(
+        self: &Arc<Self>,
+        image: ModuleImage,
+        module_name: String,
+        func_names: &[String],
+        entries: &[PtxEntry],
+    ) -> Result<CudaModule, CompileAndLoadError> {
+        self.bind_to_thread()?;
+        let lowered_names = match &image {
+            ModuleImage::Ptx(ptx) => ptx.lowered_names.clone(),
+            _ => BTreeMap::new(),
+        };
+        let cu_module = match image {
+            ModuleImage::Ptx(Ptx { kind, .. }) => match kind {
+                PtxKind::Image(image) => unsafe {
+                    result::module::load_data(image.as_ptr() as *const _).map_err(Into::into)
+                },
+                PtxKind::Src(src) => CString::new(src)
+                    .map_err(|_| INVALID_VALUE)
+                    .and_then(|c_src| unsafe {
+                        result::module::load_data(c_src.as_ptr() as *const _)
+                    })
+                    .map_err(Into::into),
+                PtxKind::File(path) => load_file(&path),
             },
-            PtxKind::Src(src) => {
-                let c_src = CString::new(src).unwrap();
-                unsafe { result::module::load_data(c_src.as_ptr() as *const _) }
+            ModuleImage::Cubin(Cubin {
+                kind: BinaryKind::Bytes(bytes),
+                ..
+            }) => {
+                let image = aligned_copy(&bytes);
+                unsafe { result::module::load_data(image.as_ptr() as *const _) }.map_err(Into::into)
             }
-            PtxKind::File(path) => {
-                let name_c = CString::new(path.to_str().unwrap()).unwrap();
-                result::module::load(name_c)
+            ModuleImage::Fatbin(Fatbin(BinaryKind::Bytes(bytes))) => {
+                let image = aligned_copy(&bytes);
+                unsafe { result::module::load_fat_binary(image.as_ptr() as *const _) }
+                    .map_err(Into::into)
             }
+            // the driver detects the kind of file itself
+            ModuleImage::Cubin(Cubin {
+                kind: BinaryKind::File(path),
+                ..
+            })
+            | ModuleImage::Fatbin(Fatbin(BinaryKind::File(path))) => load_file(&path),
+            #[cfg(feature = "cuda-12")]
+            ModuleImage::LtoIr(lto_ir) => load_lto_ir(&lto_ir).map_err(Into::into),
         }?;
         let mut functions = BTreeMap::new();
-        for &fn_name in func_names.iter() {
-            let fn_name_c = CString::new(fn_name).unwrap();
-            let cu_function = unsafe { result::module::get_function(cu_module, fn_name_c) }?;
-            functions.insert(fn_name, cu_function);
+        let mut param_sizes = BTreeMap::new();
+        for fn_name in func_names.iter() {
+            // name expressions are looked up by the name they were lowered to
+            let symbol = lowered_names.get(fn_name).unwrap_or(fn_name);
+            let cu_function = CString::new(symbol.as_str())
+                .map_err(|_| INVALID_VALUE)
+                .and_then(|symbol_c| unsafe { result::module::get_function(cu_module, symbol_c) });
+            match cu_function {
+                Ok(cu_function) => functions.insert(fn_name.clone(), cu_function),
+                Err(e) => {
+                    unsafe { result::module::unload(cu_module) }?;
+                    return Err(e.into());
+                }
+            };
+            let params = entries.iter().find(|e| &e.name == symbol);
+            if let Some(params) = params.and_then(|e| e.params.as_ref()) {
+                let sizes = params.iter().map(|p| p.size).collect();
+                param_sizes.insert(fn_name.clone(), sizes);
+            }
         }
-        let module = CudaModule {
+        let module = Arc::new(LoadedModule {
+            name: module_name.clone(),
             cu_module,
+            cu_primary_ctx: self.cu_primary_ctx,
             functions,
-        };
+            param_sizes,
+        });
         #[allow(unused_mut)]
         {
             let mut modules = self.modules.write();
             #[cfg(not(feature = "no-std"))]
             let mut modules = modules.unwrap();
-            modules.insert(module_name, module);
+            modules.insert(module_name, module.clone());
         }
-        Ok(())
+        Ok(CudaModule {
+            module,
+            device: self.clone(),
+        })
+    }
+}
+
+/// Returned for ptx source or function names containing a NUL byte, which can't be passed
+/// to the driver.
+const INVALID_VALUE: result::DriverError =
+    result::DriverError(sys::CUresult::CUDA_ERROR_INVALID_VALUE);
+
+fn load_file(path: &Path) -> Result<sys::CUmodule, CompileAndLoadError> {
+    let c_path = path
+        .to_str()
+        .and_then(|p| CString::new(p).ok())
+        .ok_or_else(|| CompileAndLoadError::File {
+            path: path.to_path_buf(),
+            kind: std::io::ErrorKind::InvalidInput,
+        })?;
+    Ok(result::module::load(c_path)?)
+}
+
+/// Links `lto_ir` into a cubin with the driver's JIT LTO, and loads that.
+#[cfg(feature = "cuda-12")]
+fn load_lto_ir(lto_ir: &LtoIr) -> Result<sys::CUmodule, result::DriverError> {
+    use result::link;
+    let mut options = [sys::CUjit_option::CU_JIT_LTO];
+    // option values are passed as pointers, but `CU_JIT_LTO` is an int flag
+    let mut values = [core::ptr::without_provenance_mut(1)];
+    let name = CString::new("lto_ir").unwrap();
+    unsafe {
+        let state = link::create(&mut options, &mut values)?;
+        let input_type = sys::CUjitInputType::CU_JIT_INPUT_NVVM;
+        // the cubin belongs to `state`, but the module doesn't need it once loaded
+        let module = link::add_data(state, input_type, lto_ir.as_bytes(), &name)
+            .and_then(|_| link::complete(state))
+            .and_then(|(cubin, _)| result::module::load_data(cubin));
+        link::destroy(state)?;
+        module
+    }
+}
+
+/// Copies `bytes` into a buffer aligned for the ELF & fatbin headers, since the driver
+/// reads them in place.
+fn aligned_copy(bytes: &[u8]) -> Vec<u64> {
+    let mut image = std::vec![0u64; bytes.len().div_ceil(8) + 1];
+    for (word, chunk) in image.iter_mut().zip(bytes.chunks(8)) {
+        let mut buf = [0u8; 8];
+        buf[..chunk.len()].copy_from_slice(chunk);
+        *word = u64::from_ne_bytes(buf);
+    }
+    image
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::driver::{LaunchAsync, LaunchConfig};
+
+    #[test]
+    fn test_module_image_from_bytes() {
+        let cubin = ModuleImage::from_bytes(b"\x7fELF\x02\x01".to_vec());
+        assert!(matches!(cubin, ModuleImage::Cubin(_)));
+        let fatbin = ModuleImage::from_bytes([0x50, 0xED, 0x55, 0xBA, 0x01, 0x00].to_vec());
+        assert!(matches!(fatbin, ModuleImage::Fatbin(_)));
+        let ptx = ModuleImage::from_bytes(b".version 7.0\n.target sm_52".to_vec());
+        assert!(matches!(ptx, ModuleImage::Ptx(_)));
+    }
+
+    #[test]
+    fn test_aligned_copy() {
+        let bytes: Vec<u8> = (0..13).collect();
+        let image = aligned_copy(&bytes);
+        assert_eq!(image.as_ptr() as usize % 8, 0);
+        let copied = unsafe { core::slice::from_raw_parts(image.as_ptr() as *const u8, 13) };
+        assert_eq!(copied, &bytes[..]);
+    }
+
+    #[cfg(feature = "cuda-12")]
+    #[test]
+    fn test_load_lto_ir() {
+        let dev = CudaDevice::new(0).unwrap();
+        let src = "extern \"C\" __global__ void set(float *out) { *out = 3.0f; }";
+        let opts = crate::nvrtc::CompileOptions {
+            arch: Some(dev.target_arch().unwrap().to_string()),
+            ..Default::default()
+        };
+        let lto_ir = crate::nvrtc::compile_lto_ir(src, opts).unwrap();
+        dev.load_module(lto_ir, "lto", &["set"]).unwrap();
+        let f = dev.get_func("lto", "set").unwrap();
+        let mut out = dev.alloc_zeros::<f32>(1).unwrap();
+        unsafe { f.launch(LaunchConfig::for_num_elems(1), (&mut out,)) }.unwrap();
+        assert_eq!(dev.dtoh_sync_copy(&out).unwrap(), [3.0]);
+    }
+
+    #[test]
+    fn test_load_ptx_all_missing_file() {
+        let dev = CudaDevice::new(0).unwrap();
+        let ptx = Ptx::from_file("cudarc_missing.ptx");
+        assert_eq!(
+            dev.load_ptx_all(ptx, "missing").unwrap_err(),
+            CompileAndLoadError::File {
+                path: "cudarc_missing.ptx".into(),
+                kind: std::io::ErrorKind::NotFound,
+            }
+        );
+    }
+
+    #[test]
+    fn test_load_file_invalid_path() {
+        let path = Path::new("cudarc\0.cubin");
+        assert_eq!(
+            load_file(path).unwrap_err(),
+            CompileAndLoadError::File {
+                path: path.into(),
+                kind: std::io::ErrorKind::InvalidInput,
+            }
+        );
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::ffi::OsStrExt;
+            let path = Path::new(std::ffi::OsStr::from_bytes(b"cudarc\xff.cubin"));
+            assert_eq!(
+                load_file(path).unwrap_err(),
+                CompileAndLoadError::File {
+                    path: path.into(),
+                    kind: std::io::ErrorKind::InvalidInput,
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn test_load_ptx_interior_nul() {
+        let dev = CudaDevice::new(0).unwrap();
+        let ptx = Ptx::from_src(".version 7.0\0");
+        assert_eq!(
+            dev.load_ptx(ptx, "nul", &["f"]).unwrap_err(),
+            CompileAndLoadError::Driver(INVALID_VALUE)
+        );
+    }
+
+    #[test]
+    fn test_load_name_expressions() {
+        let src = "template<typename T> __global__ void set(T *out, T v) { *out = v; }";
+        let ptx = crate::nvrtc::compile_ptx_with_names(
+            src,
+            Default::default(),
+            &["set<float>", "set<int>"],
+        )
+        .unwrap();
+        assert!(ptx.lowered_name("set<float>").unwrap().starts_with("_Z"));
+
+        let dev = CudaDevice::new(0).unwrap();
+        dev.load_ptx(ptx, "set", &["set<float>", "set<int>"])
+            .unwrap();
+        let set_f32 = dev.get_func("set", "set<float>").unwrap();
+        let set_i32 = dev.get_func("set", "set<int>").unwrap();
+
+        let mut a = dev.alloc_zeros::<f32>(1).unwrap();
+        let mut b = dev.alloc_zeros::<i32>(1).unwrap();
+        let cfg = LaunchConfig::for_num_elems(1);
+        unsafe { set_f32.launch(cfg, (&mut a, 1.5f32)) }.unwrap();
+        unsafe { set_i32.launch(cfg, (&mut b, 3i32)) }.unwrap();
+        assert_eq!(dev.sync_reclaim(a).unwrap(), [1.5]);
+        assert_eq!(dev.sync_reclaim(b).unwrap(), [3]);
+    }
+
+    #[test]
+    fn test_compile_and_load() {
+        let dev = CudaDevice::new(0).unwrap();
+        let arch = dev.target_arch().unwrap();
+        let (major, minor) = dev.compute_capability().unwrap();
+        assert!(arch.version() <= major * 10 + minor);
+
+        let src = "extern \"C\" __global__ void set(float *out) { *out = 2.0f; }";
+        let module = dev.compile_and_load(src, "set", &["set"]).unwrap();
+        let f = module.get_func("set").unwrap();
+        let mut a = dev.alloc_zeros::<f32>(1).unwrap();
+        unsafe { f.launch(crate::driver::LaunchConfig::for_num_elems(1), (&mut a,)) }.unwrap();
+        assert_eq!(dev.sync_reclaim(a).unwrap(), [2.0]);
+    }
+
+    #[test]
+    fn test_load_ptx_as_module_image() {
+        let ptx = crate::nvrtc::compile_ptx(
+            "extern \"C\" __global__ void f(float *out) { *out = 1.0f; }",
+        )
+        .unwrap();
+        let dev = CudaDevice::new(0).unwrap();
+        let module = dev.load_module(ptx, "m", &["f"]).unwrap();
+        assert!(module.has_func("f"));
     }
 }
@@ -0,0 +1,631 @@
+//! Reductions & scans over device memory: [CudaDevice::reduce()], [CudaDevice::argmax()]
+//! and [CudaDevice::prefix_sum()].
+//!
+//! Reductions are two pass tree reductions: a grid of blocks reduces into one partial
+//! result per block, and one block per output reduces those. Scans are blockwise, with the
+//! sum of each block scanned recursively and added back.
+
+use crate::driver::sys;
+
+use super::core::CudaDevice;
+use super::device_ptr::{DevicePtr, DevicePtrMut};
+use super::elementwise::{includes, DType};
+use super::launch::{KernelArgs, LaunchAsync, LaunchConfig};
+use super::ptx::CompileAndLoadError;
+
+use std::{format, string::String, sync::Arc};
+
+/// Threads per block of every reduction & scan kernel
+const BLOCK: usize = 256;
+/// Most blocks a single output of a reduction is split over
+const MAX_BLOCKS_PER_OUTPUT: usize = 1024;
+/// The largest `gridDim.y`
+const MAX_GRID_Y: usize = 65535;
+
+/// How [CudaDevice::reduce()] combines elements.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ReduceOp {
+    Sum,
+    /// The sum divided by the number of elements. Truncates for integers.
+    Mean,
+    Max,
+    Min,
+}
+
+/// How two elements are combined in the generated kernel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Combine {
+    Sum,
+    Max,
+    Min,
+}
+
+impl From<ReduceOp> for Combine {
+    fn from(op: ReduceOp) -> Self {
+        match op {
+            ReduceOp::Sum | ReduceOp::Mean => Self::Sum,
+            ReduceOp::Max => Self::Max,
+            ReduceOp::Min => Self::Min,
+        }
+    }
+}
+
+/// A reduction over `n` elements, `inner` apart, for each of `outer * inner` outputs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Layout {
+    outer: usize,
+    n: usize,
+    inner: usize,
+}
+
+impl Layout {
+    fn all(n: usize) -> Self {
+        Self {
+            outer: 1,
+            n,
+            inner: 1,
+        }
+    }
+
+    /// Reduces `axis` of a row-major `(rows, cols)` matrix.
+    fn axis((rows, cols): (usize, usize), axis: usize) -> Self {
+        match axis {
+            0 => Self {
+                outer: 1,
+                n: rows,
+                inner: cols,
+            },
+            1 => Self {
+                outer: rows,
+                n: cols,
+                inner: 1,
+            },
+            _ => panic!("axis must be 0 or 1 for a 2d shape, got {axis}"),
+        }
+    }
+
+    fn num_outputs(&self) -> usize {
+        self.outer * self.inner
+    }
+}
+
+/// The type `T` is accumulated in. The half types don't have accurate (or on older
+/// devices, any) arithmetic, so use `float` for them.
+fn acc_type<T: DType>() -> &'static str {
+    match T::HEADER {
+        Some(_) => "float",
+        None => T::C_TYPE,
+    }
+}
+
+fn acc_size<T: DType>() -> usize {
+    match T::HEADER {
+        Some(_) => std::mem::size_of::<f32>(),
+        None => std::mem::size_of::<T>(),
+    }
+}
+
+/// The identity of `combine` for `acc`, e.g. the lowest value for [Combine::Max].
+fn identity(combine: Combine, acc: &str) -> String {
+    let (lowest, highest) = match acc {
+        "float" => ("-__int_as_float(0x7f800000)", "__int_as_float(0x7f800000)"),
+        "double" => (
+            "-__longlong_as_double(0x7ff0000000000000LL)",
+            "__longlong_as_double(0x7ff0000000000000LL)",
+        ),
+        "signed char" => ("-128", "127"),
+        "short" => ("-32768", "32767"),
+        "int" => ("-2147483647 - 1", "2147483647"),
+        "long long" => ("-9223372036854775807LL - 1", "9223372036854775807LL"),
+        "unsigned char" | "unsigned short" | "unsigned int" | "unsigned long long" => {
+            ("0", "(~0ULL)")
+        }
+        _ => unreachable!("no limits for {acc}"),
+    };
+    match combine {
+        Combine::Sum => format!("({acc})0"),
+        Combine::Max => format!("({acc})({lowest})"),
+        Combine::Min => format!("({acc})({highest})"),
+    }
+}
+
+/// Source of a kernel that reduces `inp` (of `in_ty`) in `acc_ty`, writing one `out_ty`
+/// per block & output. If `with_idx`, also tracks the index of the max/min element, read
+/// from `inp_idx` if it isn't null.
+fn reduce_src(
+    combine: Combine,
+    with_idx: bool,
+    in_ty: &str,
+    acc_ty: &str,
+    out_ty: &str,
+    prelude: &str,
+) -> String {
+    let update = match (combine, with_idx) {
+        (Combine::Sum, _) => "acc = acc + x;",
+        (Combine::Max, false) => "acc = x > acc ? x : acc;",
+        (Combine::Min, false) => "acc = x < acc ? x : acc;",
+        // ties go to the lowest index, so the result doesn't depend on the launch config
+        (Combine::Max, true) => {
+            "if (x > acc || (x == acc && x_i < acc_i)) { acc = x; acc_i = x_i; }"
+        }
+        (Combine::Min, true) => {
+            "if (x < acc || (x == acc && x_i < acc_i)) { acc = x; acc_i = x_i; }"
+        }
+    };
+    let init = identity(combine, acc_ty);
+    let (idx_shared, idx_init, idx_load, idx_store, idx_shared_load, idx_shared_store, idx_out) =
+        match with_idx {
+            true => (
+                format!("\n    __shared__ size_t idxs[{BLOCK}];"),
+                "\n        size_t acc_i = (size_t)-1;",
+                "\n            const size_t x_i = inp_idx ? inp_idx[base + j * inner] : j;",
+                "\n        idxs[threadIdx.x] = acc_i;",
+                " size_t acc_i = idxs[threadIdx.x]; const size_t x_i = idxs[threadIdx.x + s];",
+                " idxs[threadIdx.x] = acc_i;",
+                "\n            if (out_idx) out_idx[k] = idxs[0];",
+            ),
+            false => (String::new(), "", "", "", "", "", ""),
+        };
+    format!(
+        "{prelude}extern \"C\" __global__ void reduce(const size_t outer, const size_t n, const size_t inner, const size_t divisor, const {in_ty} *inp, const size_t *inp_idx, {out_ty} *out, size_t *out_idx) {{
+    __shared__ {acc_ty} vals[{BLOCK}];{idx_shared}
+    for (size_t o = blockIdx.y; o < outer * inner; o += gridDim.y) {{
+        const size_t base = (o / inner) * n * inner + o % inner;
+        {acc_ty} acc = {init};{idx_init}
+        for (size_t j = (size_t)blockIdx.x * blockDim.x + threadIdx.x; j < n; j += (size_t)blockDim.x * gridDim.x) {{
+            const {acc_ty} x = ({acc_ty})(inp[base + j * inner]);{idx_load}
+            {update}
+        }}
+        vals[threadIdx.x] = acc;{idx_store}
+        __syncthreads();
+        for (unsigned int s = blockDim.x / 2; s > 0; s >>= 1) {{
+            if (threadIdx.x < s) {{
+                {acc_ty} acc = vals[threadIdx.x]; const {acc_ty} x = vals[threadIdx.x + s];{idx_shared_load}
+                {update}
+                vals[threadIdx.x] = acc;{idx_shared_store}
+            }}
+            __syncthreads();
+        }}
+        if (threadIdx.x == 0) {{
+            const size_t k = o * gridDim.x + blockIdx.x;
+            if (out) out[k] = ({out_ty})(vals[0] / ({acc_ty})divisor);{idx_out}
+        }}
+        __syncthreads();
+    }}
+}}
+"
+    )
+}
+
+/// Source of a kernel that does an inclusive scan of each block of `inp` into `out`, and
+/// writes the total of each block to `block_sums`.
+fn scan_blocks_src<T: DType>() -> String {
+    let ty = T::C_TYPE;
+    let acc = acc_type::<T>();
+    format!(
+        "{prelude}extern \"C\" __global__ void scan_blocks(const size_t n, const {ty} *inp, {ty} *out, {ty} *block_sums) {{
+    __shared__ {acc} vals[{BLOCK}];
+    const size_t i = (size_t)blockIdx.x * blockDim.x + threadIdx.x;
+    vals[threadIdx.x] = i < n ? ({acc})(inp[i]) : ({acc})0;
+    __syncthreads();
+    for (unsigned int offset = 1; offset < blockDim.x; offset <<= 1) {{
+        const {acc} x = threadIdx.x >= offset ? vals[threadIdx.x - offset] : ({acc})0;
+        __syncthreads();
+        vals[threadIdx.x] = vals[threadIdx.x] + x;
+        __syncthreads();
+    }}
+    if (i < n) out[i] = ({ty})(vals[threadIdx.x]);
+    if (threadIdx.x == blockDim.x - 1) block_sums[blockIdx.x] = ({ty})(vals[threadIdx.x]);
+}}
+",
+        prelude = includes(&[T::HEADER]),
+    )
+}
+
+/// Source of a kernel that adds the scanned total of all previous blocks to each block.
+fn scan_add_src<T: DType>() -> String {
+    let ty = T::C_TYPE;
+    let acc = acc_type::<T>();
+    format!(
+        "{prelude}extern \"C\" __global__ void scan_add(const size_t n, {ty} *out, const {ty} *block_sums) {{
+    const size_t i = (size_t)blockIdx.x * blockDim.x + threadIdx.x;
+    if (blockIdx.x > 0 && i < n) out[i] = ({ty})(({acc})(out[i]) + ({acc})(block_sums[blockIdx.x - 1]));
+}}
+",
+        prelude = includes(&[T::HEADER]),
+    )
+}
+
+impl CudaDevice {
+    /// Reduces all of `src` into `dst`, which has a single element.
+    ///
+    /// ```rust
+    /// # use cudarc::driver::*;
+    /// let dev = CudaDevice::new(0).unwrap();
+    /// let a = dev.htod_copy(vec![1.0f32, 4.0, 2.0, 3.0]).unwrap();
+    /// let mut sum = dev.alloc_zeros::<f32>(1).unwrap();
+    /// dev.reduce(ReduceOp::Sum, &a, &mut sum).unwrap();
+    /// assert_eq!(dev.dtoh_sync_copy(&sum).unwrap(), [10.0]);
+    /// ```
+    ///
+    /// # Panics
+    /// If `dst` doesn't have exactly one element, or `src` is empty and `op` isn't [ReduceOp::Sum].
+    pub fn reduce<T: DType, Src: DevicePtr<T>, Dst: DevicePtrMut<T>>(
+        self: &Arc<Self>,
+        op: ReduceOp,
+        src: &Src,
+        dst: &mut Dst,
+    ) -> Result<(), CompileAndLoadError> {
+        assert_eq!(dst.len(), 1);
+        let layout = Layout::all(src.len());
+        self.reduce_op::<T>(op, *src.device_ptr(), layout, *dst.device_ptr_mut())
+    }
+
+    /// Reduces `axis` of `src`, a row-major matrix of `shape` `(rows, cols)`, into `dst`.
+    /// Axis 0 reduces each column into `cols` outputs, axis 1 each row into `rows` outputs.
+    ///
+    /// ```rust
+    /// # use cudarc::driver::*;
+    /// let dev = CudaDevice::new(0).unwrap();
+    /// let a = dev.htod_copy(vec![1i32, 2, 3, 4, 5, 6]).unwrap();
+    /// let mut col_max = dev.alloc_zeros::<i32>(3).unwrap();
+    /// dev.reduce_axis(ReduceOp::Max, &a, (2, 3), 0, &mut col_max).unwrap();
+    /// assert_eq!(dev.dtoh_sync_copy(&col_max).unwrap(), [4, 5, 6]);
+    /// let mut row_sum = dev.alloc_zeros::<i32>(2).unwrap();
+    /// dev.reduce_axis(ReduceOp::Sum, &a, (2, 3), 1, &mut row_sum).unwrap();
+    /// assert_eq!(dev.dtoh_sync_copy(&row_sum).unwrap(), [6, 15]);
+    /// ```
+    ///
+    /// # Panics
+    /// If `src` doesn't have `rows * cols` elements, `axis` isn't 0 or 1, `dst` has the
+    /// wrong length, or the axis is empty and `op` isn't [ReduceOp::Sum].
+    pub fn reduce_axis<T: DType, Src: DevicePtr<T>, Dst: DevicePtrMut<T>>(
+        self: &Arc<Self>,
+        op: ReduceOp,
+        src: &Src,
+        shape: (usize, usize),
+        axis: usize,
+        dst: &mut Dst,
+    ) -> Result<(), CompileAndLoadError> {
+        assert_eq!(src.len(), shape.0 * shape.1);
+        let layout = Layout::axis(shape, axis);
+        assert_eq!(dst.len(), layout.num_outputs());
+        self.reduce_op::<T>(op, *src.device_ptr(), layout, *dst.device_ptr_mut())
+    }
+
+    /// Reduces all of `src`, and copies the result to the host. Blocks until it's done.
+    ///
+    /// ```rust
+    /// # use cudarc::driver::*;
+    /// let dev = CudaDevice::new(0).unwrap();
+    /// let a = dev.htod_copy(vec![1.0f64, 4.0, 2.0, 3.0]).unwrap();
+    /// assert_eq!(dev.reduce_sync(ReduceOp::Mean, &a).unwrap(), 2.5);
+    /// ```
+    pub fn reduce_sync<T: DType, Src: DevicePtr<T>>(
+        self: &Arc<Self>,
+        op: ReduceOp,
+        src: &Src,
+    ) -> Result<T, CompileAndLoadError> {
+        let mut dst = unsafe { self.alloc::<T>(1) }?;
+        self.reduce(op, src, &mut dst)?;
+        Ok(self.dtoh_sync_copy(&dst)?[0])
+    }
+
+    /// Writes the index of the largest element of `src` into `dst`, which has a single
+    /// element. Ties go to the lowest index.
+    ///
+    /// # Panics
+    /// If `src` is empty, or `dst` doesn't have exactly one element.
+    pub fn argmax<T: DType, Src: DevicePtr<T>, Dst: DevicePtrMut<usize>>(
+        self: &Arc<Self>,
+        src: &Src,
+        dst: &mut Dst,
+    ) -> Result<(), CompileAndLoadError> {
+        assert_eq!(dst.len(), 1);
+        let layout = Layout::all(src.len());
+        self.argmax_op::<T>(*src.device_ptr(), layout, *dst.device_ptr_mut())
+    }
+
+    /// Writes the index (along `axis`) of the largest element of each row or column of
+    /// `src` into `dst`, see [CudaDevice::reduce_axis()].
+    ///
+    /// ```rust
+    /// # use cudarc::driver::*;
+    /// let dev = CudaDevice::new(0).unwrap();
+    /// let a = dev.htod_copy(vec![1.0f32, 9.0, 3.0, 7.0, 5.0, 6.0]).unwrap();
+    /// let mut idx = dev.alloc_zeros::<usize>(2).unwrap();
+    /// dev.argmax_axis(&a, (2, 3), 1, &mut idx).unwrap();
+    /// assert_eq!(dev.dtoh_sync_copy(&idx).unwrap(), [1, 0]);
+    /// ```
+    ///
+    /// # Panics
+    /// Like [CudaDevice::reduce_axis()].
+    pub fn argmax_axis<T: DType, Src: DevicePtr<T>, Dst: DevicePtrMut<usize>>(
+        self: &Arc<Self>,
+        src: &Src,
+        shape: (usize, usize),
+        axis: usize,
+        dst: &mut Dst,
+    ) -> Result<(), CompileAndLoadError> {
+        assert_eq!(src.len(), shape.0 * shape.1);
+        let layout = Layout::axis(shape, axis);
+        assert_eq!(dst.len(), layout.num_outputs());
+        self.argmax_op::<T>(*src.device_ptr(), layout, *dst.device_ptr_mut())
+    }
+
+    /// The index of the largest element of `src`, copied to the host. Blocks until it's done.
+    ///
+    /// ```rust
+    /// # use cudarc::driver::*;
+    /// let dev = CudaDevice::new(0).unwrap();
+    /// let a = dev.htod_copy(vec![3u8, 9, 2, 9]).unwrap();
+    /// assert_eq!(dev.argmax_sync(&a).unwrap(), 1);
+    /// ```
+    pub fn argmax_sync<T: DType, Src: DevicePtr<T>>(
+        self: &Arc<Self>,
+        src: &Src,
+    ) -> Result<usize, CompileAndLoadError> {
+        let mut dst = unsafe { self.alloc::<usize>(1) }?;
+        self.argmax(src, &mut dst)?;
+        Ok(self.dtoh_sync_copy(&dst)?[0])
+    }
+
+    /// Writes the inclusive prefix sum of `src` into `dst`, i.e. `dst[i] = src[0] + ... + src[i]`.
+    ///
+    /// ```rust
+    /// # use cudarc::driver::*;
+    /// let dev = CudaDevice::new(0).unwrap();
+    /// let a = dev.htod_copy(vec![1u32, 2, 3, 4]).unwrap();
+    /// let mut b = dev.alloc_zeros::<u32>(4).unwrap();
+    /// dev.prefix_sum(&a, &mut b).unwrap();
+    /// assert_eq!(dev.dtoh_sync_copy(&b).unwrap(), [1, 3, 6, 10]);
+    /// ```
+    ///
+    /// # Panics
+    /// If `src` and `dst` have different lengths.
+    pub fn prefix_sum<T: DType, Src: DevicePtr<T>, Dst: DevicePtrMut<T>>(
+        self: &Arc<Self>,
+        src: &Src,
+        dst: &mut Dst,
+    ) -> Result<(), CompileAndLoadError> {
+        assert_eq!(src.len(), dst.len());
+        self.scan::<T>(*src.device_ptr(), *dst.device_ptr_mut(), src.len())
+    }
+
+    fn reduce_op<T: DType>(
+        self: &Arc<Self>,
+        op: ReduceOp,
+        src: sys::CUdeviceptr,
+        layout: Layout,
+        dst: sys::CUdeviceptr,
+    ) -> Result<(), CompileAndLoadError> {
+        assert!(
+            layout.n > 0 || op == ReduceOp::Sum,
+            "can't take the {op:?} of no elements"
+        );
+        let divisor = match op {
+            ReduceOp::Mean => layout.n,
+            _ => 1,
+        };
+        self.reduce_launch::<T>(op.into(), false, src, layout, divisor, dst, 0)
+    }
+
+    fn argmax_op<T: DType>(
+        self: &Arc<Self>,
+        src: sys::CUdeviceptr,
+        layout: Layout,
+        dst: sys::CUdeviceptr,
+    ) -> Result<(), CompileAndLoadError> {
+        assert!(layout.n > 0, "can't take the argmax of no elements");
+        self.reduce_launch::<T>(Combine::Max, true, src, layout, 1, 0, dst)
+    }
+
+    /// Reduces with one pass if a single block per output is enough, otherwise reduces into
+    /// partials per block first. Either of `dst` & `dst_idx` may be null.
+    #[allow(clippy::too_many_arguments)]
+    fn reduce_launch<T: DType>(
+        self: &Arc<Self>,
+        combine: Combine,
+        with_idx: bool,
+        src: sys::CUdeviceptr,
+        layout: Layout,
+        divisor: usize,
+        dst: sys::CUdeviceptr,
+        dst_idx: sys::CUdeviceptr,
+    ) -> Result<(), CompileAndLoadError> {
+        let num_outputs = layout.num_outputs();
+        if num_outputs == 0 {
+            return Ok(());
+        }
+        let ty = T::C_TYPE;
+        let acc = acc_type::<T>();
+        let prelude = includes(&[T::HEADER]);
+        let needs_headers = T::HEADER.is_some();
+        let blocks_x = layout.n.div_ceil(BLOCK * 4).clamp(1, MAX_BLOCKS_PER_OUTPUT);
+        let cfg = |blocks_x: usize| LaunchConfig {
+            grid_dim: (blocks_x as u32, num_outputs.min(MAX_GRID_Y) as u32, 1),
+            block_dim: (BLOCK as u32, 1, 1),
+            shared_mem_bytes: 0,
+        };
+        let args = |layout: Layout, divisor: usize, inp, inp_idx, out, out_idx| {
+            KernelArgs::new()
+                .arg(layout.outer)
+                .arg(layout.n)
+                .arg(layout.inner)
+                .arg(divisor)
+                .arg::<sys::CUdeviceptr>(inp)
+                .arg::<sys::CUdeviceptr>(inp_idx)
+                .arg::<sys::CUdeviceptr>(out)
+                .arg::<sys::CUdeviceptr>(out_idx)
+        };
+
+        if blocks_x == 1 {
+            let src_code = reduce_src(combine, with_idx, ty, acc, ty, &prelude);
+            let func = self.generated_func("reduce", src_code, needs_headers)?;
+            let args = args(layout, divisor, src, 0, dst, dst_idx);
+            unsafe { func.launch(cfg(1), args) }?;
+            return Ok(());
+        }
+
+        let num_partials = num_outputs * blocks_x;
+        let partials = unsafe { self.alloc::<u8>(num_partials * acc_size::<T>()) }?;
+        let partial_idxs = match with_idx {
+            true => Some(unsafe { self.alloc::<usize>(num_partials) }?),
+            false => None,
+        };
+        let partial_idxs_ptr = partial_idxs.as_ref().map_or(0, |p| *p.device_ptr());
+
+        let src_code = reduce_src(combine, with_idx, ty, acc, acc, &prelude);
+        let func = self.generated_func("reduce", src_code, needs_headers)?;
+        let args1 = args(layout, 1, src, 0, *partials.device_ptr(), partial_idxs_ptr);
+        unsafe { func.launch(cfg(blocks_x), args1) }?;
+
+        let partials_layout = Layout {
+            outer: num_outputs,
+            n: blocks_x,
+            inner: 1,
+        };
+        let src_code = reduce_src(combine, with_idx, acc, acc, ty, &prelude);
+        let func = self.generated_func("reduce", src_code, needs_headers)?;
+        let args2 = args(
+            partials_layout,
+            divisor,
+            *partials.device_ptr(),
+            partial_idxs_ptr,
+            dst,
+            dst_idx,
+        );
+        unsafe { func.launch(cfg(1), args2) }?;
+        Ok(())
+    }
+
+    /// Scans `n` elements of `src` into `dst`, recursing on the totals of each block.
+    pub(crate) fn scan<T: DType>(
+        self: &Arc<Self>,
+        src: sys::CUdeviceptr,
+        dst: sys::CUdeviceptr,
+        n: usize,
+    ) -> Result<(), CompileAndLoadError> {
+        if n == 0 {
+            return Ok(());
+        }
+        let needs_headers = T::HEADER.is_some();
+        let num_blocks = n.div_ceil(BLOCK);
+        let cfg = LaunchConfig {
+            grid_dim: (num_blocks.try_into().unwrap(), 1, 1),
+            block_dim: (BLOCK as u32, 1, 1),
+            shared_mem_bytes: 0,
+        };
+        let block_sums = unsafe { self.alloc::<T>(num_blocks) }?;
+        let block_sums_ptr = *block_sums.device_ptr();
+
+        let func = self.generated_func("scan_blocks", scan_blocks_src::<T>(), needs_headers)?;
+        let args = KernelArgs::new()
+            .arg(n)
+            .arg(src)
+            .arg(dst)
+            .arg(block_sums_ptr);
+        unsafe { func.launch(cfg, args) }?;
+
+        if num_blocks > 1 {
+            self.scan::<T>(block_sums_ptr, block_sums_ptr, num_blocks)?;
+            let func = self.generated_func("scan_add", scan_add_src::<T>(), needs_headers)?;
+            let args = KernelArgs::new().arg(n).arg(dst).arg(block_sums_ptr);
+            unsafe { func.launch(cfg, args) }?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::vec::Vec;
+
+    #[test]
+    fn test_layout() {
+        assert_eq!(Layout::all(5).num_outputs(), 1);
+        let cols = Layout::axis((2, 3), 0);
+        assert_eq!((cols.outer, cols.n, cols.inner), (1, 2, 3));
+        let rows = Layout::axis((2, 3), 1);
+        assert_eq!((rows.outer, rows.n, rows.inner), (2, 3, 1));
+    }
+
+    #[test]
+    fn test_reduce_src() {
+        let src = reduce_src(Combine::Max, false, "int", "int", "int", "");
+        assert!(src.contains("int acc = (int)(-2147483647 - 1);"));
+        assert!(src.contains("acc = x > acc ? x : acc;"));
+        assert!(!src.contains("acc_i"));
+
+        let src = reduce_src(Combine::Max, true, "double", "double", "float", "");
+        assert!(src.contains("__shared__ size_t idxs[256];"));
+        assert!(src.contains("if (out) out[k] = (float)(vals[0] / (double)divisor);"));
+        assert!(src.contains("if (out_idx) out_idx[k] = idxs[0];"));
+    }
+
+    #[cfg(feature = "f16")]
+    #[test]
+    fn test_reduce_half_src() {
+        assert_eq!(acc_type::<half::f16>(), "float");
+        let src = scan_blocks_src::<half::bf16>();
+        assert!(src.starts_with("#include \"cuda_bf16.h\"\n\n"));
+        assert!(src.contains("__shared__ float vals[256];"));
+    }
+
+    #[test]
+    fn test_reduce_all() {
+        let dev = CudaDevice::new(0).unwrap();
+        // big enough to need partials
+        let n = BLOCK * 4 * 3 + 5;
+        let a_host: Vec<f64> = (0..n).map(|i| (i % 17) as f64).collect();
+        let a = dev.htod_copy(a_host.clone()).unwrap();
+
+        let sum: f64 = a_host.iter().sum();
+        assert_eq!(dev.reduce_sync(ReduceOp::Sum, &a).unwrap(), sum);
+        assert_eq!(dev.reduce_sync(ReduceOp::Mean, &a).unwrap(), sum / n as f64);
+        assert_eq!(dev.reduce_sync(ReduceOp::Max, &a).unwrap(), 16.0);
+        assert_eq!(dev.reduce_sync(ReduceOp::Min, &a).unwrap(), 0.0);
+        assert_eq!(dev.argmax_sync(&a).unwrap(), 16);
+
+        let empty = dev.alloc_zeros::<f64>(0).unwrap();
+        assert_eq!(dev.reduce_sync(ReduceOp::Sum, &empty).unwrap(), 0.0);
+    }
+
+    #[test]
+    fn test_reduce_axis() {
+        let dev = CudaDevice::new(0).unwrap();
+        let (rows, cols) = (3, 5000);
+        let a_host: Vec<i64> = (0..rows * cols).map(|i| i as i64 % 7).collect();
+        let a = dev.htod_copy(a_host.clone()).unwrap();
+
+        let mut row_sums = dev.alloc_zeros::<i64>(rows).unwrap();
+        dev.reduce_axis(ReduceOp::Sum, &a, (rows, cols), 1, &mut row_sums)
+            .unwrap();
+        let expected: Vec<i64> = a_host.chunks(cols).map(|r| r.iter().sum()).collect();
+        assert_eq!(dev.dtoh_sync_copy(&row_sums).unwrap(), expected);
+
+        let mut col_argmax = dev.alloc_zeros::<usize>(cols).unwrap();
+        dev.argmax_axis(&a, (rows, cols), 0, &mut col_argmax)
+            .unwrap();
+        let expected: Vec<usize> = (0..cols)
+            .map(|c| {
+                (0..rows)
+                    .max_by_key(|&r| (a_host[r * cols + c], -(r as i64)))
+                    .unwrap()
+            })
+            .collect();
+        assert_eq!(dev.dtoh_sync_copy(&col_argmax).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_prefix_sum() {
+        let dev = CudaDevice::new(0).unwrap();
+        let n = BLOCK * BLOCK + 3;
+        let mut a = dev.htod_copy(std::vec![1u32; n]).unwrap();
+        let a_copy = a.clone();
+        dev.prefix_sum(&a_copy, &mut a).unwrap();
+        let expected: Vec<u32> = (1..=n as u32).collect();
+        assert_eq!(dev.dtoh_sync_copy(&a).unwrap(), expected);
+    }
+}
@@ -0,0 +1,569 @@
+//! Stable radix sorts on device memory: [CudaDevice::sort()], [CudaDevice::sort_pairs()],
+//! and their segmented versions [CudaDevice::sort_segments()] &
+//! [CudaDevice::sort_segments_pairs()].
+//!
+//! Keys are sorted 8 bits at a time, least significant digit first. Each pass counts the
+//! digits in each block of [RADIX] elements, scans the counts (digit major, so the
+//! scanned counts are where each block writes each digit), and then scatters every element
+//! to its place, keeping the order of equal digits. Segmented sorts are a sort by key
+//! followed by a sort by segment, which keeps the keys within each segment sorted.
+
+use crate::driver::{result, sys};
+
+use super::alloc::DeviceRepr;
+use super::builtins::{opaque_type, BYTES16};
+use super::core::{CudaDevice, CudaSlice};
+use super::device_ptr::{DevicePtr, DevicePtrMut};
+use super::elementwise::{grid_stride_cfg, grid_stride_src, includes, DType};
+use super::launch::{KernelArgs, LaunchAsync, LaunchConfig};
+use super::ptx::CompileAndLoadError;
+
+use std::{
+    format,
+    string::{String, ToString},
+    sync::Arc,
+    vec::Vec,
+};
+
+/// Number of buckets per pass, which is also the number of threads per block.
+const RADIX: usize = 256;
+
+/// A key [CudaDevice::sort()] can sort by.
+pub trait RadixKey: DType {
+    /// The unsigned type with as many bits as the key, in cuda source
+    const BITS_TYPE: &'static str;
+    /// A C expression of the key `k`, of type [RadixKey::BITS_TYPE], whose bits sort in the
+    /// same order as the key.
+    const ORDERED_BITS: &'static str;
+}
+
+macro_rules! radix_key {
+    ($($ty:ty => $bits:literal, $ordered:literal),* $(,)?) => {
+        $(impl RadixKey for $ty {
+            const BITS_TYPE: &'static str = $bits;
+            const ORDERED_BITS: &'static str = $ordered;
+        })*
+    };
+}
+
+radix_key!(
+    u8 => "unsigned char", "k",
+    u16 => "unsigned short", "k",
+    u32 => "unsigned int", "k",
+    u64 => "unsigned long long", "k",
+    // flipping the sign bit puts negative numbers first
+    i8 => "unsigned char", "(unsigned char)k ^ 0x80u",
+    i16 => "unsigned short", "(unsigned short)k ^ 0x8000u",
+    i32 => "unsigned int", "(unsigned int)k ^ 0x80000000u",
+    i64 => "unsigned long long", "(unsigned long long)k ^ 0x8000000000000000ull",
+    // flipping all bits of negative numbers reverses them, and puts them first
+    f32 => "unsigned int",
+        "__float_as_uint(k) ^ ((__float_as_uint(k) >> 31) ? 0xffffffffu : 0x80000000u)",
+    f64 => "unsigned long long",
+        "(unsigned long long)__double_as_longlong(k) ^ (((unsigned long long)__double_as_longlong(k) >> 63) ? 0xffffffffffffffffull : 0x8000000000000000ull)",
+);
+
+#[cfg(feature = "f16")]
+radix_key!(
+    half::f16 => "unsigned short",
+        "__half_as_ushort(k) ^ ((__half_as_ushort(k) >> 15) ? 0xffffu : 0x8000u)",
+    half::bf16 => "unsigned short",
+        "__bfloat16_as_ushort(k) ^ ((__bfloat16_as_ushort(k) >> 15) ? 0xffffu : 0x8000u)",
+);
+
+/// What the generated kernels need to know about a key type.
+#[derive(Debug, Clone, Copy)]
+struct KeyType {
+    c_type: &'static str,
+    header: Option<&'static str>,
+    bits_type: &'static str,
+    ordered_bits: &'static str,
+    size: usize,
+}
+
+impl KeyType {
+    fn of<K: RadixKey>() -> Self {
+        Self {
+            c_type: K::C_TYPE,
+            header: K::HEADER,
+            bits_type: K::BITS_TYPE,
+            ordered_bits: K::ORDERED_BITS,
+            size: std::mem::size_of::<K>(),
+        }
+    }
+
+    /// The prelude of every kernel sorting by this key, which defines `digit()`.
+    fn prelude(&self) -> String {
+        let Self {
+            c_type,
+            bits_type,
+            ordered_bits,
+            ..
+        } = self;
+        format!(
+            "{includes}__device__ __forceinline__ unsigned int digit(const {c_type} k, const unsigned int shift) {{
+    const {bits_type} bits = {ordered_bits};
+    return (unsigned int)((bits >> shift) & 0xff);
+}}
+
+",
+            includes = includes(&[self.header]),
+        )
+    }
+}
+
+/// Counts the digits in each block of keys into `counts`, digit major.
+fn histogram_src(key: &KeyType) -> String {
+    format!(
+        "{prelude}extern \"C\" __global__ void radix_histogram(const size_t n, const unsigned int shift, const {ty} *keys, unsigned long long *counts) {{
+    __shared__ unsigned int hist[{RADIX}];
+    hist[threadIdx.x] = 0;
+    __syncthreads();
+    const size_t i = (size_t)blockIdx.x * blockDim.x + threadIdx.x;
+    if (i < n) atomicAdd(&hist[digit(keys[i], shift)], 1u);
+    __syncthreads();
+    counts[(size_t)threadIdx.x * gridDim.x + blockIdx.x] = hist[threadIdx.x];
+}}
+",
+        prelude = key.prelude(),
+        ty = key.c_type,
+    )
+}
+
+/// Moves each key, and the elements of each payload at the same index, to where the scanned
+/// counts say its digit goes. Elements with equal digits keep their order.
+fn scatter_src(key: &KeyType, payloads: &[&str]) -> String {
+    let ty = key.c_type;
+    let mut params = std::vec![
+        "const size_t n".to_string(),
+        "const unsigned int shift".into(),
+        "const unsigned long long *counts".into(),
+        "const unsigned long long *scanned".into(),
+        format!("const {ty} *keys_in"),
+        format!("{ty} *keys_out"),
+    ];
+    let mut moves = String::new();
+    for (p, payload) in payloads.iter().enumerate() {
+        params.push(format!("const {payload} *p{p}_in"));
+        params.push(format!("{payload} *p{p}_out"));
+        moves.push_str(&format!("\n        p{p}_out[pos] = p{p}_in[i];"));
+    }
+    let bytes16 = match payloads.contains(&"bytes16") {
+        true => BYTES16,
+        false => "",
+    };
+    format!(
+        "{bytes16}{prelude}extern \"C\" __global__ void radix_scatter({params}) {{
+    __shared__ unsigned int digits[{RADIX}];
+    const size_t i = (size_t)blockIdx.x * blockDim.x + threadIdx.x;
+    const unsigned int d = i < n ? digit(keys_in[i], shift) : {RADIX};
+    digits[threadIdx.x] = d;
+    __syncthreads();
+    if (i < n) {{
+        unsigned int rank = 0;
+        for (unsigned int t = 0; t < threadIdx.x; t++) rank += digits[t] == d;
+        const size_t idx = (size_t)d * gridDim.x + blockIdx.x;
+        const size_t pos = scanned[idx] - counts[idx] + rank;
+        keys_out[pos] = keys_in[i];{moves}
+    }}
+}}
+",
+        prelude = key.prelude(),
+        params = params.join(", "),
+    )
+}
+
+/// Writes the segment each element is in, i.e. the last `s` with `offsets[s] <= i`.
+fn segment_ids_src() -> String {
+    let params = [
+        "const size_t numel".to_string(),
+        "const size_t num_segments".into(),
+        "const size_t *offsets".into(),
+        "unsigned int *out".into(),
+    ];
+    let body = "        size_t lo = 0, hi = num_segments;
+        while (hi - lo > 1) {
+            const size_t mid = lo + (hi - lo) / 2;
+            if (offsets[mid] <= i) lo = mid; else hi = mid;
+        }
+        out[i] = (unsigned int)lo;
+";
+    grid_stride_src("", "segment_ids", &params, body)
+}
+
+/// An array that is sorted along with the keys, and the scratch buffer it's moved into
+/// on every other pass.
+struct SortArray {
+    ptr: sys::CUdeviceptr,
+    scratch: CudaSlice<u8>,
+    size: usize,
+}
+
+impl SortArray {
+    fn new(
+        dev: &Arc<CudaDevice>,
+        ptr: sys::CUdeviceptr,
+        n: usize,
+        size: usize,
+    ) -> Result<Self, result::DriverError> {
+        let scratch = unsafe { dev.alloc::<u8>(n * size) }?;
+        Ok(Self { ptr, scratch, size })
+    }
+
+    /// The buffer the array is in after `passes` passes, and the other one.
+    fn buffers(&self, passes: usize) -> (sys::CUdeviceptr, sys::CUdeviceptr) {
+        buffers(self.ptr, *self.scratch.device_ptr(), passes)
+    }
+}
+
+/// The buffer out of `ptr` & `scratch` an array is in after `passes` passes, and the other one.
+fn buffers(
+    ptr: sys::CUdeviceptr,
+    scratch: sys::CUdeviceptr,
+    passes: usize,
+) -> (sys::CUdeviceptr, sys::CUdeviceptr) {
+    match passes % 2 {
+        0 => (ptr, scratch),
+        _ => (scratch, ptr),
+    }
+}
+
+/// The segment ids are written to `ids`, but need to be in the buffer the keys are in
+/// after `passes` passes. Returns the `(dst, src)` to copy them with, if they aren't.
+fn segment_ids_copy(
+    ids: sys::CUdeviceptr,
+    scratch: sys::CUdeviceptr,
+    passes: usize,
+) -> Option<(sys::CUdeviceptr, sys::CUdeviceptr)> {
+    let (current, _) = buffers(ids, scratch, passes);
+    (current != ids).then_some((current, ids))
+}
+
+impl CudaDevice {
+    /// Sorts `keys` in ascending order.
+    ///
+    /// Floats are ordered by their bits, so `-0.0` comes before `0.0`, and NaNs are
+    /// at the start or end depending on their sign.
+    ///
+    /// ```rust
+    /// # use cudarc::driver::*;
+    /// let dev = CudaDevice::new(0).unwrap();
+    /// let mut keys = dev.htod_copy(vec![3.0f32, -1.5, 2.0, -4.0]).unwrap();
+    /// dev.sort(&mut keys).unwrap();
+    /// assert_eq!(dev.dtoh_sync_copy(&keys).unwrap(), [-4.0, -1.5, 2.0, 3.0]);
+    /// ```
+    pub fn sort<K: RadixKey, Keys: DevicePtrMut<K>>(
+        self: &Arc<Self>,
+        keys: &mut Keys,
+    ) -> Result<(), CompileAndLoadError> {
+        let n = keys.len();
+        self.radix_sort(KeyType::of::<K>(), *keys.device_ptr_mut(), None, n, None)
+    }
+
+    /// Sorts `keys` in ascending order, moving `values` along with them. The sort is stable,
+    /// so values with equal keys keep their order.
+    ///
+    /// ```rust
+    /// # use cudarc::driver::*;
+    /// let dev = CudaDevice::new(0).unwrap();
+    /// let mut keys = dev.htod_copy(vec![2u32, 1, 2, 0]).unwrap();
+    /// let mut values = dev.htod_copy(vec![0usize, 1, 2, 3]).unwrap();
+    /// dev.sort_pairs(&mut keys, &mut values).unwrap();
+    /// assert_eq!(dev.dtoh_sync_copy(&keys).unwrap(), [0, 1, 2, 2]);
+    /// assert_eq!(dev.dtoh_sync_copy(&values).unwrap(), [3, 1, 0, 2]);
+    /// ```
+    ///
+    /// # Panics
+    /// If `keys` and `values` have different lengths, or `V` isn't 1, 2, 4, 8 or 16 bytes.
+    pub fn sort_pairs<
+        K: RadixKey,
+        V: DeviceRepr,
+        Keys: DevicePtrMut<K>,
+        Values: DevicePtrMut<V>,
+    >(
+        self: &Arc<Self>,
+        keys: &mut Keys,
+        values: &mut Values,
+    ) -> Result<(), CompileAndLoadError> {
+        assert_eq!(keys.len(), values.len());
+        let n = keys.len();
+        let values = (*values.device_ptr_mut(), std::mem::size_of::<V>());
+        self.radix_sort(
+            KeyType::of::<K>(),
+            *keys.device_ptr_mut(),
+            Some(values),
+            n,
+            None,
+        )
+    }
+
+    /// Sorts each segment `offsets[s]..offsets[s + 1]` of `keys` on its own.
+    ///
+    /// ```rust
+    /// # use cudarc::driver::*;
+    /// let dev = CudaDevice::new(0).unwrap();
+    /// let mut keys = dev.htod_copy(vec![5i32, 1, 3, 9, -2, 0]).unwrap();
+    /// dev.sort_segments(&mut keys, &[0, 3, 3, 6]).unwrap();
+    /// assert_eq!(dev.dtoh_sync_copy(&keys).unwrap(), [1, 3, 5, -2, 0, 9]);
+    /// ```
+    ///
+    /// # Panics
+    /// If `offsets` doesn't start at 0, end at the length of `keys` and never decrease.
+    pub fn sort_segments<K: RadixKey, Keys: DevicePtrMut<K>>(
+        self: &Arc<Self>,
+        keys: &mut Keys,
+        offsets: &[usize],
+    ) -> Result<(), CompileAndLoadError> {
+        let n = keys.len();
+        let key = KeyType::of::<K>();
+        self.radix_sort(key, *keys.device_ptr_mut(), None, n, Some(offsets))
+    }
+
+    /// Sorts each segment `offsets[s]..offsets[s + 1]` of `keys` on its own, moving `values`
+    /// along with them, see [CudaDevice::sort_pairs()].
+    ///
+    /// # Panics
+    /// Like [CudaDevice::sort_pairs()] and [CudaDevice::sort_segments()].
+    pub fn sort_segments_pairs<
+        K: RadixKey,
+        V: DeviceRepr,
+        Keys: DevicePtrMut<K>,
+        Values: DevicePtrMut<V>,
+    >(
+        self: &Arc<Self>,
+        keys: &mut Keys,
+        values: &mut Values,
+        offsets: &[usize],
+    ) -> Result<(), CompileAndLoadError> {
+        assert_eq!(keys.len(), values.len());
+        let n = keys.len();
+        let key = KeyType::of::<K>();
+        let values = (*values.device_ptr_mut(), std::mem::size_of::<V>());
+        self.radix_sort(key, *keys.device_ptr_mut(), Some(values), n, Some(offsets))
+    }
+
+    fn radix_sort(
+        self: &Arc<Self>,
+        key: KeyType,
+        keys: sys::CUdeviceptr,
+        values: Option<(sys::CUdeviceptr, usize)>,
+        n: usize,
+        offsets: Option<&[usize]>,
+    ) -> Result<(), CompileAndLoadError> {
+        if let Some((_, size)) = values {
+            assert!(
+                opaque_type(size).is_some(),
+                "can't sort values of {size} bytes"
+            );
+        }
+        if let Some(offsets) = offsets {
+            assert_eq!(offsets.first(), Some(&0), "offsets must start at 0");
+            assert_eq!(offsets.last(), Some(&n), "offsets must end at the length");
+            assert!(
+                offsets.windows(2).all(|w| w[0] <= w[1]),
+                "offsets must not decrease"
+            );
+        }
+        let num_segments = offsets.map_or(1, |o| o.len() - 1);
+        if n <= 1 {
+            return Ok(());
+        }
+
+        let num_blocks = n.div_ceil(RADIX);
+        let counts = unsafe { self.alloc::<u64>(RADIX * num_blocks) }?;
+        let scanned = unsafe { self.alloc::<u64>(RADIX * num_blocks) }?;
+
+        let mut arrays = std::vec![SortArray::new(self, keys, n, key.size)?];
+        if let Some((ptr, size)) = values {
+            arrays.push(SortArray::new(self, ptr, n, size)?);
+        }
+
+        let mut passes = 0;
+        for shift in (0..key.size * 8).step_by(8) {
+            self.radix_pass(&key, &arrays, passes, shift, n, &counts, &scanned)?;
+            passes += 1;
+        }
+
+        if num_segments > 1 {
+            assert!(num_segments <= u32::MAX as usize, "too many segments");
+            let segments = unsafe { self.alloc::<u32>(n) }?;
+            let segment_offsets = self.htod_sync_copy(offsets.unwrap())?;
+            let func = self.generated_func("segment_ids", segment_ids_src(), false)?;
+            let args = KernelArgs::new()
+                .arg(n)
+                .arg(num_segments)
+                .arg(*segment_offsets.device_ptr())
+                .arg(*segments.device_ptr());
+            unsafe { func.launch(grid_stride_cfg(n), args) }?;
+
+            // the ids need to be in the same buffer as the keys currently are
+            let segment_array = SortArray::new(self, *segments.device_ptr(), n, 4)?;
+            let scratch = *segment_array.scratch.device_ptr();
+            if let Some((dst, src)) = segment_ids_copy(*segments.device_ptr(), scratch, passes) {
+                unsafe { result::memcpy_dtod_async(dst, src, n * 4, self.stream) }?;
+            }
+            arrays.insert(0, segment_array);
+
+            // only sort by as many bits as the largest segment id has
+            let segment_bits = (usize::BITS - (num_segments - 1).leading_zeros()) as usize;
+            let segment_key = KeyType::of::<u32>();
+            for shift in (0..segment_bits).step_by(8) {
+                self.radix_pass(&segment_key, &arrays, passes, shift, n, &counts, &scanned)?;
+                passes += 1;
+            }
+            arrays.remove(0);
+        }
+
+        if passes % 2 == 1 {
+            for array in arrays.iter() {
+                let (current, original) = array.buffers(passes);
+                unsafe {
+                    result::memcpy_dtod_async(original, current, n * array.size, self.stream)
+                }?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Sorts by the 8 bits at `shift` of the key in `arrays[0]`, moving the other arrays
+    /// along with it. Moves from the buffers of pass `pass` into the other ones.
+    #[allow(clippy::too_many_arguments)]
+    fn radix_pass(
+        self: &Arc<Self>,
+        key: &KeyType,
+        arrays: &[SortArray],
+        pass: usize,
+        shift: usize,
+        n: usize,
+        counts: &CudaSlice<u64>,
+        scanned: &CudaSlice<u64>,
+    ) -> Result<(), CompileAndLoadError> {
+        let num_blocks = n.div_ceil(RADIX);
+        let cfg = LaunchConfig {
+            grid_dim: (num_blocks.try_into().unwrap(), 1, 1),
+            block_dim: (RADIX as u32, 1, 1),
+            shared_mem_bytes: 0,
+        };
+        let needs_headers = key.header.is_some();
+        let (keys_in, keys_out) = arrays[0].buffers(pass);
+
+        let func = self.generated_func("radix_histogram", histogram_src(key), needs_headers)?;
+        let args = KernelArgs::new()
+            .arg(n)
+            .arg(shift as u32)
+            .arg(keys_in)
+            .arg(*counts.device_ptr());
+        unsafe { func.launch(cfg, args) }?;
+
+        self.scan::<u64>(
+            *counts.device_ptr(),
+            *scanned.device_ptr(),
+            RADIX * num_blocks,
+        )?;
+
+        let payloads: Vec<&str> = arrays[1..]
+            .iter()
+            .map(|a| opaque_type(a.size).unwrap())
+            .collect();
+        let src = scatter_src(key, &payloads);
+        let func = self.generated_func("radix_scatter", src, needs_headers)?;
+        let mut args = KernelArgs::new()
+            .arg(n)
+            .arg(shift as u32)
+            .arg(*counts.device_ptr())
+            .arg(*scanned.device_ptr())
+            .arg(keys_in)
+            .arg(keys_out);
+        for array in arrays[1..].iter() {
+            let (inp, out) = array.buffers(pass);
+            args.push(inp);
+            args.push(out);
+        }
+        unsafe { func.launch(cfg, args) }?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scatter_src() {
+        let src = scatter_src(&KeyType::of::<i32>(), &["bytes16", "unsigned int"]);
+        assert!(src.starts_with(BYTES16));
+        assert!(src.contains("const unsigned int bits = (unsigned int)k ^ 0x80000000u;"));
+        assert!(src.contains("const bytes16 *p0_in, bytes16 *p0_out"));
+        assert!(src.contains("p1_out[pos] = p1_in[i];"));
+        assert!(!histogram_src(&KeyType::of::<u8>()).contains("#include"));
+    }
+
+    #[test]
+    fn test_segment_ids_copy() {
+        let (ids, scratch) = (0x1000, 0x2000);
+        assert_eq!(buffers(ids, scratch, 0), (ids, scratch));
+        assert_eq!(buffers(ids, scratch, 3), (scratch, ids));
+        // every key of 2, 4 or 8 bytes takes an even number of passes
+        assert_eq!(segment_ids_copy(ids, scratch, 2), None);
+        assert_eq!(segment_ids_copy(ids, scratch, 4), None);
+        assert_eq!(segment_ids_copy(ids, scratch, 1), Some((scratch, ids)));
+    }
+
+    #[cfg(feature = "f16")]
+    #[test]
+    fn test_half_key_src() {
+        let src = histogram_src(&KeyType::of::<half::f16>());
+        assert!(src.starts_with("#include \"cuda_fp16.h\"\n\n"));
+        assert!(src.contains("const __half *keys"));
+    }
+
+    #[test]
+    fn test_sort() {
+        let dev = CudaDevice::new(0).unwrap();
+        let n = 3 * RADIX + 7;
+        let keys_host: Vec<f64> = (0..n).map(|i| ((i * 7919) % 1013) as f64 - 500.5).collect();
+        let mut keys = dev.htod_copy(keys_host.clone()).unwrap();
+        dev.sort(&mut keys).unwrap();
+        let mut expected = keys_host;
+        expected.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        assert_eq!(dev.dtoh_sync_copy(&keys).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_sort_pairs_is_stable() {
+        let dev = CudaDevice::new(0).unwrap();
+        let n = 2 * RADIX + 1;
+        let keys_host: Vec<i8> = (0..n).map(|i| (i % 5) as i8 - 2).collect();
+        let values_host: Vec<u16> = (0..n as u16).collect();
+        let mut keys = dev.htod_copy(keys_host.clone()).unwrap();
+        let mut values = dev.htod_copy(values_host.clone()).unwrap();
+        dev.sort_pairs(&mut keys, &mut values).unwrap();
+
+        let mut expected: Vec<(i8, u16)> = keys_host.into_iter().zip(values_host).collect();
+        expected.sort_by_key(|&(k, _)| k);
+        let (keys_exp, values_exp): (Vec<i8>, Vec<u16>) = expected.into_iter().unzip();
+        assert_eq!(dev.dtoh_sync_copy(&keys).unwrap(), keys_exp);
+        assert_eq!(dev.dtoh_sync_copy(&values).unwrap(), values_exp);
+    }
+
+    #[test]
+    fn test_sort_segments_pairs() {
+        let dev = CudaDevice::new(0).unwrap();
+        let offsets = [0, 2, 2, 300, 301, 1000];
+        let keys_host: Vec<u64> = (0..1000).map(|i| (i * 31) % 97).collect();
+        let mut keys = dev.htod_copy(keys_host.clone()).unwrap();
+        let mut values = dev
+            .htod_copy(keys_host.iter().map(|&k| k as u128 * 3).collect())
+            .unwrap();
+        dev.sort_segments_pairs(&mut keys, &mut values, &offsets)
+            .unwrap();
+
+        let mut expected = keys_host;
+        for w in offsets.windows(2) {
+            expected[w[0]..w[1]].sort();
+        }
+        assert_eq!(dev.dtoh_sync_copy(&keys).unwrap(), expected);
+        let values_exp: Vec<u128> = expected.iter().map(|&k| k as u128 * 3).collect();
+        assert_eq!(dev.dtoh_sync_copy(&values).unwrap(), values_exp);
+    }
+}
@@ -0,0 +1,667 @@
+//! N-d views of device memory with a shape & strides: [StridedView] and [StridedViewMut].
+//!
+//! Views can be made over any [DevicePtr], and transposed, broadcast, sliced and indexed
+//! along axes without copying anything.
+
+use crate::driver::sys;
+
+use super::core::RangeHelper;
+use super::device_ptr::{DevicePtr, DevicePtrMut};
+
+use core::marker::PhantomData;
+use core::ops::RangeBounds;
+
+/// [MAX_DIMS] as a literal, so [STRIDED_VIEW_SRC] can be built from it with `concat!`.
+macro_rules! max_dims {
+    () => {
+        8
+    };
+}
+
+/// The most dimensions a [StridedLayout] can have.
+pub const MAX_DIMS: usize = max_dims!();
+
+/// Declares `template <typename T> struct StridedView`, which is what a [StridedView] or
+/// [StridedViewMut] is as a kernel param. Elements can be accessed with `view[i]` in row
+/// major order, or with `view(i, j)` for matrices.
+pub const STRIDED_VIEW_SRC: &str = concat!(
+    "template <typename T>
+struct StridedView {
+    T *ptr;
+    size_t ndim;
+    size_t shape[",
+    max_dims!(),
+    "];
+    size_t strides[",
+    max_dims!(),
+    "];
+
+    __device__ size_t numel() const {
+        size_t n = 1;
+        for (size_t d = 0; d < ndim; d++) n *= shape[d];
+        return n;
+    }
+
+    __device__ T &operator[](size_t i) const {
+        size_t offset = 0;
+        for (size_t d = ndim; d-- > 0;) {
+            offset += (i % shape[d]) * strides[d];
+            i /= shape[d];
+        }
+        return ptr[offset];
+    }
+
+    __device__ T &operator()(size_t i, size_t j) const {
+        return ptr[i * strides[0] + j * strides[1]];
+    }
+};
+"
+);
+
+/// The shape & strides (in elements) of up to [MAX_DIMS] dimensions. A stride of 0 repeats
+/// the same elements along that dimension.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct StridedLayout {
+    ndim: usize,
+    shape: [usize; MAX_DIMS],
+    strides: [usize; MAX_DIMS],
+}
+
+impl StridedLayout {
+    /// # Panics
+    /// If `shape` and `strides` have different lengths, or more than [MAX_DIMS].
+    pub fn new(shape: &[usize], strides: &[usize]) -> Self {
+        assert_eq!(shape.len(), strides.len());
+        assert!(shape.len() <= MAX_DIMS, "more than {MAX_DIMS} dims");
+        let mut layout = Self {
+            ndim: shape.len(),
+            shape: [0; MAX_DIMS],
+            strides: [0; MAX_DIMS],
+        };
+        layout.shape[..shape.len()].copy_from_slice(shape);
+        layout.strides[..strides.len()].copy_from_slice(strides);
+        layout
+    }
+
+    /// Contiguous, with the last dimension next to each other (C order).
+    pub fn row_major(shape: &[usize]) -> Self {
+        assert!(shape.len() <= MAX_DIMS, "more than {MAX_DIMS} dims");
+        let mut layout = Self::new(shape, &[0; MAX_DIMS][..shape.len()]);
+        let mut stride = 1;
+        for (d, &dim) in shape.iter().enumerate().rev() {
+            layout.strides[d] = stride;
+            stride *= dim;
+        }
+        layout
+    }
+
+    /// Contiguous, with the first dimension next to each other (Fortran order).
+    pub fn col_major(shape: &[usize]) -> Self {
+        assert!(shape.len() <= MAX_DIMS, "more than {MAX_DIMS} dims");
+        let mut layout = Self::new(shape, &[0; MAX_DIMS][..shape.len()]);
+        let mut stride = 1;
+        for (d, &dim) in shape.iter().enumerate() {
+            layout.strides[d] = stride;
+            stride *= dim;
+        }
+        layout
+    }
+
+    pub fn ndim(&self) -> usize {
+        self.ndim
+    }
+
+    pub fn shape(&self) -> &[usize] {
+        &self.shape[..self.ndim]
+    }
+
+    pub fn strides(&self) -> &[usize] {
+        &self.strides[..self.ndim]
+    }
+
+    /// The number of elements, i.e. the product of the shape.
+    pub fn numel(&self) -> usize {
+        self.shape().iter().product()
+    }
+
+    /// How many elements from the first one the layout reaches, i.e. one past the offset of
+    /// the last element. Saturates at `usize::MAX`.
+    pub fn extent(&self) -> usize {
+        if self.numel() == 0 {
+            return 0;
+        }
+        self.shape()
+            .iter()
+            .zip(self.strides())
+            .fold(1usize, |extent, (&dim, &stride)| {
+                extent.saturating_add((dim - 1).saturating_mul(stride))
+            })
+    }
+
+    /// Whether this is [StridedLayout::row_major()], ignoring the strides of dimensions of
+    /// size 1.
+    pub fn is_row_major(&self) -> bool {
+        self.is_contiguous_in((0..self.ndim).rev())
+    }
+
+    /// Whether this is [StridedLayout::col_major()], ignoring the strides of dimensions of
+    /// size 1.
+    pub fn is_col_major(&self) -> bool {
+        self.is_contiguous_in(0..self.ndim)
+    }
+
+    fn is_contiguous_in(&self, dims: impl Iterator<Item = usize>) -> bool {
+        let mut stride = 1;
+        for d in dims {
+            if self.shape[d] != 1 && self.strides[d] != stride {
+                return false;
+            }
+            stride *= self.shape[d];
+        }
+        true
+    }
+
+    /// The offset of the element at `index`, or `None` if it's out of bounds.
+    pub fn offset(&self, index: &[usize]) -> Option<usize> {
+        if index.len() != self.ndim {
+            return None;
+        }
+        let mut offset = 0;
+        for ((&i, &dim), &stride) in index.iter().zip(self.shape()).zip(self.strides()) {
+            if i >= dim {
+                return None;
+            }
+            offset += i * stride;
+        }
+        Some(offset)
+    }
+
+    /// Swaps dimensions `a` and `b`.
+    ///
+    /// # Panics
+    /// If `a` or `b` is out of bounds.
+    pub fn transpose(mut self, a: usize, b: usize) -> Self {
+        assert!(a < self.ndim && b < self.ndim, "axis out of bounds");
+        self.shape.swap(a, b);
+        self.strides.swap(a, b);
+        self
+    }
+
+    /// Reverses the dimensions, which is the transpose of a matrix.
+    pub fn t(mut self) -> Self {
+        self.shape[..self.ndim].reverse();
+        self.strides[..self.ndim].reverse();
+        self
+    }
+
+    /// Reorders the dimensions, so dimension `d` is the old dimension `axes[d]`.
+    ///
+    /// # Panics
+    /// If `axes` isn't a permutation of `0..self.ndim()`.
+    pub fn permute(self, axes: &[usize]) -> Self {
+        assert_eq!(axes.len(), self.ndim);
+        let mut seen = [false; MAX_DIMS];
+        let mut layout = self;
+        for (d, &axis) in axes.iter().enumerate() {
+            assert!(
+                axis < self.ndim && !seen[axis],
+                "{axes:?} isn't a permutation"
+            );
+            seen[axis] = true;
+            layout.shape[d] = self.shape[axis];
+            layout.strides[d] = self.strides[axis];
+        }
+        layout
+    }
+
+    /// Broadcasts to `shape` like numpy: dimensions are matched from the last one, and
+    /// dimensions of size 1 (or missing ones at the front) are repeated with a stride of 0.
+    ///
+    /// Returns `None` if a dimension doesn't match and isn't 1, or `shape` has fewer
+    /// dimensions than `self`, or more than [MAX_DIMS].
+    pub fn broadcast(&self, shape: &[usize]) -> Option<Self> {
+        if shape.len() < self.ndim || shape.len() > MAX_DIMS {
+            return None;
+        }
+        let new_dims = shape.len() - self.ndim;
+        let mut strides = [0; MAX_DIMS];
+        for (d, &dim) in self.shape().iter().enumerate() {
+            strides[new_dims + d] = match dim {
+                _ if dim == shape[new_dims + d] => self.strides[d],
+                1 => 0,
+                _ => return None,
+            };
+        }
+        Some(Self::new(shape, &strides[..shape.len()]))
+    }
+
+    /// The offset of `range` along `axis`, and the layout of it.
+    ///
+    /// Returns `None` if `range` is out of bounds or empty.
+    ///
+    /// # Panics
+    /// If `axis` is out of bounds.
+    pub fn slice(&self, axis: usize, range: impl RangeBounds<usize>) -> Option<(usize, Self)> {
+        assert!(axis < self.ndim, "axis out of bounds");
+        range.bounds(..self.shape[axis]).map(|(start, end)| {
+            let mut layout = *self;
+            layout.shape[axis] = 1 + end - start;
+            (start * self.strides[axis], layout)
+        })
+    }
+
+    /// The offset of `index` along `axis`, and the layout of the remaining dimensions.
+    ///
+    /// Returns `None` if `index` is out of bounds.
+    ///
+    /// # Panics
+    /// If `axis` is out of bounds.
+    pub fn select(&self, axis: usize, index: usize) -> Option<(usize, Self)> {
+        assert!(axis < self.ndim, "axis out of bounds");
+        if index >= self.shape[axis] {
+            return None;
+        }
+        let mut layout = *self;
+        layout.shape.copy_within(axis + 1.., axis);
+        layout.strides.copy_within(axis + 1.., axis);
+        layout.shape[MAX_DIMS - 1] = 0;
+        layout.strides[MAX_DIMS - 1] = 0;
+        layout.ndim -= 1;
+        Some((index * self.strides[axis], layout))
+    }
+
+    /// This as a matrix BLAS libraries like cuBLAS can use, if it's 2d and either
+    /// dimension is contiguous.
+    pub fn matrix(&self) -> Option<MatrixLayout> {
+        let &[rows, cols] = self.shape() else {
+            return None;
+        };
+        let &[row_stride, col_stride] = self.strides() else {
+            return None;
+        };
+        // the strides of dimensions of size 1 don't matter
+        let ld = if cols == 1 { rows.max(1) } else { col_stride };
+        if (rows == 1 || row_stride == 1) && ld >= rows.max(1) {
+            return Some(MatrixLayout {
+                rows,
+                cols,
+                ld,
+                transposed: false,
+            });
+        }
+        let ld = if rows == 1 { cols.max(1) } else { row_stride };
+        if (cols == 1 || col_stride == 1) && ld >= cols.max(1) {
+            return Some(MatrixLayout {
+                rows,
+                cols,
+                ld,
+                transposed: true,
+            });
+        }
+        None
+    }
+}
+
+/// A `rows x cols` matrix the way BLAS libraries describe them, see [StridedLayout::matrix()].
+///
+/// The memory is a column major matrix with leading dimension `ld`, i.e. columns `ld`
+/// elements apart. If `transposed` it's the `cols x rows` transpose that's stored column major
+/// (so the matrix itself is row major), which cuBLAS takes as `CUBLAS_OP_T`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MatrixLayout {
+    pub rows: usize,
+    pub cols: usize,
+    pub ld: usize,
+    pub transposed: bool,
+}
+
+/// An immutable N-d view of any [DevicePtr], with a [StridedLayout].
+///
+/// Views can be transposed, broadcast, sliced and indexed along axes without copying
+/// anything. Passed to a kernel (by reference, like a [super::CudaView]) they're a struct of
+/// pointer, shape & strides, declared for cuda source by [STRIDED_VIEW_SRC]:
+///
+/// ```rust
+/// # use cudarc::{driver::*, nvrtc::*};
+/// let dev = CudaDevice::new(0).unwrap();
+/// let src = std::format!("{STRIDED_VIEW_SRC}
+/// extern \"C\" __global__ void double_it(StridedView<float> a) {{
+///     for (size_t i = 0; i < a.numel(); i++) a[i] *= 2.0f;
+/// }}");
+/// dev.load_ptx(compile_ptx(src).unwrap(), "double_it", &["double_it"]).unwrap();
+/// let f = dev.get_func("double_it", "double_it").unwrap();
+///
+/// let mut a = dev.htod_copy(vec![1.0f32, 2.0, 3.0, 4.0, 5.0, 6.0]).unwrap();
+/// // the second column of the 2x3 `a`
+/// let mut column = StridedViewMut::row_major(&mut a, &[2, 3]).unwrap().select(1, 1);
+/// unsafe { f.launch(LaunchConfig::for_num_elems(1), (&mut column,)) }.unwrap();
+/// assert_eq!(dev.dtoh_sync_copy(&a).unwrap(), [1.0, 4.0, 3.0, 4.0, 10.0, 6.0]);
+/// ```
+///
+/// [StridedView::as_raw_parts()] gives the pointer & layout, e.g. for another library.
+#[repr(C)]
+pub struct StridedView<'a, T> {
+    pub(crate) ptr: sys::CUdeviceptr,
+    pub(crate) layout: StridedLayout,
+    pub(crate) marker: PhantomData<&'a [T]>,
+}
+
+impl<'a, T> Clone for StridedView<'a, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'a, T> Copy for StridedView<'a, T> {}
+
+/// A mutable N-d view of any [DevicePtrMut], see [StridedView].
+///
+/// Nothing stops dimensions from overlapping (e.g. with a stride of 0), in which case
+/// kernels writing to different elements can write to the same memory.
+#[repr(C)]
+pub struct StridedViewMut<'a, T> {
+    pub(crate) ptr: sys::CUdeviceptr,
+    pub(crate) layout: StridedLayout,
+    pub(crate) marker: PhantomData<&'a mut [T]>,
+}
+
+impl<'a, T> StridedView<'a, T> {
+    /// A view of `src` with `layout`, starting at the first element of `src`.
+    ///
+    /// Returns `None` if `layout` reaches past the end of `src`.
+    pub fn new<Src: DevicePtr<T>>(src: &'a Src, layout: StridedLayout) -> Option<Self> {
+        (layout.extent() <= src.len()).then(|| Self {
+            ptr: *src.device_ptr(),
+            layout,
+            marker: PhantomData,
+        })
+    }
+
+    /// See [StridedLayout::row_major()] and [StridedView::new()].
+    pub fn row_major<Src: DevicePtr<T>>(src: &'a Src, shape: &[usize]) -> Option<Self> {
+        Self::new(src, StridedLayout::row_major(shape))
+    }
+
+    /// See [StridedLayout::col_major()] and [StridedView::new()].
+    pub fn col_major<Src: DevicePtr<T>>(src: &'a Src, shape: &[usize]) -> Option<Self> {
+        Self::new(src, StridedLayout::col_major(shape))
+    }
+}
+
+impl<'a, T> StridedViewMut<'a, T> {
+    /// A view of `src` with `layout`, starting at the first element of `src`.
+    ///
+    /// Returns `None` if `layout` reaches past the end of `src`.
+    pub fn new<Src: DevicePtrMut<T>>(src: &'a mut Src, layout: StridedLayout) -> Option<Self> {
+        (layout.extent() <= src.len()).then(|| Self {
+            ptr: *src.device_ptr_mut(),
+            layout,
+            marker: PhantomData,
+        })
+    }
+
+    /// See [StridedLayout::row_major()] and [StridedViewMut::new()].
+    pub fn row_major<Src: DevicePtrMut<T>>(src: &'a mut Src, shape: &[usize]) -> Option<Self> {
+        Self::new(src, StridedLayout::row_major(shape))
+    }
+
+    /// See [StridedLayout::col_major()] and [StridedViewMut::new()].
+    pub fn col_major<Src: DevicePtrMut<T>>(src: &'a mut Src, shape: &[usize]) -> Option<Self> {
+        Self::new(src, StridedLayout::col_major(shape))
+    }
+
+    /// An immutable view of all of `self`.
+    pub fn as_view(&self) -> StridedView<'_, T> {
+        StridedView {
+            ptr: self.ptr,
+            layout: self.layout,
+            marker: PhantomData,
+        }
+    }
+
+    /// A shorter lived [StridedViewMut] of all of `self`, so `self` can be used again after it.
+    pub fn reborrow(&mut self) -> StridedViewMut<'_, T> {
+        StridedViewMut {
+            ptr: self.ptr,
+            layout: self.layout,
+            marker: PhantomData,
+        }
+    }
+}
+
+macro_rules! impl_strided_view {
+    ($View:ident) => {
+        impl<'a, T> $View<'a, T> {
+            pub fn layout(&self) -> &StridedLayout {
+                &self.layout
+            }
+
+            pub fn ndim(&self) -> usize {
+                self.layout.ndim()
+            }
+
+            pub fn shape(&self) -> &[usize] {
+                self.layout.shape()
+            }
+
+            pub fn strides(&self) -> &[usize] {
+                self.layout.strides()
+            }
+
+            pub fn numel(&self) -> usize {
+                self.layout.numel()
+            }
+
+            /// See [StridedLayout::matrix()].
+            pub fn matrix(&self) -> Option<MatrixLayout> {
+                self.layout.matrix()
+            }
+
+            /// The device pointer of the first element, and the layout of the elements from
+            /// it. The view can reach [StridedLayout::extent()] elements from the pointer.
+            pub fn as_raw_parts(&self) -> (sys::CUdeviceptr, &StridedLayout) {
+                (self.ptr, &self.layout)
+            }
+
+            /// The same memory with `layout`, `offset` elements further.
+            fn with_layout(self, offset: usize, layout: StridedLayout) -> Self {
+                Self {
+                    ptr: self.ptr + (offset * std::mem::size_of::<T>()) as u64,
+                    layout,
+                    marker: PhantomData,
+                }
+            }
+
+            /// See [StridedLayout::transpose()].
+            pub fn transpose(self, a: usize, b: usize) -> Self {
+                let layout = self.layout.transpose(a, b);
+                self.with_layout(0, layout)
+            }
+
+            /// See [StridedLayout::t()].
+            pub fn t(self) -> Self {
+                let layout = self.layout.t();
+                self.with_layout(0, layout)
+            }
+
+            /// See [StridedLayout::permute()].
+            pub fn permute(self, axes: &[usize]) -> Self {
+                let layout = self.layout.permute(axes);
+                self.with_layout(0, layout)
+            }
+
+            /// See [StridedLayout::broadcast()].
+            pub fn broadcast(self, shape: &[usize]) -> Option<Self> {
+                let layout = self.layout.broadcast(shape)?;
+                Some(self.with_layout(0, layout))
+            }
+
+            /// The sub-view of `range` along `axis`.
+            ///
+            /// # Panics
+            /// If `axis` or `range` is out of bounds, or `range` is empty.
+            pub fn slice(self, axis: usize, range: impl RangeBounds<usize>) -> Self {
+                self.try_slice(axis, range).unwrap()
+            }
+
+            /// Fallible version of [Self::slice()], see [StridedLayout::slice()].
+            pub fn try_slice(self, axis: usize, range: impl RangeBounds<usize>) -> Option<Self> {
+                let (offset, layout) = self.layout.slice(axis, range)?;
+                Some(self.with_layout(offset, layout))
+            }
+
+            /// The sub-view at `index` along `axis`, without that dimension. E.g. a row of a
+            /// matrix is `select(0, row)`.
+            ///
+            /// # Panics
+            /// If `axis` or `index` is out of bounds.
+            pub fn select(self, axis: usize, index: usize) -> Self {
+                self.try_select(axis, index).unwrap()
+            }
+
+            /// Fallible version of [Self::select()], see [StridedLayout::select()].
+            pub fn try_select(self, axis: usize, index: usize) -> Option<Self> {
+                let (offset, layout) = self.layout.select(axis, index)?;
+                Some(self.with_layout(offset, layout))
+            }
+        }
+    };
+}
+
+impl_strided_view!(StridedView);
+impl_strided_view!(StridedViewMut);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::driver::{CudaDevice, LaunchAsync, LaunchConfig};
+    use crate::nvrtc::compile_ptx;
+    use std::format;
+
+    #[test]
+    fn test_layouts() {
+        let a = StridedLayout::row_major(&[2, 3, 4]);
+        assert_eq!(a.strides(), [12, 4, 1]);
+        assert_eq!(StridedLayout::col_major(&[2, 3, 4]).strides(), [1, 2, 6]);
+        assert!(a.is_row_major() && !a.is_col_major());
+        assert_eq!((a.numel(), a.extent()), (24, 24));
+        assert_eq!(a.offset(&[1, 2, 3]), Some(23));
+        assert_eq!(a.offset(&[2, 0, 0]), None);
+
+        let t = a.t();
+        assert_eq!((t.shape(), t.strides()), (&[4, 3, 2][..], &[1, 4, 12][..]));
+        assert!(t.is_col_major());
+        assert_eq!(a.transpose(0, 2), t);
+        assert_eq!(a.permute(&[2, 0, 1]).strides(), [1, 12, 4]);
+
+        let (offset, s) = a.slice(1, 1..).unwrap();
+        assert_eq!((offset, s.shape(), s.extent()), (4, &[2, 2, 4][..], 20));
+        assert!(!s.is_row_major());
+        assert!(a.slice(1, 3..).is_none());
+
+        let (offset, s) = a.select(1, 2).unwrap();
+        assert_eq!(
+            (offset, s.shape(), s.strides()),
+            (8, &[2, 4][..], &[12, 1][..])
+        );
+        assert!(a.select(0, 2).is_none());
+        assert_eq!(StridedLayout::new(&[0, 3], &[3, 1]).extent(), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "more than 8 dims")]
+    fn test_too_many_dims() {
+        StridedLayout::row_major(&[1; MAX_DIMS + 1]);
+    }
+
+    #[test]
+    fn test_strided_view_src() {
+        let dims = format!("[{MAX_DIMS}];");
+        assert_eq!(STRIDED_VIEW_SRC.matches(&dims).count(), 2);
+    }
+
+    #[test]
+    fn test_broadcast() {
+        let row = StridedLayout::row_major(&[1, 3]);
+        let b = row.broadcast(&[2, 4, 3]).unwrap();
+        assert_eq!((b.strides(), b.extent()), (&[0, 0, 1][..], 3));
+        assert!(row.broadcast(&[2]).is_none());
+        assert!(row.broadcast(&[4, 2]).is_none());
+        assert!(StridedLayout::row_major(&[3])
+            .broadcast(&[1; MAX_DIMS + 1])
+            .is_none());
+    }
+
+    #[test]
+    fn test_matrix() {
+        let a = StridedLayout::row_major(&[4, 6]);
+        let m = |rows, cols, ld, transposed| MatrixLayout {
+            rows,
+            cols,
+            ld,
+            transposed,
+        };
+        assert_eq!(a.matrix(), Some(m(4, 6, 6, true)));
+        assert_eq!(a.t().matrix(), Some(m(6, 4, 6, false)));
+        let (_, sub) = a.slice(1, 2..5).unwrap();
+        assert_eq!(sub.matrix(), Some(m(4, 3, 6, true)));
+        let (_, column) = a.slice(1, 2..3).unwrap();
+        assert_eq!(column.matrix(), Some(m(4, 1, 6, true)));
+        assert_eq!(
+            StridedLayout::new(&[4, 1], &[1, 0]).matrix(),
+            Some(m(4, 1, 4, false))
+        );
+        assert_eq!(StridedLayout::new(&[4, 6], &[0, 1]).matrix(), None);
+        assert_eq!(StridedLayout::new(&[4, 6], &[2, 12]).matrix(), None);
+        assert_eq!(StridedLayout::row_major(&[2, 2, 2]).matrix(), None);
+    }
+
+    #[test]
+    fn test_strided_views() {
+        let dev = CudaDevice::new(0).unwrap();
+        let a = dev.alloc_zeros::<f32>(24).unwrap();
+        assert!(StridedView::row_major(&a, &[5, 5]).is_none());
+
+        let view = StridedView::row_major(&a, &[4, 6]).unwrap();
+        let row = view.select(0, 2).slice(0, 1..4);
+        let (ptr, layout) = row.as_raw_parts();
+        assert_eq!(ptr, *a.device_ptr() + 13 * 4);
+        assert_eq!((layout.shape(), layout.extent()), (&[3][..], 3));
+        let column = view.t().select(0, 5);
+        let (ptr, layout) = column.as_raw_parts();
+        assert_eq!(ptr, *a.device_ptr() + 5 * 4);
+        assert_eq!((layout.strides(), layout.extent()), (&[6][..], 19));
+    }
+
+    #[test]
+    fn test_strided_view_kernel() {
+        let dev = CudaDevice::new(0).unwrap();
+        let src = format!(
+            "{STRIDED_VIEW_SRC}
+extern \"C\" __global__ void add_t(StridedView<float> out, const StridedView<float> a) {{
+    const size_t i = threadIdx.x;
+    if (i < out.numel()) out[i] += a(i / out.shape[1], i % out.shape[1]);
+}}"
+        );
+        dev.load_ptx(compile_ptx(src).unwrap(), "add_t", &["add_t"])
+            .unwrap();
+        let f = dev.get_func("add_t", "add_t").unwrap();
+
+        let a = dev.htod_copy((0..12).map(|x| x as f32).collect()).unwrap();
+        let mut out = dev.alloc_zeros::<f32>(12).unwrap();
+        // the bottom right 2x2 of the transpose of the 3x4 `a`, into the top left of `out`
+        let a_t = StridedView::row_major(&a, &[3, 4]).unwrap().t();
+        let a_t = a_t.slice(0, 2..).slice(1, 1..);
+        let mut out_view = StridedViewMut::row_major(&mut out, &[3, 4])
+            .unwrap()
+            .slice(0, ..2)
+            .slice(1, ..2);
+        let cfg = LaunchConfig::for_num_elems(4);
+        unsafe { f.launch(cfg, (&mut out_view, &a_t)) }.unwrap();
+        assert_eq!(
+            dev.dtoh_sync_copy(&out).unwrap(),
+            [6.0, 10.0, 0.0, 0.0, 7.0, 11.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0]
+        );
+    }
+}
@@ -0,0 +1,190 @@
+//! Loading of the cuda libraries at runtime instead of linking them at build time.
+//!
+//! Enabled with the `dynamic-loading` feature. In this mode the `sys` functions of each
+//! module look up their symbol in a library opened on first use, so the build does not
+//! need the cuda toolkit and a binary can start on machines without it.
+//!
+//! Each library is searched for:
+//! 1. On the system's default library search path (e.g. `LD_LIBRARY_PATH`)
+//! 2. Under the directories in the `CUDA_PATH`, `CUDA_ROOT` and `CUDA_TOOLKIT_ROOT_DIR` environment variables
+//! 3. Under the standard cuda install locations (e.g. `/usr/local/cuda`)
+//!
+//! The safe constructors ([crate::driver::CudaDevice::new()], [crate::cublas::CudaBlas::new()],
+//! [crate::curand::CudaRng::new()] and [crate::nvrtc::compile_ptx()]) check that their
+//! library can be loaded, and that it exports every symbol used by the safe api, and return
+//! a `LoadError` variant of their error instead of panicking if not. See [LoadError].
+//!
+//! **Calling a `sys` function whose library or symbol is missing panics.**
+
+#[cfg(feature = "no-std")]
+use spin::Once as OnceLock;
+#[cfg(not(feature = "no-std"))]
+use std::sync::OnceLock;
+
+use std::{
+    path::{Path, PathBuf},
+    string::ToString,
+    vec::Vec,
+};
+
+/// The reason a cuda library could not be used.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LoadError {
+    /// None of the file names for `library` could be opened.
+    LibraryNotFound { library: &'static str },
+
+    /// `library` was opened, but does not export `symbol`.
+    SymbolMissing {
+        library: &'static str,
+        symbol: &'static str,
+    },
+}
+
+impl std::fmt::Display for LoadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::LibraryNotFound { library } => write!(
+                f,
+                "Unable to find the {library} library. Add its directory to the library search path or set CUDA_PATH."
+            ),
+            Self::SymbolMissing { library, symbol } => write!(
+                f,
+                "The {library} library does not export `{symbol}`, it may be too old."
+            ),
+        }
+    }
+}
+
+impl std::error::Error for LoadError {}
+
+/// A cuda library that is opened the first time it is used.
+pub struct DynamicLibrary {
+    name: &'static str,
+    file_names: &'static [&'static str],
+    required_symbols: &'static [&'static str],
+    library: OnceLock<Result<libloading::Library, LoadError>>,
+}
+
+impl DynamicLibrary {
+    /// - `name` is used in error messages
+    /// - `file_names` are tried in order until one can be opened
+    /// - `required_symbols` are checked for in [DynamicLibrary::load()]
+    pub const fn new(
+        name: &'static str,
+        file_names: &'static [&'static str],
+        required_symbols: &'static [&'static str],
+    ) -> Self {
+        Self {
+            name,
+            file_names,
+            required_symbols,
+            library: OnceLock::new(),
+        }
+    }
+
+    /// Opens the library if it isn't already, and checks that it exports
+    /// every required symbol. The result is cached.
+    pub fn load(&self) -> Result<&libloading::Library, LoadError> {
+        #[cfg(feature = "no-std")]
+        let library = self.library.call_once(|| self.open_checked());
+        #[cfg(not(feature = "no-std"))]
+        let library = self.library.get_or_init(|| self.open_checked());
+        library.as_ref().map_err(|e| *e)
+    }
+
+    fn open_checked(&self) -> Result<libloading::Library, LoadError> {
+        let library = self.open()?;
+        for &symbol in self.required_symbols {
+            let found = unsafe { library.get::<*const ()>(symbol.as_bytes()) };
+            if found.is_err() {
+                return Err(LoadError::SymbolMissing {
+                    library: self.name,
+                    symbol,
+                });
+            }
+        }
+        Ok(library)
+    }
+
+    /// Looks up `symbol` in the library.
+    ///
+    /// # Panics
+    /// If the library can't be loaded, or doesn't export `symbol`.
+    ///
+    /// # Safety
+    /// `T` must be the type of `symbol`.
+    pub unsafe fn get<T: Copy>(&self, symbol: &'static str) -> T {
+        let library = self.load().unwrap_or_else(|e| panic!("{e}"));
+        match library.get::<T>(symbol.as_bytes()) {
+            Ok(function) => *function,
+            Err(_) => panic!(
+                "{}",
+                LoadError::SymbolMissing {
+                    library: self.name,
+                    symbol
+                }
+            ),
+        }
+    }
+
+    fn open(&self) -> Result<libloading::Library, LoadError> {
+        let dirs = search_dirs();
+        for &file_name in self.file_names {
+            if let Ok(library) = unsafe { libloading::Library::new(file_name) } {
+                return Ok(library);
+            }
+            for dir in dirs.iter() {
+                let path = dir.join(file_name);
+                if path.is_file() {
+                    if let Ok(library) = unsafe { libloading::Library::new(&path) } {
+                        return Ok(library);
+                    }
+                }
+            }
+        }
+        Err(LoadError::LibraryNotFound { library: self.name })
+    }
+}
+
+fn search_dirs() -> Vec<PathBuf> {
+    let env_vars = ["CUDA_PATH", "CUDA_ROOT", "CUDA_TOOLKIT_ROOT_DIR"];
+    let roots = [
+        "/usr/local/cuda",
+        "/opt/cuda",
+        "/usr/lib/cuda",
+        "C:/Program Files/NVIDIA GPU Computing Toolkit",
+        "C:/CUDA",
+    ];
+    let subdirs = [
+        "",
+        "bin",
+        "lib/x64",
+        "lib64",
+        "lib",
+        "targets/x86_64-linux/lib",
+    ];
+
+    let env_vars = env_vars.iter().map(std::env::var).filter_map(Result::ok);
+    let roots = roots.iter().map(|r| r.to_string());
+    let roots: Vec<PathBuf> = env_vars.chain(roots).map(Into::into).collect();
+    roots
+        .iter()
+        .flat_map(|root| subdirs.iter().map(move |s| Path::new(root).join(s)))
+        .filter(|dir| dir.is_dir())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_missing_library() {
+        static LIBRARY: DynamicLibrary =
+            DynamicLibrary::new("missing", &["libcudarc_missing.so"], &[]);
+        assert_eq!(
+            LIBRARY.load().err(),
+            Some(LoadError::LibraryNotFound { library: "missing" })
+        );
+    }
+}
@@ -10,6 +10,12 @@
 //! 3. A `sys` module which contains the raw bindings
 //!
 //! Each module exports the safe API, and exposes each level if you want to use a different one.
+//!
+//! By default the cuda libraries are linked at build time. With the `dynamic-loading` feature
+//! they are instead loaded at runtime, see [dynamic_loading].
+//!
+//! The `cuda-12` feature enables the parts of the api that need cuda 12 or newer, such as
+//! compiling to LTO-IR with nvrtc.
 
 #![cfg_attr(feature = "no-std", no_std)]
 
@@ -18,11 +24,17 @@ extern crate alloc;
 #[cfg(feature = "no-std")]
 extern crate no_std_compat as std;
 
+// lets `#[derive(DeviceRepr)]` refer to `::cudarc` from inside this crate
+#[cfg(feature = "derive")]
+extern crate self as cudarc;
+
 #[cfg(feature = "cublas")]
 pub mod cublas;
 #[cfg(feature = "curand")]
 pub mod curand;
 #[cfg(feature = "driver")]
 pub mod driver;
+#[cfg(feature = "dynamic-loading")]
+pub mod dynamic_loading;
 #[cfg(feature = "nvrtc")]
 pub mod nvrtc;
@@ -0,0 +1,309 @@
+//! Compiling kernels ahead of time from a build script, to embed with
+//! [include_ptx!](crate::include_ptx).
+//!
+//! Add cudarc as a build dependency, and in `main()` of `build.rs`:
+//! ```no_run
+//! cudarc::nvrtc::build::compile_kernels().unwrap();
+//! ```
+//! This compiles every `kernels/*.cu` file of the crate to `$OUT_DIR/kernels/*.ptx`, so
+//! `kernels/sin.cu` can then be loaded with:
+//! ```ignore
+//! let ptx: Ptx = cudarc::include_ptx!("sin");
+//! ```
+//!
+//! The build script is re-run whenever a `.cu` file is added to `kernels/`, or one of
+//! them (or a header they `#include "..."`) changes.
+
+use super::includes::{cuda_roots, includes};
+use super::{compile_ptx_with_opts, CompileError, CompileOptions, Severity};
+
+use std::{
+    path::{Path, PathBuf},
+    println,
+    process::Command,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+/// Embeds a kernel compiled by [compile_kernels()] as a [Ptx](super::Ptx), by the name of
+/// its `.cu` file without the extension.
+///
+/// ```ignore
+/// // kernels/sin.cu
+/// let ptx = cudarc::include_ptx!("sin");
+/// dev.load_ptx(ptx, "sin", &["sin_kernel"])?;
+/// ```
+#[macro_export]
+macro_rules! include_ptx {
+    ($name:literal) => {
+        $crate::nvrtc::Ptx::from_src(include_str!(concat!(
+            env!("OUT_DIR"),
+            "/kernels/",
+            $name,
+            ".ptx"
+        )))
+    };
+}
+
+/// Which compiler [KernelBuild] uses.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Compiler {
+    /// Compiles in process with nvrtc, which needs to be available when building.
+    #[default]
+    Nvrtc,
+    /// Runs `nvcc --ptx`. The binary is `$NVCC` if set, otherwise the one in the cuda
+    /// toolkit (see `$CUDA_PATH`), otherwise `nvcc` from `$PATH`.
+    Nvcc,
+}
+
+/// Compiles the `.cu` files in a directory to ptx in `$OUT_DIR/kernels`. Only meant to be
+/// used from a build script, see [compile_kernels()] for the defaults.
+///
+/// ```no_run
+/// # use cudarc::nvrtc::{build::*, CompileOptions};
+/// KernelBuild {
+///     dir: "src/cuda".into(),
+///     options: CompileOptions {
+///         arch: Some("compute_80".into()),
+///         ..Default::default()
+///     },
+///     ..Default::default()
+/// }
+/// .compile()
+/// .unwrap();
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KernelBuild {
+    /// Relative to the crate's root. Defaults to `kernels`
+    pub dir: PathBuf,
+    pub compiler: Compiler,
+    /// `dir` is always added to `include_paths`. `headers` and `default_device` are
+    /// nvrtc only, so [Compiler::Nvcc] returns [BuildError::NvrtcOnly] if they're set.
+    pub options: CompileOptions,
+}
+
+impl Default for KernelBuild {
+    fn default() -> Self {
+        Self {
+            dir: "kernels".into(),
+            compiler: Compiler::Nvrtc,
+            options: Default::default(),
+        }
+    }
+}
+
+/// Compiles every `kernels/*.cu` file with nvrtc and the default [CompileOptions]. See
+/// [KernelBuild] to change any of that.
+pub fn compile_kernels() -> Result<Vec<PathBuf>, BuildError> {
+    KernelBuild::default().compile()
+}
+
+impl KernelBuild {
+    /// Compiles every `.cu` file in `dir`, returning the paths of the `.ptx` files written.
+    /// Warnings are passed on to cargo, which shows them for the crate being built.
+    pub fn compile(&self) -> Result<Vec<PathBuf>, BuildError> {
+        if self.compiler == Compiler::Nvcc {
+            if !self.options.headers.is_empty() {
+                return Err(BuildError::NvrtcOnly("headers"));
+            }
+            if self.options.default_device == Some(true) {
+                return Err(BuildError::NvrtcOnly("default_device"));
+            }
+        }
+
+        let out_dir = std::env::var_os("OUT_DIR").ok_or(BuildError::NoOutDir)?;
+        let out_dir = PathBuf::from(out_dir).join("kernels");
+        std::fs::create_dir_all(&out_dir).map_err(|e| BuildError::Io(out_dir.clone(), e))?;
+
+        let dir = match std::env::var_os("CARGO_MANIFEST_DIR") {
+            Some(root) => PathBuf::from(root).join(&self.dir),
+            None => self.dir.clone(),
+        };
+        println!("cargo:rerun-if-changed={}", dir.display());
+        if self.compiler == Compiler::Nvcc {
+            println!("cargo:rerun-if-env-changed=NVCC");
+        }
+
+        let mut sources = Vec::new();
+        let entries = std::fs::read_dir(&dir).map_err(|e| BuildError::Io(dir.clone(), e))?;
+        for entry in entries {
+            let path = entry.map_err(|e| BuildError::Io(dir.clone(), e))?.path();
+            if path.extension().is_some_and(|ext| ext == "cu") {
+                sources.push(path);
+            }
+        }
+        sources.sort();
+
+        let mut options = self.options.clone();
+        options.include_paths.push(dir.display().to_string());
+
+        let mut outputs = Vec::with_capacity(sources.len());
+        for src_path in sources {
+            let src = read(&src_path)?;
+            for dep in includes(&src_path, &src, &options.include_paths) {
+                println!("cargo:rerun-if-changed={}", dep.display());
+            }
+
+            let out = out_dir
+                .join(src_path.file_stem().unwrap())
+                .with_extension("ptx");
+            match self.compiler {
+                Compiler::Nvrtc => compile_nvrtc(&src_path, &src, &out, options.clone())?,
+                Compiler::Nvcc => compile_nvcc(&src_path, &out, options.clone())?,
+            }
+            outputs.push(out);
+        }
+        Ok(outputs)
+    }
+}
+
+fn compile_nvrtc(
+    src_path: &Path,
+    src: &str,
+    out: &Path,
+    options: CompileOptions,
+) -> Result<(), BuildError> {
+    let ptx = compile_ptx_with_opts(src, options).map_err(|err| BuildError::Compile {
+        path: src_path.to_path_buf(),
+        src: src.into(),
+        err,
+    })?;
+    for diagnostic in ptx.diagnostics() {
+        if diagnostic.severity >= Severity::Warning {
+            for line in diagnostic.render(Some(src)).lines() {
+                println!("cargo:warning={}: {line}", src_path.display());
+            }
+        }
+    }
+    ptx.to_src()
+        .and_then(|ptx| std::fs::write(out, ptx))
+        .map_err(|e| BuildError::Io(out.to_path_buf(), e))
+}
+
+fn compile_nvcc(src_path: &Path, out: &Path, options: CompileOptions) -> Result<(), BuildError> {
+    let nvcc = nvcc();
+    let options = CompileOptions {
+        // `--device-as-default-execution-space=false` isn't a flag nvcc knows either
+        default_device: None,
+        ..options
+    };
+    let output = Command::new(&nvcc)
+        .arg("--ptx")
+        .args(options.build())
+        .arg("-o")
+        .arg(out)
+        .arg(src_path)
+        .output()
+        .map_err(|e| BuildError::Io(nvcc, e))?;
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    if !output.status.success() {
+        return Err(BuildError::Nvcc {
+            path: src_path.to_path_buf(),
+            stderr: stderr.into_owned(),
+        });
+    }
+    for line in stderr.lines().filter(|l| !l.trim().is_empty()) {
+        println!("cargo:warning={}: {line}", src_path.display());
+    }
+    Ok(())
+}
+
+fn nvcc() -> PathBuf {
+    if let Some(nvcc) = std::env::var_os("NVCC") {
+        return nvcc.into();
+    }
+    for root in cuda_roots() {
+        let nvcc = root.join("bin").join("nvcc");
+        if nvcc.exists() || nvcc.with_extension("exe").exists() {
+            return nvcc;
+        }
+    }
+    "nvcc".into()
+}
+
+fn read(path: &Path) -> Result<String, BuildError> {
+    std::fs::read_to_string(path).map_err(|e| BuildError::Io(path.to_path_buf(), e))
+}
+
+/// An error from [KernelBuild::compile()].
+#[derive(Debug)]
+pub enum BuildError {
+    /// `OUT_DIR` isn't set, i.e. this isn't running in a build script
+    NoOutDir,
+    /// The [CompileOptions] field is set, but only [Compiler::Nvrtc] supports it
+    NvrtcOnly(&'static str),
+    /// Reading or writing the path failed (or running nvcc, for its path)
+    Io(PathBuf, std::io::Error),
+    /// nvrtc failed to compile the file at `path`
+    Compile {
+        path: PathBuf,
+        src: String,
+        err: CompileError,
+    },
+    /// nvcc failed to compile the file at `path`
+    Nvcc { path: PathBuf, stderr: String },
+}
+
+impl std::fmt::Display for BuildError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Compile { path, src, err } => {
+                let diagnostics = err.diagnostics();
+                if diagnostics.is_empty() {
+                    return write!(f, "failed to compile {}: {err}", path.display());
+                }
+                writeln!(f, "failed to compile {}", path.display())?;
+                for diagnostic in diagnostics {
+                    write!(f, "\n{}", diagnostic.render(Some(src)))?;
+                }
+                Ok(())
+            }
+            Self::Nvcc { path, stderr } => {
+                write!(f, "failed to compile {}:\n{stderr}", path.display())
+            }
+            Self::NvrtcOnly(field) => {
+                write!(
+                    f,
+                    "CompileOptions::{field} is only supported by nvrtc, not nvcc"
+                )
+            }
+            _ => write!(f, "{self:?}"),
+        }
+    }
+}
+
+impl std::error::Error for BuildError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nvcc_rejects_nvrtc_only_options() {
+        let build = KernelBuild {
+            compiler: Compiler::Nvcc,
+            options: CompileOptions {
+                headers: [("common.cuh".into(), "".into())].into(),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        assert!(matches!(
+            build.compile(),
+            Err(BuildError::NvrtcOnly("headers"))
+        ));
+
+        let build = KernelBuild {
+            compiler: Compiler::Nvcc,
+            options: CompileOptions {
+                default_device: Some(true),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        assert!(matches!(
+            build.compile(),
+            Err(BuildError::NvrtcOnly("default_device"))
+        ));
+    }
+}
@@ -0,0 +1,563 @@
+//! A cache of compiled ptx & cubins, kept in memory and optionally on disk, so that repeated
+//! compiles of the same source (e.g. on every start of a program) skip nvrtc.
+//!
+//! Entries are keyed by a [CacheKey], which hashes the source, the compile flags, the
+//! nvrtc version, and the contents of every header the source can include: the in-memory
+//! [CompileOptions::headers], and the files that `#include "..."` (in the source, a header,
+//! or [CompileOptions::pre_include]) finds in [CompileOptions::include_paths]. Editing an
+//! included file therefore changes the key. System headers (`#include <...>`) and files nvrtc
+//! finds by other means aren't hashed.
+//!
+//! ```rust
+//! # use cudarc::nvrtc::{cache::CompileCache, CompileOptions};
+//! let cache = CompileCache::new(64).with_dir(std::env::temp_dir().join("cudarc-doc-cache"));
+//! let src = "extern \"C\" __global__ void kernel() { }";
+//! let ptx = cache.compile_ptx(src, CompileOptions::default()).unwrap();
+//! // doesn't call nvrtc, even after a restart
+//! let ptx = cache.compile_ptx(src, CompileOptions::default()).unwrap();
+//! ```
+
+use super::includes::includes;
+use super::{
+    compile_cubin, compile_ptx_with_names, version, BinaryKind, CompileError, CompileOptions,
+    Cubin, Ptx,
+};
+
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+#[cfg(feature = "no-std")]
+use spin::RwLock;
+#[cfg(not(feature = "no-std"))]
+use std::sync::RwLock;
+
+use std::{
+    collections::VecDeque,
+    path::{Path, PathBuf},
+    string::{String, ToString},
+    vec::Vec,
+};
+
+/// Identifies the output of a compile. Two compiles with the same key produce the same output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct CacheKey(u128);
+
+impl CacheKey {
+    /// Hashes everything that affects the output of a compile:
+    /// - `kind` is the kind of output, e.g. `"ptx"`
+    /// - `src` is the source that was compiled
+    /// - `options` are the flags passed to nvrtc, see [CompileOptions]
+    /// - `nvrtc_version` is the (major, minor) version of nvrtc, see [version()]
+    ///
+    /// The hash is stable across runs & platforms, so it can be used to look up files on disk.
+    pub fn new<O: AsRef<str>>(
+        kind: &str,
+        src: &str,
+        options: &[O],
+        nvrtc_version: (i32, i32),
+    ) -> Self {
+        let mut hasher = Fnv128::default();
+        hasher.write_str(kind);
+        hasher.write_str(src);
+        hasher.write(&(options.len() as u64).to_le_bytes());
+        for option in options {
+            hasher.write_str(option.as_ref());
+        }
+        hasher.write(&nvrtc_version.0.to_le_bytes());
+        hasher.write(&nvrtc_version.1.to_le_bytes());
+        Self(hasher.0)
+    }
+
+    /// Also hashes a header the source can include, see [CompileOptions::headers].
+    pub fn with_header(self, name: &str, contents: &str) -> Self {
+        let mut hasher = Fnv128(self.0);
+        hasher.write_str(name);
+        hasher.write_str(contents);
+        Self(hasher.0)
+    }
+
+    /// The key of compiling `src` with `opts`, including the headers it can include (see the
+    /// [module docs](self)) and `name_expressions`.
+    fn from_opts<N: AsRef<str>>(
+        kind: &str,
+        src: &str,
+        opts: &CompileOptions,
+        name_expressions: &[N],
+        nvrtc_version: (i32, i32),
+    ) -> Self {
+        let mut options = opts.clone().build();
+        options.extend(
+            name_expressions
+                .iter()
+                .map(|expr| std::format!("name-expression={}", expr.as_ref())),
+        );
+        let key = Self::new(kind, src, &options, nvrtc_version);
+        let key = opts
+            .headers
+            .iter()
+            .fold(key, |key, (name, contents)| key.with_header(name, contents));
+
+        // everything that can pull in a file from `include_paths`
+        let mut includers = String::new();
+        for name in opts.pre_include.iter() {
+            includers.push_str(&std::format!("#include \"{name}\"\n"));
+        }
+        for contents in opts.headers.values() {
+            includers.push_str(contents);
+            includers.push('\n');
+        }
+        includers.push_str(src);
+        includes(Path::new(""), &includers, &opts.include_paths)
+            .into_iter()
+            .fold(key, |key, path| {
+                let contents = std::fs::read_to_string(&path).unwrap_or_default();
+                key.with_header(&path.display().to_string(), &contents)
+            })
+    }
+}
+
+impl core::fmt::Display for CacheKey {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{:032x}", self.0)
+    }
+}
+
+/// 128 bit FNV-1a. Unlike [std::collections::hash_map::DefaultHasher] its output is
+/// specified, so it doesn't change between rust versions.
+struct Fnv128(u128);
+
+impl Default for Fnv128 {
+    fn default() -> Self {
+        Self(0x6c62272e07bb014262b821756295c58d)
+    }
+}
+
+impl Fnv128 {
+    const PRIME: u128 = 0x0000000001000000000000000000013b;
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &b in bytes {
+            self.0 ^= b as u128;
+            self.0 = self.0.wrapping_mul(Self::PRIME);
+        }
+    }
+
+    /// Length prefixed, so that e.g. `("ab", "c")` and `("a", "bc")` hash differently.
+    fn write_str(&mut self, s: &str) {
+        self.write(&(s.len() as u64).to_le_bytes());
+        self.write(s.as_bytes());
+    }
+}
+
+/// A cache of compile outputs with a least recently used in-memory layer, and an optional
+/// directory that persists entries across runs.
+///
+/// Files in the directory are named after their [CacheKey], and are written atomically, so
+/// several processes can share a directory. The directory is never cleaned up by the cache.
+///
+/// See the [module docs](self) for an example.
+#[derive(Debug)]
+pub struct CompileCache {
+    dir: Option<PathBuf>,
+    capacity: usize,
+    /// Least recently used entries first
+    memory: RwLock<VecDeque<(CacheKey, Vec<u8>)>>,
+}
+
+impl CompileCache {
+    /// Creates a cache that keeps up to `capacity` entries in memory, and nothing on disk.
+    /// A `capacity` of 0 disables the in-memory layer.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            dir: None,
+            capacity,
+            memory: RwLock::new(VecDeque::new()),
+        }
+    }
+
+    /// Also stores entries as files in `dir`, which is created if it doesn't exist.
+    pub fn with_dir<P: Into<PathBuf>>(mut self, dir: P) -> Self {
+        self.dir = Some(dir.into());
+        self
+    }
+
+    /// The directory entries are stored in, if any.
+    pub fn dir(&self) -> Option<&Path> {
+        self.dir.as_deref()
+    }
+
+    /// Looks up `key` in memory, then on disk.
+    pub fn get(&self, key: &CacheKey) -> Option<Vec<u8>> {
+        if let Some(bytes) = self.get_memory(key) {
+            return Some(bytes);
+        }
+        let bytes = std::fs::read(self.dir.as_ref()?.join(key.to_string())).ok()?;
+        self.insert_memory(*key, bytes.clone());
+        Some(bytes)
+    }
+
+    /// Stores `bytes` under `key` in memory, and on disk if there is a directory.
+    ///
+    /// Returns an error only if writing the file fails, in which case the entry is still
+    /// kept in memory.
+    pub fn insert(&self, key: &CacheKey, bytes: &[u8]) -> Result<(), std::io::Error> {
+        self.insert_memory(*key, bytes.to_vec());
+        match &self.dir {
+            Some(dir) => write_atomic(dir, &key.to_string(), bytes),
+            None => Ok(()),
+        }
+    }
+
+    /// Removes every entry from memory. Files on disk are kept.
+    pub fn clear_memory(&self) {
+        #[allow(unused_mut)]
+        let mut memory = self.memory.write();
+        #[cfg(not(feature = "no-std"))]
+        let mut memory = memory.unwrap_or_else(|e| e.into_inner());
+        memory.clear();
+    }
+
+    /// Like [compile_ptx_with_opts()](super::compile_ptx_with_opts()), but returns the cached
+    /// ptx if the same `src` was already compiled with the same `opts` (including headers) by
+    /// the same version of nvrtc.
+    ///
+    /// nvrtc's log is stored along with the ptx, so cached ptx has the same
+    /// [Ptx::diagnostics()] as a fresh compile.
+    ///
+    /// Failing to write the cache file is not an error, since the ptx was still compiled.
+    pub fn compile_ptx<S: AsRef<str>>(
+        &self,
+        src: S,
+        opts: CompileOptions,
+    ) -> Result<Ptx, CompileError> {
+        self.compile_ptx_with_names::<S, &str>(src, opts, &[])
+    }
+
+    /// Like [compile_ptx_with_names()], but cached like [CompileCache::compile_ptx()]. The
+    /// lowered names are stored along with the ptx, so cached ptx has them too.
+    pub fn compile_ptx_with_names<S: AsRef<str>, N: AsRef<str>>(
+        &self,
+        src: S,
+        opts: CompileOptions,
+        name_expressions: &[N],
+    ) -> Result<Ptx, CompileError> {
+        let version = version()?;
+
+        let src = src.as_ref();
+        let key = CacheKey::from_opts("ptx", src, &opts, name_expressions, version);
+        if let Some(bytes) = self.get(&key) {
+            let (log, ptx_src) = decode_log(&bytes);
+            let mut ptx = decode_ptx(&String::from_utf8_lossy(ptx_src));
+            ptx.log = log;
+            return Ok(ptx);
+        }
+
+        let ptx = compile_ptx_with_names(src, opts, name_expressions)?;
+        if let Ok(ptx_src) = ptx.to_src() {
+            let mut encoded = encode_log(&ptx.log);
+            encoded.push_str(&encode_ptx(&ptx_src, ptx.lowered_names()));
+            let _ = self.insert(&key, encoded.as_bytes());
+        }
+        Ok(ptx)
+    }
+
+    /// Like [compile_cubin()], but returns the cached cubin if the same `src` was
+    /// already compiled with the same `opts` (including headers) by the same version of nvrtc.
+    ///
+    /// Like cached ptx, cached cubins keep nvrtc's log, see [CompileCache::compile_ptx()].
+    ///
+    /// Failing to write the cache file is not an error, since the cubin was still compiled.
+    pub fn compile_cubin<S: AsRef<str>>(
+        &self,
+        src: S,
+        opts: CompileOptions,
+    ) -> Result<Cubin, CompileError> {
+        let version = version()?;
+
+        let src = src.as_ref();
+        let key = CacheKey::from_opts::<&str>("cubin", src, &opts, &[], version);
+        if let Some(bytes) = self.get(&key) {
+            let (log, cubin) = decode_log(&bytes);
+            let mut cubin = Cubin::from_bytes(cubin);
+            cubin.log = log;
+            return Ok(cubin);
+        }
+
+        let cubin = compile_cubin(src, opts)?;
+        if let BinaryKind::Bytes(bytes) = &cubin.kind {
+            let mut encoded = encode_log(&cubin.log).into_bytes();
+            encoded.extend_from_slice(bytes);
+            let _ = self.insert(&key, &encoded);
+        }
+        Ok(cubin)
+    }
+
+    fn get_memory(&self, key: &CacheKey) -> Option<Vec<u8>> {
+        #[allow(unused_mut)]
+        let mut memory = self.memory.write();
+        #[cfg(not(feature = "no-std"))]
+        let mut memory = memory.unwrap_or_else(|e| e.into_inner());
+        let i = memory.iter().position(|(k, _)| k == key)?;
+        let entry = memory.remove(i)?;
+        let bytes = entry.1.clone();
+        memory.push_back(entry);
+        Some(bytes)
+    }
+
+    fn insert_memory(&self, key: CacheKey, bytes: Vec<u8>) {
+        if self.capacity == 0 {
+            return;
+        }
+        #[allow(unused_mut)]
+        let mut memory = self.memory.write();
+        #[cfg(not(feature = "no-std"))]
+        let mut memory = memory.unwrap_or_else(|e| e.into_inner());
+        memory.retain(|(k, _)| *k != key);
+        while memory.len() >= self.capacity {
+            memory.pop_front();
+        }
+        memory.push_back((key, bytes));
+    }
+}
+
+/// Starts the lines that store nvrtc's log in front of a cached ptx or cubin. Neither ptx
+/// nor cubins (which are ELF files) can start with it.
+const LOG: &str = "// cudarc log: ";
+
+/// A line of `{LOG}{line}` for each line of `log`.
+fn encode_log(log: &str) -> String {
+    let mut encoded = String::new();
+    for line in log.lines() {
+        encoded.push_str(&std::format!("{LOG}{line}\n"));
+    }
+    encoded
+}
+
+/// The inverse of [encode_log()], returning the log and the bytes after it.
+fn decode_log(encoded: &[u8]) -> (String, &[u8]) {
+    let mut log = String::new();
+    let mut rest = encoded;
+    while let Some(line) = rest.strip_prefix(LOG.as_bytes()) {
+        let end = line.iter().position(|&b| b == b'\n').unwrap_or(line.len());
+        log.push_str(&String::from_utf8_lossy(&line[..end]));
+        log.push('\n');
+        rest = line.get(end + 1..).unwrap_or_default();
+    }
+    (log, rest)
+}
+
+/// Starts the lines that store lowered names in front of cached ptx. They're ptx comments,
+/// so the file is still valid ptx.
+const LOWERED_NAME: &str = "// cudarc lowered name: ";
+
+/// `ptx_src` with a comment line of `{lowered name} {name expression}` in front of it for
+/// each of `lowered_names`. Lowered names have no spaces, but name expressions can.
+fn encode_ptx<'a>(
+    ptx_src: &str,
+    lowered_names: impl Iterator<Item = (&'a str, &'a str)>,
+) -> String {
+    let mut encoded = String::new();
+    for (expr, name) in lowered_names {
+        encoded.push_str(&std::format!("{LOWERED_NAME}{name} {expr}\n"));
+    }
+    encoded.push_str(ptx_src);
+    encoded
+}
+
+/// The inverse of [encode_ptx()].
+fn decode_ptx(encoded: &str) -> Ptx {
+    let mut lowered_names = std::collections::BTreeMap::new();
+    let mut ptx_src = encoded;
+    while let Some(rest) = ptx_src.strip_prefix(LOWERED_NAME) {
+        let (line, next) = rest.split_once('\n').unwrap_or((rest, ""));
+        if let Some((name, expr)) = line.split_once(' ') {
+            lowered_names.insert(expr.to_string(), name.to_string());
+        }
+        ptx_src = next;
+    }
+    let mut ptx = Ptx::from_src(ptx_src);
+    ptx.lowered_names = lowered_names;
+    ptx
+}
+
+/// Writes to a temporary file in `dir` and renames it to `name`, so readers never see a
+/// partially written file.
+fn write_atomic(dir: &Path, name: &str, bytes: &[u8]) -> Result<(), std::io::Error> {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+    std::fs::create_dir_all(dir)?;
+    let n = COUNTER.fetch_add(1, Ordering::Relaxed);
+    let tmp = dir.join(std::format!(".{name}.{}.{n}.tmp", std::process::id()));
+    std::fs::write(&tmp, bytes)?;
+    std::fs::rename(&tmp, dir.join(name)).inspect_err(|_| {
+        let _ = std::fs::remove_file(&tmp);
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(std::format!("cudarc-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn test_cache_key() {
+        let key = CacheKey::new("ptx", "src", &["--ftz=true"], (12, 2));
+        assert_eq!(key, CacheKey::new("ptx", "src", &["--ftz=true"], (12, 2)));
+        assert_eq!(key.to_string().len(), 32);
+
+        assert_ne!(key, CacheKey::new("cubin", "src", &["--ftz=true"], (12, 2)));
+        assert_ne!(key, CacheKey::new("ptx", "src2", &["--ftz=true"], (12, 2)));
+        assert_ne!(key, CacheKey::new("ptx", "src", &["--ftz=false"], (12, 2)));
+        assert_ne!(key, CacheKey::new("ptx", "src", &["--ftz=true"], (12, 3)));
+        assert_ne!(
+            CacheKey::new("ptx", "src", &["a", "b"], (12, 2)),
+            CacheKey::new("ptx", "src", &["ab"], (12, 2))
+        );
+    }
+
+    #[test]
+    fn test_cache_key_headers() {
+        let opts = CompileOptions {
+            headers: [("a.cuh".into(), "#define A 1".into())].into(),
+            ..Default::default()
+        };
+        let key = CacheKey::from_opts::<&str>("ptx", "src", &opts, &[], (12, 0));
+        assert_ne!(
+            key,
+            CacheKey::from_opts::<&str>("ptx", "src", &Default::default(), &[], (12, 0))
+        );
+
+        let mut changed = opts.clone();
+        changed.headers.insert("a.cuh".into(), "#define A 2".into());
+        assert_ne!(
+            key,
+            CacheKey::from_opts::<&str>("ptx", "src", &changed, &[], (12, 0))
+        );
+    }
+
+    #[test]
+    fn test_cache_key_includes() {
+        let dir = temp_dir("cache-includes");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("a.cuh"), "#include \"b.cuh\"").unwrap();
+        std::fs::write(dir.join("b.cuh"), "#define B 1").unwrap();
+        std::fs::write(dir.join("pre.cuh"), "#define PRE 1").unwrap();
+        let opts = CompileOptions {
+            include_paths: std::vec![dir.display().to_string()],
+            pre_include: std::vec!["pre.cuh".into()],
+            ..Default::default()
+        };
+        let key = |opts: &CompileOptions| {
+            CacheKey::from_opts::<&str>("ptx", "#include \"a.cuh\"", opts, &[], (12, 0))
+        };
+        let before = key(&opts);
+        assert_eq!(before, key(&opts));
+
+        // a file included by an included file
+        std::fs::write(dir.join("b.cuh"), "#define B 2").unwrap();
+        let after = key(&opts);
+        assert_ne!(before, after);
+
+        std::fs::write(dir.join("pre.cuh"), "#define PRE 2").unwrap();
+        assert_ne!(after, key(&opts));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_cache_key_name_expressions() {
+        let opts = CompileOptions::default();
+        assert_ne!(
+            CacheKey::from_opts("ptx", "src", &opts, &["add<float>"], (12, 0)),
+            CacheKey::from_opts::<&str>("ptx", "src", &opts, &[], (12, 0))
+        );
+    }
+
+    #[test]
+    fn test_encode_lowered_names() {
+        let names = [("add<float, 2>", "_Z3addIfLi2EEvPT_"), ("k", "k")];
+        let encoded = encode_ptx(".version 8.0\n", names.into_iter());
+        let ptx = decode_ptx(&encoded);
+        assert_eq!(ptx.lowered_name("add<float, 2>"), Some("_Z3addIfLi2EEvPT_"));
+        assert_eq!(ptx.lowered_name("k"), Some("k"));
+        assert_eq!(ptx.to_src().unwrap(), ".version 8.0\n");
+        assert_eq!(decode_ptx(".version 8.0").lowered_names().count(), 0);
+    }
+
+    #[test]
+    fn test_encode_log() {
+        let log = "a.cu(2): warning #177-D: variable \"x\" was declared but never referenced\n\n";
+        let mut encoded = encode_log(log).into_bytes();
+        encoded.extend_from_slice(b"\x7fELF");
+        let (decoded, rest) = decode_log(&encoded);
+        assert_eq!(decoded, log);
+        assert_eq!(rest, b"\x7fELF");
+        assert_eq!(
+            decode_log(b".version 8.0"),
+            (String::new(), &b".version 8.0"[..])
+        );
+    }
+
+    #[test]
+    fn test_cache_memory_lru() {
+        let cache = CompileCache::new(2);
+        let keys: Vec<_> = (0..3)
+            .map(|i| CacheKey::new("ptx", &std::format!("{i}"), &[""; 0], (12, 0)))
+            .collect();
+        cache.insert(&keys[0], b"0").unwrap();
+        cache.insert(&keys[1], b"1").unwrap();
+        // makes keys[1] the least recently used
+        assert_eq!(cache.get(&keys[0]).as_deref(), Some(&b"0"[..]));
+        cache.insert(&keys[2], b"2").unwrap();
+        assert_eq!(cache.get(&keys[1]), None);
+        assert_eq!(cache.get(&keys[0]).as_deref(), Some(&b"0"[..]));
+        assert_eq!(cache.get(&keys[2]).as_deref(), Some(&b"2"[..]));
+
+        cache.clear_memory();
+        assert_eq!(cache.get(&keys[0]), None);
+    }
+
+    #[test]
+    fn test_cache_dir() {
+        let dir = temp_dir("cache-dir");
+        let key = CacheKey::new("ptx", "src", &["--ftz=true"], (12, 0));
+
+        let cache = CompileCache::new(0).with_dir(&dir);
+        assert_eq!(cache.get(&key), None);
+        cache.insert(&key, b".version 8.0").unwrap();
+        cache.insert(&key, b".version 8.1").unwrap();
+
+        // a new cache, like after a restart
+        let cache = CompileCache::new(4).with_dir(&dir);
+        assert_eq!(cache.get(&key).as_deref(), Some(&b".version 8.1"[..]));
+
+        let files: Vec<_> = std::fs::read_dir(&dir)
+            .unwrap()
+            .map(|e| e.unwrap().file_name().into_string().unwrap())
+            .collect();
+        assert_eq!(files, [key.to_string()]);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+        // still in memory
+        assert_eq!(cache.get(&key).as_deref(), Some(&b".version 8.1"[..]));
+    }
+
+    #[test]
+    fn test_cache_compile_ptx() {
+        let dir = temp_dir("cache-compile");
+        let cache = CompileCache::new(4).with_dir(&dir);
+        let src = "extern \"C\" __global__ void kernel(float *out) { int unused; *out = 1.0f; }";
+        let ptx = cache.compile_ptx(src, Default::default()).unwrap();
+        let cached = CompileCache::new(4)
+            .with_dir(&dir)
+            .compile_ptx(src, Default::default())
+            .unwrap();
+        assert_eq!(ptx.to_src().unwrap(), cached.to_src().unwrap());
+        assert!(!ptx.diagnostics().is_empty());
+        assert_eq!(ptx.diagnostics(), cached.diagnostics());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
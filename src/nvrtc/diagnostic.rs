@@ -0,0 +1,323 @@
+//! Errors & warnings parsed from nvrtc's program log.
+//!
+//! See [CompileError::diagnostics()](super::CompileError::diagnostics()) and
+//! [Ptx::diagnostics()](super::Ptx::diagnostics()).
+
+use std::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+/// How serious a [Diagnostic] is. Ordered from least to most serious.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Severity {
+    /// Informational, e.g. nvrtc's `remark`s and `note`s
+    Remark,
+    /// Something suspicious that still compiled, e.g. an unused variable
+    Warning,
+    /// The program failed to compile. Includes nvrtc's `catastrophic error`s and
+    /// `internal error`s
+    Error,
+}
+
+impl core::fmt::Display for Severity {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(match self {
+            Self::Remark => "remark",
+            Self::Warning => "warning",
+            Self::Error => "error",
+        })
+    }
+}
+
+/// A single error, warning or remark from compiling a program.
+///
+/// Log lines that aren't about a location, e.g. `2 errors detected in the compilation of
+/// "default_program".`, are diagnostics with an empty `file` and a `line` of 0.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Diagnostic {
+    /// The file the diagnostic is in, `default_program` for the source passed to nvrtc.
+    /// Empty if the diagnostic has no location
+    pub file: String,
+    /// 1-based line number, 0 if the diagnostic has no location
+    pub line: usize,
+    /// 1-based column, if nvrtc pointed at one
+    pub column: Option<usize>,
+    /// Whether this is an error, warning or remark
+    pub severity: Severity,
+    /// The diagnostic's number, e.g. `177-D`, if it has one
+    pub code: Option<String>,
+    /// The first line of the diagnostic, without the location & severity, e.g.
+    /// `identifier "y" is undefined`
+    pub message: String,
+    /// Further lines of the message, e.g. `detected during instantiation of ...`
+    pub notes: Vec<String>,
+}
+
+impl Diagnostic {
+    /// Renders the diagnostic like rustc does, showing line `line` of `src` with a caret
+    /// under the column if `src` is given. Diagnostics without a location are just their
+    /// message & notes.
+    ///
+    /// ```text
+    /// error: identifier "y" is undefined
+    ///  --> default_program:2:12
+    ///   |
+    /// 2 |     *out = y;
+    ///   |            ^
+    /// ```
+    pub fn render(&self, src: Option<&str>) -> String {
+        let mut out = format!("{}", self.severity);
+        if let Some(code) = &self.code {
+            out.push_str(&format!("[{code}]"));
+        }
+        out.push_str(&format!(": {}\n", self.message));
+
+        let line_num = self.line.to_string();
+        let pad = " ".repeat(line_num.len());
+        if self.file.is_empty() {
+            for note in &self.notes {
+                out.push_str(&format!("{pad} = note: {note}\n"));
+            }
+            return out;
+        }
+        match self.column {
+            Some(col) => out.push_str(&format!("{pad}--> {}:{}:{col}\n", self.file, self.line)),
+            None => out.push_str(&format!("{pad}--> {}:{}\n", self.file, self.line)),
+        }
+
+        let src_line = src.and_then(|src| src.lines().nth(self.line.checked_sub(1)?));
+        if let Some(src_line) = src_line {
+            out.push_str(&format!("{pad} |\n{line_num} | {src_line}\n"));
+            if let Some(col) = self.column {
+                // keep tabs so the caret lines up with the source line
+                let indent: String = src_line
+                    .chars()
+                    .take(col.saturating_sub(1))
+                    .map(|c| if c == '\t' { '\t' } else { ' ' })
+                    .collect();
+                out.push_str(&format!("{pad} | {indent}^\n"));
+            }
+        }
+        for note in &self.notes {
+            out.push_str(&format!("{pad} = note: {note}\n"));
+        }
+        out
+    }
+}
+
+impl core::fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(&self.render(None))
+    }
+}
+
+/// Parses nvrtc's program log into diagnostics. Unindented lines that aren't about a
+/// location (e.g. `1 error detected in the compilation of ...`) are their own diagnostics,
+/// without a file.
+///
+/// A diagnostic looks like this, where the source line & caret are optional:
+/// ```text
+/// default_program(2): error: identifier "y" is undefined
+///       *out = y;
+///              ^
+/// ```
+/// An indented line is only taken as the quoted source line if a caret follows it,
+/// otherwise it's a note.
+pub fn parse_log(log: &str) -> Vec<Diagnostic> {
+    let mut diagnostics: Vec<Diagnostic> = Vec::new();
+    let lines: Vec<&str> = log.lines().collect();
+    let is_caret = |i: usize| lines.get(i).is_some_and(|line| line.trim() == "^");
+    let mut i = 0;
+    while i < lines.len() {
+        let line = lines[i];
+        i += 1;
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            continue;
+        }
+        if let Some(diagnostic) = parse_header(line) {
+            diagnostics.push(diagnostic);
+            continue;
+        }
+        if !line.starts_with(char::is_whitespace) {
+            diagnostics.push(parse_global(trimmed));
+            continue;
+        }
+        let Some(current) = diagnostics.last_mut() else {
+            continue;
+        };
+        if current.column.is_none() && line.starts_with(' ') && is_caret(i) {
+            current.column = Some(caret_column(line, lines[i]));
+            i += 1;
+        } else if trimmed != "^" {
+            current.notes.push(trimmed.to_string());
+        }
+    }
+    diagnostics
+}
+
+/// Parses e.g. `default_program(12): warning #177-D: variable "x" was declared but never referenced`.
+fn parse_header(line: &str) -> Option<Diagnostic> {
+    let open = line.find('(')?;
+    let close = open + line[open..].find("): ")?;
+    let file = &line[..open];
+    let line_num = line[open + 1..close].parse().ok()?;
+    let rest = &line[close + "): ".len()..];
+
+    let (kind, message) = rest.split_once(": ")?;
+    let (kind, code) = match kind.split_once(" #") {
+        Some((kind, code)) => (kind, Some(code.to_string())),
+        None => (kind, None),
+    };
+    let severity = match kind {
+        "error" | "catastrophic error" | "internal error" => Severity::Error,
+        "warning" => Severity::Warning,
+        "remark" | "note" => Severity::Remark,
+        _ => return None,
+    };
+    Some(Diagnostic {
+        file: file.to_string(),
+        line: line_num,
+        column: None,
+        severity,
+        code,
+        message: message.trim().to_string(),
+        notes: Vec::new(),
+    })
+}
+
+/// Parses a line that isn't about a location, e.g. `Remark: The warnings can be suppressed
+/// with "-diag-suppress <warning-number>"`. Lines without a severity are remarks.
+fn parse_global(line: &str) -> Diagnostic {
+    let (severity, message) = match line.split_once(": ") {
+        Some((kind, message)) => match kind.to_ascii_lowercase().as_str() {
+            "error" | "catastrophic error" | "internal error" => (Severity::Error, message),
+            "warning" => (Severity::Warning, message),
+            "remark" | "note" => (Severity::Remark, message),
+            _ => (Severity::Remark, line),
+        },
+        None => (Severity::Remark, line),
+    };
+    Diagnostic {
+        file: String::new(),
+        line: 0,
+        column: None,
+        severity,
+        code: None,
+        message: message.trim().to_string(),
+        notes: Vec::new(),
+    }
+}
+
+/// The 1-based column in the original source of the caret in `caret_line`. nvrtc indents
+/// the quoted source line by 2 spaces, and the caret line with it.
+fn caret_column(quoted: &str, caret_line: &str) -> usize {
+    const INDENT: usize = 2;
+    let caret = caret_line.find('^').unwrap_or(0);
+    let indent = if quoted.starts_with("  ") { INDENT } else { 0 };
+    caret.saturating_sub(indent) + 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LOG: &str =
+        "default_program(2): warning #177-D: variable \"unused\" was declared but never referenced
+      int unused;
+          ^
+
+Remark: The warnings can be suppressed with \"-diag-suppress <warning-number>\"
+
+default_program(3): error: identifier \"y\" is undefined
+      *out = y;
+             ^
+
+common.cuh(1): error: expected a \";\"
+          detected during instantiation of \"f<T>\"
+
+2 errors detected in the compilation of \"default_program\".
+";
+
+    #[test]
+    fn test_parse_log() {
+        let diagnostics = parse_log(LOG);
+        assert_eq!(diagnostics.len(), 5);
+
+        assert_eq!(diagnostics[0].severity, Severity::Warning);
+        assert_eq!(diagnostics[0].code.as_deref(), Some("177-D"));
+        assert_eq!(diagnostics[0].line, 2);
+        assert_eq!(diagnostics[0].column, Some(9));
+        assert!(diagnostics[0].notes.is_empty());
+
+        assert_eq!(
+            diagnostics[1],
+            Diagnostic {
+                file: String::new(),
+                line: 0,
+                column: None,
+                severity: Severity::Remark,
+                code: None,
+                message: "The warnings can be suppressed with \"-diag-suppress <warning-number>\""
+                    .into(),
+                notes: Vec::new(),
+            }
+        );
+
+        assert_eq!(
+            diagnostics[2],
+            Diagnostic {
+                file: "default_program".into(),
+                line: 3,
+                column: Some(12),
+                severity: Severity::Error,
+                code: None,
+                message: "identifier \"y\" is undefined".into(),
+                notes: Vec::new(),
+            }
+        );
+
+        // indented, but not quoted source since no caret follows
+        assert_eq!(diagnostics[3].file, "common.cuh");
+        assert_eq!(diagnostics[3].column, None);
+        assert_eq!(
+            diagnostics[3].notes,
+            ["detected during instantiation of \"f<T>\""]
+        );
+
+        assert_eq!(diagnostics[4].file, "");
+        assert_eq!(diagnostics[4].severity, Severity::Remark);
+        assert_eq!(
+            diagnostics[4].message,
+            "2 errors detected in the compilation of \"default_program\"."
+        );
+    }
+
+    #[test]
+    fn test_render() {
+        const SRC: &str = "extern \"C\" __global__ void kernel(float *out) {
+    int unused;
+    *out = y;
+}";
+        let diagnostics = parse_log(LOG);
+        assert_eq!(
+            diagnostics[2].render(Some(SRC)),
+            "error: identifier \"y\" is undefined
+ --> default_program:3:12
+  |
+3 |     *out = y;
+  |            ^
+"
+        );
+        assert_eq!(
+            diagnostics[3].to_string(),
+            "error: expected a \";\"\n --> common.cuh:1\n  = note: detected during instantiation of \"f<T>\"\n"
+        );
+        assert_eq!(
+            diagnostics[4].render(Some(SRC)),
+            "remark: 2 errors detected in the compilation of \"default_program\".\n"
+        );
+    }
+}
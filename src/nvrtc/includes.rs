@@ -0,0 +1,113 @@
+//! Finding the files a cuda source `#include`s, so that builds & caches can tell when
+//! they change, and finding the cuda toolkit itself.
+
+use std::{
+    collections::BTreeSet,
+    path::{Path, PathBuf},
+    string::{String, ToString},
+    vec::Vec,
+};
+
+/// The cuda toolkit directories named by the `CUDA_PATH`, `CUDA_ROOT` and
+/// `CUDA_TOOLKIT_ROOT_DIR` environment variables, in that order.
+pub(crate) fn cuda_roots() -> Vec<PathBuf> {
+    ["CUDA_PATH", "CUDA_ROOT", "CUDA_TOOLKIT_ROOT_DIR"]
+        .into_iter()
+        .filter_map(std::env::var_os)
+        .map(PathBuf::from)
+        .collect()
+}
+
+/// Where nvrtc looks for the toolkit's headers like `cuda_fp16.h`: the `include` directory
+/// of each of [cuda_roots()], then the default install locations on unix.
+pub(crate) fn cuda_include_paths() -> Vec<String> {
+    let mut paths: Vec<PathBuf> = cuda_roots().iter().map(|r| r.join("include")).collect();
+    if cfg!(unix) {
+        paths.push("/usr/local/cuda/include".into());
+        paths.push("/usr/include".into());
+    }
+    paths.iter().map(|p| p.display().to_string()).collect()
+}
+
+/// Every file that `src` (at `path`) transitively includes with `#include "..."`, looked up
+/// next to the including file and then in `include_paths`. Includes that can't be found
+/// (e.g. system headers) are skipped.
+pub(crate) fn includes(path: &Path, src: &str, include_paths: &[String]) -> BTreeSet<PathBuf> {
+    let mut found = BTreeSet::new();
+    let mut todo = std::vec![(path.to_path_buf(), src.to_string())];
+    while let Some((path, src)) = todo.pop() {
+        for name in include_names(&src) {
+            let candidates = path
+                .parent()
+                .into_iter()
+                .chain(include_paths.iter().map(Path::new))
+                .map(|dir| dir.join(name));
+            for candidate in candidates {
+                if candidate.is_file() {
+                    if found.insert(candidate.clone()) {
+                        if let Ok(src) = std::fs::read_to_string(&candidate) {
+                            todo.push((candidate, src));
+                        }
+                    }
+                    break;
+                }
+            }
+        }
+    }
+    found
+}
+
+/// The names in every `#include "name"` of `src`.
+fn include_names(src: &str) -> impl Iterator<Item = &str> {
+    src.lines().filter_map(|line| {
+        let line = line.trim_start().strip_prefix('#')?.trim_start();
+        let rest = line.strip_prefix("include")?.trim_start();
+        let rest = rest.strip_prefix('"')?;
+        rest.split_once('"').map(|(name, _)| name)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::vec::Vec;
+
+    #[test]
+    fn test_cuda_include_paths() {
+        let paths = cuda_include_paths();
+        for root in cuda_roots() {
+            assert!(paths.contains(&root.join("include").display().to_string()));
+        }
+        if cfg!(unix) {
+            assert!(paths.contains(&"/usr/local/cuda/include".to_string()));
+        }
+    }
+
+    #[test]
+    fn test_include_names() {
+        let src = "#include \"common.cuh\"
+  #  include \"detail/math.cuh\" // comment
+#include <cuda_fp16.h>
+// #include \"commented.cuh\"
+#define INCLUDE \"not_an_include.cuh\"";
+        let names: Vec<_> = include_names(src).collect();
+        assert_eq!(names, ["common.cuh", "detail/math.cuh"]);
+    }
+
+    #[test]
+    fn test_includes() {
+        let dir = std::env::temp_dir().join(std::format!("cudarc-build-{}", std::process::id()));
+        let inc = dir.join("inc");
+        std::fs::create_dir_all(&inc).unwrap();
+        std::fs::write(dir.join("a.cuh"), "#include \"b.cuh\"\n#include \"a.cuh\"").unwrap();
+        std::fs::write(inc.join("b.cuh"), "#include \"missing.cuh\"").unwrap();
+
+        let src = "#include \"a.cuh\"\n#include <stdio.h>";
+        let deps = includes(&dir.join("k.cu"), src, &[inc.display().to_string()]);
+        assert_eq!(
+            deps.into_iter().collect::<Vec<_>>(),
+            [dir.join("a.cuh"), inc.join("b.cuh")]
+        );
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,13 @@
+use super::sys;
+
+extern "C" {
+    pub fn nvrtcGetLTOIRSize(prog: sys::nvrtcProgram, LTOIRSizeRet: *mut usize)
+        -> sys::nvrtcResult;
+}
+
+extern "C" {
+    pub fn nvrtcGetLTOIR(
+        prog: sys::nvrtcProgram,
+        LTOIR: *mut core::ffi::c_char,
+    ) -> sys::nvrtcResult;
+}
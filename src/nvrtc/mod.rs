@@ -1,9 +1,37 @@
 //! Wrappers around the [Nvidia Runtime Compilation (nvrtc) API](https://docs.nvidia.com/cuda/nvrtc/index.html),
 //! in three levels. See crate documentation for description of each.
 
+#[cfg(feature = "std")]
+pub mod build;
+pub mod cache;
+pub mod diagnostic;
+pub(crate) mod includes;
+#[cfg(all(feature = "cuda-12", not(feature = "dynamic-loading")))]
+pub mod lto;
+#[cfg(all(feature = "cuda-12", feature = "dynamic-loading"))]
+#[allow(warnings)]
+pub mod lto {
+    include!(concat!(env!("OUT_DIR"), "/nvrtc_lto.rs"));
+}
 pub mod result;
 pub mod safe;
+#[cfg(not(feature = "dynamic-loading"))]
 #[allow(warnings)]
 pub mod sys;
+#[cfg(feature = "dynamic-loading")]
+#[allow(warnings)]
+pub mod sys {
+    include!(concat!(env!("OUT_DIR"), "/nvrtc_sys.rs"));
+    include!(concat!(env!("OUT_DIR"), "/nvrtc_required.rs"));
+
+    #[cfg(not(windows))]
+    const FILE_NAMES: &[&str] = &["libnvrtc.so", "libnvrtc.so.12", "libnvrtc.so.11.2"];
+    #[cfg(windows)]
+    const FILE_NAMES: &[&str] = &["nvrtc64_120_0.dll", "nvrtc64_112_0.dll"];
+
+    pub static LIBRARY: crate::dynamic_loading::DynamicLibrary =
+        crate::dynamic_loading::DynamicLibrary::new("nvrtc", FILE_NAMES, REQUIRED_SYMBOLS);
+}
 
+pub use diagnostic::{Diagnostic, Severity};
 pub use safe::*;
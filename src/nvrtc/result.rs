@@ -32,6 +32,41 @@ impl std::fmt::Display for NvrtcError {
 #[cfg(feature = "std")]
 impl std::error::Error for NvrtcError {}
 
+/// Returns the (major, minor) version of the nvrtc library.
+///
+/// See [nvrtcVersion() docs](https://docs.nvidia.com/cuda/nvrtc/index.html#group__query)
+///
+/// Example:
+/// ```rust
+/// # use cudarc::nvrtc::result::*;
+/// let (major, minor) = version().unwrap();
+/// ```
+pub fn version() -> Result<(c_int, c_int), NvrtcError> {
+    let mut major = 0;
+    let mut minor = 0;
+    unsafe { sys::nvrtcVersion(&mut major, &mut minor) }.result()?;
+    Ok((major, minor))
+}
+
+/// Returns the architectures nvrtc can compile for, in ascending order, as
+/// `major * 10 + minor` (e.g. `86` for `sm_86`).
+///
+/// See [nvrtcGetSupportedArchs() docs](https://docs.nvidia.com/cuda/nvrtc/index.html#group__query)
+///
+/// Example:
+/// ```rust
+/// # use cudarc::nvrtc::result::*;
+/// let archs = get_supported_archs().unwrap();
+/// assert!(!archs.is_empty());
+/// ```
+pub fn get_supported_archs() -> Result<Vec<c_int>, NvrtcError> {
+    let mut num_archs = 0;
+    unsafe { sys::nvrtcGetNumSupportedArchs(&mut num_archs) }.result()?;
+    let mut archs = std::vec![0; num_archs as usize];
+    unsafe { sys::nvrtcGetSupportedArchs(archs.as_mut_ptr()) }.result()?;
+    Ok(archs)
+}
+
 /// Creates a program from source code `src`. This should be source code from a .cu file.
 ///
 /// See [nvrtcCreateProgram() docs](https://docs.nvidia.com/cuda/nvrtc/index.html#group__compilation_1g9ae65f68911d1cf0adda2af4ad8cb458)
@@ -42,16 +77,49 @@ impl std::error::Error for NvrtcError {}
 /// let prog = create_program("extern \"C\" __global__ void kernel() { }").unwrap();
 /// ```
 pub fn create_program<S: AsRef<str>>(src: S) -> Result<sys::nvrtcProgram, NvrtcError> {
-    let src_c = CString::new(src.as_ref()).unwrap();
+    create_program_with_headers::<_, &str, &str>(src, &[])
+}
+
+/// Creates a program from source code `src`, like [create_program()], which can `#include`
+/// each of `headers` by name. Each header is a pair of (name, contents).
+///
+/// Returns `NVRTC_ERROR_INVALID_INPUT` if `src` or a header contains a NUL byte.
+///
+/// See [nvrtcCreateProgram() docs](https://docs.nvidia.com/cuda/nvrtc/index.html#group__compilation_1g9ae65f68911d1cf0adda2af4ad8cb458)
+///
+/// Example:
+/// ```rust
+/// # use cudarc::nvrtc::result::*;
+/// let headers = [("common.cuh", "#define BLOCK_SIZE 256")];
+/// let prog = create_program_with_headers("#include \"common.cuh\"", &headers).unwrap();
+/// ```
+pub fn create_program_with_headers<S: AsRef<str>, N: AsRef<str>, C: AsRef<str>>(
+    src: S,
+    headers: &[(N, C)],
+) -> Result<sys::nvrtcProgram, NvrtcError> {
+    let c_string = |s: &str| {
+        CString::new(s).map_err(|_| NvrtcError(sys::nvrtcResult::NVRTC_ERROR_INVALID_INPUT))
+    };
+    let src_c = c_string(src.as_ref())?;
+    let names = headers
+        .iter()
+        .map(|(name, _)| c_string(name.as_ref()))
+        .collect::<Result<Vec<_>, _>>()?;
+    let contents = headers
+        .iter()
+        .map(|(_, contents)| c_string(contents.as_ref()))
+        .collect::<Result<Vec<_>, _>>()?;
+    let name_ptrs: Vec<*const c_char> = names.iter().map(|n| n.as_ptr()).collect();
+    let content_ptrs: Vec<*const c_char> = contents.iter().map(|c| c.as_ptr()).collect();
     let mut prog = MaybeUninit::uninit();
     unsafe {
         sys::nvrtcCreateProgram(
             prog.as_mut_ptr(),
             src_c.as_c_str().as_ptr(),
             std::ptr::null(),
-            0,
-            std::ptr::null(),
-            std::ptr::null(),
+            headers.len() as c_int,
+            content_ptrs.as_ptr(),
+            name_ptrs.as_ptr(),
         )
         .result()?;
         Ok(prog.assume_init())
@@ -88,6 +156,42 @@ pub unsafe fn compile_program<O: Clone + Into<Vec<u8>>>(
     sys::nvrtcCompileProgram(prog, opts.len() as c_int, opts.as_ptr()).result()
 }
 
+/// Adds a name expression, e.g. `"add<float>"`, naming a `__global__` function or
+/// variable to instantiate. Its lowered name can be retrieved with [get_lowered_name()]
+/// after compiling.
+///
+/// See [nvrtcAddNameExpression() docs](https://docs.nvidia.com/cuda/nvrtc/index.html#group__hostapi)
+///
+/// # Safety
+///
+/// `prog` must be created from [create_program()], not have been compiled and not have been freed by [destroy_program()].
+pub unsafe fn add_name_expression(
+    prog: sys::nvrtcProgram,
+    name_expression: &str,
+) -> Result<(), NvrtcError> {
+    let name_expression = CString::new(name_expression).unwrap();
+    sys::nvrtcAddNameExpression(prog, name_expression.as_ptr()).result()
+}
+
+/// The lowered (mangled) name of a name expression added with [add_name_expression()].
+/// Call [compile_program()] before this.
+///
+/// See [nvrtcGetLoweredName() docs](https://docs.nvidia.com/cuda/nvrtc/index.html#group__hostapi)
+///
+/// # Safety
+///
+/// `prog` must be created from [create_program()] and not have been freed by [destroy_program()].
+pub unsafe fn get_lowered_name(
+    prog: sys::nvrtcProgram,
+    name_expression: &str,
+) -> Result<CString, NvrtcError> {
+    let name_expression = CString::new(name_expression).unwrap();
+    let mut lowered_name = core::ptr::null();
+    sys::nvrtcGetLoweredName(prog, name_expression.as_ptr(), &mut lowered_name).result()?;
+    // owned by `prog`, so it has to be copied
+    Ok(CStr::from_ptr(lowered_name).into())
+}
+
 /// Releases resources associated with `prog`.
 ///
 /// See [nvrtcDestroyProgram() docs](https://docs.nvidia.com/cuda/nvrtc/index.html#group__compilation_1gaa237c59615b7d4f48d5b308b5c9b140).
@@ -118,6 +222,41 @@ pub unsafe fn get_ptx(prog: sys::nvrtcProgram) -> Result<Vec<c_char>, NvrtcError
     Ok(ptx_src)
 }
 
+/// Extract the cubin associated with `prog`. Call [compile_program()] with a real
+/// architecture (e.g. `--gpu-architecture=sm_80`) before this, otherwise there is no cubin.
+///
+/// See [nvrtcGetCUBIN() docs](https://docs.nvidia.com/cuda/nvrtc/index.html#group__compilation)
+///
+/// # Safety
+///
+/// `prog` must be created from [create_program()] and not have been freed by [destroy_program()].
+pub unsafe fn get_cubin(prog: sys::nvrtcProgram) -> Result<Vec<u8>, NvrtcError> {
+    let mut size: usize = 0;
+    sys::nvrtcGetCUBINSize(prog, &mut size as *mut _).result()?;
+
+    let mut cubin = std::vec![0u8; size];
+    sys::nvrtcGetCUBIN(prog, cubin.as_mut_ptr() as *mut c_char).result()?;
+    Ok(cubin)
+}
+
+/// Extract the LTO-IR associated with `prog`. Call [compile_program()] with `-dlto`
+/// before this, otherwise there is no LTO-IR.
+///
+/// See [nvrtcGetLTOIR() docs](https://docs.nvidia.com/cuda/nvrtc/index.html#group__compilation)
+///
+/// # Safety
+///
+/// `prog` must be created from [create_program()] and not have been freed by [destroy_program()].
+#[cfg(feature = "cuda-12")]
+pub unsafe fn get_lto_ir(prog: sys::nvrtcProgram) -> Result<Vec<u8>, NvrtcError> {
+    let mut size: usize = 0;
+    super::lto::nvrtcGetLTOIRSize(prog, &mut size as *mut _).result()?;
+
+    let mut lto_ir = std::vec![0u8; size];
+    super::lto::nvrtcGetLTOIR(prog, lto_ir.as_mut_ptr() as *mut c_char).result()?;
+    Ok(lto_ir)
+}
+
 /// Extract log from a compiled program.
 ///
 /// See [nvrtcGetProgramLog() docs](https://docs.nvidia.com/cuda/nvrtc/index.html#group__compilation_1g74c550e5cab81efbd59e4f72579edbd1)
@@ -141,6 +280,14 @@ pub unsafe fn get_program_log(prog: sys::nvrtcProgram) -> Result<Vec<c_char>, Nv
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_create_program_interior_nul() {
+        let invalid = Err(NvrtcError(sys::nvrtcResult::NVRTC_ERROR_INVALID_INPUT));
+        assert_eq!(create_program("\0"), invalid);
+        assert_eq!(create_program_with_headers("", &[("a\0.cuh", "")]), invalid);
+        assert_eq!(create_program_with_headers("", &[("a.cuh", "\0")]), invalid);
+    }
+
     #[test]
     fn test_compile_program_no_opts() {
         let prog = create_program("extern \"C\" __global__ void kernel() { }").unwrap();
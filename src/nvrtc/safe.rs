@@ -1,30 +1,82 @@
 //! Safe abstractions around [crate::nvrtc::result] for compiling PTX files.
 //!
-//! Call [compile_ptx()] or [compile_ptx_with_opts()].
+//! Call [compile_ptx()] or [compile_ptx_with_opts()], or [compile_cubin()] to skip
+//! the driver's jit compilation.
 
-use super::{result, sys};
+use super::{diagnostic, result, sys, Diagnostic};
 
 use core::ffi::{c_char, CStr};
 use std::ffi::CString;
-use std::{borrow::ToOwned, path::PathBuf, string::String, vec::Vec};
+use std::{
+    borrow::ToOwned,
+    collections::BTreeMap,
+    path::PathBuf,
+    string::{String, ToString},
+    vec::Vec,
+};
 
 /// An opaque structure representing a compiled PTX program
 /// output from [compile_ptx()] or [compile_ptx_with_opts()].
 ///
 /// Can also be created from a [Ptx::from_file] and [Ptx::from_src]
 #[derive(Debug, Clone)]
-pub struct Ptx(pub(crate) PtxKind);
+pub struct Ptx {
+    pub(crate) kind: PtxKind,
+    /// Name expressions mapped to the lowered names of the kernels they refer to.
+    /// See [compile_ptx_with_names()].
+    pub(crate) lowered_names: BTreeMap<String, String>,
+    /// The log nvrtc wrote while compiling this, empty if this wasn't compiled with nvrtc.
+    pub(crate) log: String,
+}
 
 impl Ptx {
     /// Creates a Ptx from a pre-compiled .ptx file.
     pub fn from_file<P: Into<PathBuf>>(path: P) -> Self {
-        Self(PtxKind::File(path.into()))
+        PtxKind::File(path.into()).into()
     }
 
     /// Creates a Ptx from the source string of a pre-compiled .ptx
     /// file.
     pub fn from_src<S: Into<String>>(src: S) -> Self {
-        Self(PtxKind::Src(src.into()))
+        PtxKind::Src(src.into()).into()
+    }
+
+    /// The lowered (mangled) name of the kernel that `name_expression` refers to, if it was
+    /// passed to [compile_ptx_with_names()].
+    pub fn lowered_name(&self, name_expression: &str) -> Option<&str> {
+        self.lowered_names.get(name_expression).map(String::as_str)
+    }
+
+    /// Every name expression this was compiled with, along with its lowered name.
+    pub fn lowered_names(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.lowered_names
+            .iter()
+            .map(|(expr, name)| (expr.as_str(), name.as_str()))
+    }
+
+    /// The warnings & remarks nvrtc logged while compiling this. Empty if this wasn't
+    /// compiled with nvrtc.
+    ///
+    /// ```no_run
+    /// # use cudarc::nvrtc::*;
+    /// const SRC: &str = "extern \"C\" __global__ void kernel() { int unused; }";
+    /// let ptx = compile_ptx(SRC).unwrap();
+    /// for diagnostic in ptx.diagnostics() {
+    ///     eprintln!("{}", diagnostic.render(Some(SRC)));
+    /// }
+    /// ```
+    pub fn diagnostics(&self) -> Vec<Diagnostic> {
+        diagnostic::parse_log(&self.log)
+    }
+}
+
+impl From<PtxKind> for Ptx {
+    fn from(kind: PtxKind) -> Self {
+        Self {
+            kind,
+            lowered_names: BTreeMap::new(),
+            log: String::new(),
+        }
     }
 }
 
@@ -34,6 +86,211 @@ impl<S: Into<String>> From<S> for Ptx {
     }
 }
 
+/// A cubin, i.e. machine code (SASS) compiled ahead of time for a single architecture,
+/// e.g. with `nvcc --cubin`. Loading one skips the JIT compilation of [Ptx], but it only
+/// runs on devices of a compatible compute capability.
+///
+/// Create with [Cubin::from_file()] or [Cubin::from_bytes()].
+#[derive(Debug, Clone)]
+pub struct Cubin {
+    pub(crate) kind: BinaryKind,
+    /// The log nvrtc wrote while compiling this, empty if this wasn't compiled with nvrtc.
+    pub(crate) log: String,
+}
+
+impl Cubin {
+    /// Creates a Cubin from a pre-compiled .cubin file.
+    pub fn from_file<P: Into<PathBuf>>(path: P) -> Self {
+        BinaryKind::File(path.into()).into()
+    }
+
+    /// Creates a Cubin from the contents of a pre-compiled .cubin file.
+    pub fn from_bytes<B: Into<Vec<u8>>>(bytes: B) -> Self {
+        BinaryKind::Bytes(bytes.into()).into()
+    }
+
+    /// The warnings & remarks nvrtc logged while compiling this, see [Ptx::diagnostics()].
+    pub fn diagnostics(&self) -> Vec<Diagnostic> {
+        diagnostic::parse_log(&self.log)
+    }
+}
+
+impl From<BinaryKind> for Cubin {
+    fn from(kind: BinaryKind) -> Self {
+        Self {
+            kind,
+            log: String::new(),
+        }
+    }
+}
+
+/// A fatbin, i.e. a bundle of cubins for several architectures and/or ptx, e.g.
+/// from `nvcc --fatbin`. The driver picks the best match for the device it's loaded on,
+/// falling back to JIT compiling the ptx.
+///
+/// Create with [Fatbin::from_file()] or [Fatbin::from_bytes()].
+#[derive(Debug, Clone)]
+pub struct Fatbin(pub(crate) BinaryKind);
+
+impl Fatbin {
+    /// Creates a Fatbin from a pre-compiled .fatbin file.
+    pub fn from_file<P: Into<PathBuf>>(path: P) -> Self {
+        Self(BinaryKind::File(path.into()))
+    }
+
+    /// Creates a Fatbin from the contents of a pre-compiled .fatbin file.
+    pub fn from_bytes<B: Into<Vec<u8>>>(bytes: B) -> Self {
+        Self(BinaryKind::Bytes(bytes.into()))
+    }
+}
+
+impl Ptx {
+    /// The ptx source, e.g. to write it to a file.
+    ///
+    /// Returns an error only if this was created with [Ptx::from_file()] and the file can't be read.
+    pub fn to_src(&self) -> Result<String, std::io::Error> {
+        Ok(match &self.kind {
+            PtxKind::Image(image) => {
+                let src = unsafe { CStr::from_ptr(image.as_ptr()) };
+                src.to_string_lossy().into_owned()
+            }
+            PtxKind::Src(src) => src.clone(),
+            PtxKind::File(path) => std::fs::read_to_string(path)?,
+        })
+    }
+
+    /// The kernels (`.entry` directives) declared in this ptx, along with their parameters.
+    ///
+    /// Returns an error only if this was created with [Ptx::from_file()] and the file can't be read.
+    ///
+    /// Example:
+    /// ```rust
+    /// # use cudarc::nvrtc::*;
+    /// let ptx = compile_ptx("extern \"C\" __global__ void kernel(float *out, int n) { }").unwrap();
+    /// let entries = ptx.entries().unwrap();
+    /// assert_eq!(entries[0].name, "kernel");
+    /// assert_eq!(entries[0].params.as_ref().unwrap().len(), 2);
+    /// ```
+    pub fn entries(&self) -> Result<Vec<PtxEntry>, std::io::Error> {
+        Ok(match &self.kind {
+            PtxKind::Image(image) => {
+                let src = unsafe { CStr::from_ptr(image.as_ptr()) };
+                parse_entries(&src.to_string_lossy())
+            }
+            PtxKind::Src(src) => parse_entries(src),
+            PtxKind::File(path) => parse_entries(&std::fs::read_to_string(path)?),
+        })
+    }
+}
+
+/// A kernel declared in ptx with `.entry`. See [Ptx::entries()].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PtxEntry {
+    pub name: String,
+    /// `None` if any of the params couldn't be parsed
+    pub params: Option<Vec<PtxParam>>,
+}
+
+/// A `.param` of a [PtxEntry], e.g. `.param .u64 out` or `.param .align 8 .b8 s[24]`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PtxParam {
+    pub name: String,
+    /// The ptx type without the leading `.`, e.g. `u64`, `f32` or `b8`
+    pub ty: String,
+    /// The number of elements if this is an array (e.g. a struct passed by value), otherwise `1`
+    pub len: usize,
+    /// The declared alignment in bytes, or the size of `ty` if not specified
+    pub align: usize,
+    /// The size in bytes of the whole parameter
+    pub size: usize,
+}
+
+/// Extracts every `.entry` and its parameters from ptx source. An entry with a parameter
+/// that can't be parsed has no parameters, rather than being an error.
+pub(crate) fn parse_entries(ptx: &str) -> Vec<PtxEntry> {
+    let ptx = strip_ptx_comments(ptx);
+    let mut entries = Vec::new();
+    let mut rest = ptx.as_str();
+    while let Some(i) = rest.find(".entry") {
+        rest = &rest[i + ".entry".len()..];
+        let name_end = rest.find(['(', '{', ';']).unwrap_or(rest.len());
+        let name = rest[..name_end].trim().to_string();
+        rest = &rest[name_end..];
+
+        let mut params = Some(Vec::new());
+        if rest.starts_with('(') {
+            let close = rest.find(')').unwrap_or(rest.len());
+            params = rest[1..close]
+                .split(',')
+                .filter(|param| !param.trim().is_empty())
+                .map(parse_param)
+                .collect();
+            rest = &rest[close..];
+        }
+        entries.push(PtxEntry { name, params });
+    }
+    entries
+}
+
+fn parse_param(param: &str) -> Option<PtxParam> {
+    let mut words = param.split_whitespace();
+    if words.next()? != ".param" {
+        return None;
+    }
+    let mut ty = None;
+    let mut align = None;
+    let mut name = None;
+    while let Some(word) = words.next() {
+        match word {
+            ".align" => align = Some(words.next()?.parse().ok()?),
+            ".ptr" | ".global" | ".const" | ".local" | ".shared" => {}
+            w if w.starts_with('.') => ty = Some(&w[1..]),
+            w => name = Some(w),
+        }
+    }
+    let ty = ty?;
+    let (name, len) = match name?.split_once('[') {
+        Some((name, len)) => (name, len.trim_end_matches(']').parse().ok()?),
+        None => (name?, 1),
+    };
+    let ty_size = ptx_type_size(ty)?;
+    Some(PtxParam {
+        name: name.to_string(),
+        ty: ty.to_string(),
+        len,
+        align: align.unwrap_or(ty_size),
+        size: ty_size * len,
+    })
+}
+
+fn ptx_type_size(ty: &str) -> Option<usize> {
+    Some(match ty {
+        "pred" | "b8" | "u8" | "s8" => 1,
+        "b16" | "u16" | "s16" | "f16" | "bf16" => 2,
+        "b32" | "u32" | "s32" | "f32" | "f16x2" | "bf16x2" => 4,
+        "b64" | "u64" | "s64" | "f64" => 8,
+        "b128" => 16,
+        _ => return None,
+    })
+}
+
+fn strip_ptx_comments(ptx: &str) -> String {
+    let mut out = String::with_capacity(ptx.len());
+    let mut rest = ptx;
+    while !rest.is_empty() {
+        if let Some(r) = rest.strip_prefix("//") {
+            rest = r.find('\n').map_or("", |i| &r[i..]);
+        } else if let Some(r) = rest.strip_prefix("/*") {
+            rest = r.find("*/").map_or("", |i| &r[i + 2..]);
+        } else {
+            let c = rest.chars().next().unwrap();
+            out.push(c);
+            rest = &rest[c.len_utf8()..];
+        }
+    }
+    out
+}
+
 #[derive(Debug, Clone)]
 pub(crate) enum PtxKind {
     /// An image created by [compile_ptx]
@@ -46,6 +303,44 @@ pub(crate) enum PtxKind {
     File(PathBuf),
 }
 
+/// LTO-IR, i.e. an intermediate representation for link time optimization, created with
+/// [compile_lto_ir()].
+///
+/// Load it with [crate::driver::CudaDevice::load_module()], which links it into a cubin with
+/// the driver's JIT LTO first.
+#[cfg(feature = "cuda-12")]
+#[derive(Debug, Clone)]
+pub struct LtoIr {
+    pub(crate) bytes: Vec<u8>,
+    /// The log nvrtc wrote while compiling this, empty if this wasn't compiled with nvrtc.
+    pub(crate) log: String,
+}
+
+#[cfg(feature = "cuda-12")]
+impl LtoIr {
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+
+    /// The warnings & remarks nvrtc logged while compiling this, see [Ptx::diagnostics()].
+    pub fn diagnostics(&self) -> Vec<Diagnostic> {
+        diagnostic::parse_log(&self.log)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) enum BinaryKind {
+    /// Content of a pre compiled file
+    Bytes(Vec<u8>),
+
+    /// Path to a pre compiled file
+    File(PathBuf),
+}
+
 /// Calls [compile_ptx_with_opts] with no options. `src` is the source string
 /// of a `.cu` file.
 ///
@@ -74,8 +369,69 @@ pub fn compile_ptx_with_opts<S: AsRef<str>>(
     src: S,
     opts: CompileOptions,
 ) -> Result<Ptx, CompileError> {
-    let prog = Program::create(src)?;
-    prog.compile(opts)
+    let prog = Program::create(src, &opts.headers)?;
+    prog.compile::<&str>(opts, &[])
+}
+
+/// Compiles `src` with the given `opts`, like [compile_ptx_with_opts()], and also
+/// instantiates each of `name_expressions`, e.g. `"add<float>"` for a templated kernel
+/// `add`. Their lowered names are available from [Ptx::lowered_name()], and
+/// [crate::driver::CudaDevice::load_ptx()] accepts the name expressions as function names.
+///
+/// Example:
+/// ```rust
+/// # use cudarc::nvrtc::*;
+/// let src = "template<typename T> __global__ void add(T *out, T v) { *out += v; }";
+/// let ptx = compile_ptx_with_names(src, Default::default(), &["add<float>", "add<double>"]).unwrap();
+/// assert_ne!(ptx.lowered_name("add<float>"), ptx.lowered_name("add<double>"));
+/// ```
+pub fn compile_ptx_with_names<S: AsRef<str>, N: AsRef<str>>(
+    src: S,
+    opts: CompileOptions,
+    name_expressions: &[N],
+) -> Result<Ptx, CompileError> {
+    let prog = Program::create(src, &opts.headers)?;
+    prog.compile(opts, name_expressions)
+}
+
+/// Compiles `src` to a [Cubin], i.e. machine code that the driver loads without jit
+/// compiling it. `opts.arch` must be a real architecture such as `sm_80`, and the cubin
+/// only runs on devices of that compute capability, or a later minor version of it.
+///
+/// Load it with [crate::driver::CudaDevice::load_module()].
+///
+/// Example:
+/// ```rust
+/// # use cudarc::nvrtc::*;
+/// let opts = CompileOptions {
+///     arch: Some("sm_70".into()),
+///     ..Default::default()
+/// };
+/// let cubin = compile_cubin("extern \"C\" __global__ void kernel() { }", opts).unwrap();
+/// ```
+pub fn compile_cubin<S: AsRef<str>>(src: S, opts: CompileOptions) -> Result<Cubin, CompileError> {
+    let prog = Program::create(src, &opts.headers)?;
+    prog.compile_cubin(opts)
+}
+
+/// Compiles `src` to [LtoIr] (with `-dlto`), for link time optimization with other
+/// compile units. `opts.arch` should be the architecture the result will be linked for,
+/// e.g. `sm_80`.
+///
+/// Example:
+/// ```rust
+/// # use cudarc::nvrtc::*;
+/// let opts = CompileOptions {
+///     arch: Some("sm_80".into()),
+///     ..Default::default()
+/// };
+/// let lto_ir = compile_lto_ir("extern \"C\" __global__ void kernel() { }", opts).unwrap();
+/// assert!(!lto_ir.as_bytes().is_empty());
+/// ```
+#[cfg(feature = "cuda-12")]
+pub fn compile_lto_ir<S: AsRef<str>>(src: S, opts: CompileOptions) -> Result<LtoIr, CompileError> {
+    let prog = Program::create(src, &opts.headers)?;
+    prog.compile_lto_ir(opts)
 }
 
 pub(crate) struct Program {
@@ -83,28 +439,83 @@ pub(crate) struct Program {
 }
 
 impl Program {
-    pub(crate) fn create<S: AsRef<str>>(src: S) -> Result<Self, CompileError> {
-        let prog = result::create_program(src).map_err(CompileError::CreationError)?;
+    pub(crate) fn create<S: AsRef<str>>(
+        src: S,
+        headers: &BTreeMap<String, String>,
+    ) -> Result<Self, CompileError> {
+        #[cfg(feature = "dynamic-loading")]
+        sys::LIBRARY.load().map_err(CompileError::LoadError)?;
+        let headers: Vec<(&String, &String)> = headers.iter().collect();
+        let prog = result::create_program_with_headers(src, &headers)
+            .map_err(CompileError::CreationError)?;
         Ok(Self { prog })
     }
 
-    pub(crate) fn compile(self, opts: CompileOptions) -> Result<Ptx, CompileError> {
-        let options = opts.build();
+    pub(crate) fn compile<N: AsRef<str>>(
+        self,
+        opts: CompileOptions,
+        name_expressions: &[N],
+    ) -> Result<Ptx, CompileError> {
+        let (lowered_names, log) = self.compile_program(opts.build(), name_expressions)?;
+        let image = unsafe { result::get_ptx(self.prog) }.map_err(CompileError::GetPtxError)?;
+        Ok(Ptx {
+            kind: PtxKind::Image(image),
+            lowered_names,
+            log,
+        })
+    }
 
-        unsafe { result::compile_program(self.prog, &options) }.map_err(|e| {
-            let log_raw = unsafe { result::get_program_log(self.prog) }.unwrap();
-            let log_ptr = log_raw.as_ptr();
-            let log = unsafe { CStr::from_ptr(log_ptr) }.to_owned();
-            CompileError::CompileError {
-                nvrtc: e,
-                options,
-                log,
-            }
-        })?;
+    pub(crate) fn compile_cubin(self, opts: CompileOptions) -> Result<Cubin, CompileError> {
+        let (_, log) = self.compile_program::<&str>(opts.build(), &[])?;
+        let cubin = unsafe { result::get_cubin(self.prog) }.map_err(CompileError::GetCubinError)?;
+        Ok(Cubin {
+            kind: BinaryKind::Bytes(cubin),
+            log,
+        })
+    }
 
-        let image = unsafe { result::get_ptx(self.prog) }.map_err(CompileError::GetPtxError)?;
+    #[cfg(feature = "cuda-12")]
+    pub(crate) fn compile_lto_ir(self, opts: CompileOptions) -> Result<LtoIr, CompileError> {
+        let mut options = opts.build();
+        options.push("-dlto".into());
+        let (_, log) = self.compile_program::<&str>(options, &[])?;
+        let bytes =
+            unsafe { result::get_lto_ir(self.prog) }.map_err(CompileError::GetLtoIrError)?;
+        Ok(LtoIr { bytes, log })
+    }
 
-        Ok(Ptx(PtxKind::Image(image)))
+    /// Compiles with `options`, returning the lowered name of each of `name_expressions`,
+    /// and the log.
+    fn compile_program<N: AsRef<str>>(
+        &self,
+        options: Vec<String>,
+        name_expressions: &[N],
+    ) -> Result<(BTreeMap<String, String>, String), CompileError> {
+        for expr in name_expressions {
+            unsafe { result::add_name_expression(self.prog, expr.as_ref()) }
+                .map_err(CompileError::AddNameExpressionError)?;
+        }
+
+        let compiled = unsafe { result::compile_program(self.prog, &options) };
+        let log_raw =
+            unsafe { result::get_program_log(self.prog) }.map_err(CompileError::GetLogError)?;
+        let log_ptr = log_raw.as_ptr();
+        let log = unsafe { CStr::from_ptr(log_ptr) }.to_owned();
+        if let Err(nvrtc) = compiled {
+            return Err(CompileError::CompileError {
+                nvrtc,
+                options,
+                log,
+            });
+        }
+        let mut lowered_names = BTreeMap::new();
+        for expr in name_expressions {
+            let expr = expr.as_ref();
+            let name = unsafe { result::get_lowered_name(self.prog, expr) }
+                .map_err(CompileError::GetLoweredNameError)?;
+            lowered_names.insert(expr.to_string(), name.to_string_lossy().into_owned());
+        }
+        Ok((lowered_names, log.to_string_lossy().into_owned()))
     }
 }
 
@@ -112,14 +523,139 @@ impl Drop for Program {
     fn drop(&mut self) {
         let prog = std::mem::replace(&mut self.prog, std::ptr::null_mut());
         if !prog.is_null() {
-            unsafe { result::destroy_program(prog) }.unwrap()
+            if let Err(e) = unsafe { result::destroy_program(prog) } {
+                #[cfg(feature = "driver")]
+                crate::driver::safe::error::defer_error(None, e);
+                #[cfg(not(feature = "driver"))]
+                let _ = e;
+            }
         }
     }
 }
 
+/// A `--gpu-architecture` to compile for. See [TargetArch::select()] for choosing one
+/// that runs on a given device.
+///
+/// Example:
+/// ```rust
+/// # use cudarc::nvrtc::*;
+/// let opts = CompileOptions {
+///     arch: Some(TargetArch::Real(86).to_string()),
+///     ..Default::default()
+/// };
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TargetArch {
+    /// `sm_XX`, machine code that can be compiled to a [Cubin], and runs on devices of the
+    /// same major compute capability, with an equal or greater minor version.
+    Real(i32),
+
+    /// `compute_XX`, ptx that the driver jit compiles for devices of an equal or greater
+    /// compute capability.
+    Virtual(i32),
+}
+
+impl TargetArch {
+    /// Selects the best arch for a device of `compute_capability` (major, minor) among
+    /// `supported_archs` (see [supported_archs()]):
+    /// 1. The real arch of the device, or the newest real arch it is binary compatible with
+    /// 2. Otherwise the newest virtual arch that isn't newer than the device
+    ///
+    /// Returns `None` if every supported arch is newer than the device.
+    ///
+    /// Example:
+    /// ```rust
+    /// # use cudarc::nvrtc::*;
+    /// let supported = [52, 60, 70, 75, 80];
+    /// assert_eq!(TargetArch::select((8, 6), &supported), Some(TargetArch::Real(80)));
+    /// assert_eq!(TargetArch::select((9, 0), &supported), Some(TargetArch::Virtual(80)));
+    /// assert_eq!(TargetArch::select((3, 5), &supported), None);
+    /// ```
+    pub fn select(compute_capability: (i32, i32), supported_archs: &[i32]) -> Option<Self> {
+        let (major, minor) = compute_capability;
+        let device = major * 10 + minor;
+        let newest = |compatible: &dyn Fn(i32) -> bool| {
+            supported_archs
+                .iter()
+                .copied()
+                .filter(|&arch| compatible(arch))
+                .max()
+        };
+        newest(&|arch| arch / 10 == major && arch <= device)
+            .map(Self::Real)
+            .or_else(|| newest(&|arch| arch <= device).map(Self::Virtual))
+    }
+
+    /// Like [TargetArch::select()], but only returns an arch whose output a driver that
+    /// supports cuda `driver_version` (major, minor) can load, when compiled by nvrtc
+    /// `nvrtc_version` (see [version()]):
+    /// - If the driver is older than nvrtc, it can't jit the ptx nvrtc emits (its ISA version is
+    ///   too new), so only a real arch is returned. Cubins still load, as long as the driver
+    ///   has the same major version.
+    /// - If the driver has an older major version than nvrtc, nothing is returned.
+    ///
+    /// Example:
+    /// ```rust
+    /// # use cudarc::nvrtc::*;
+    /// let supported = [52, 60, 70, 75, 80];
+    /// assert_eq!(TargetArch::select_for_driver((9, 0), &supported, (12, 4), (12, 4)), Some(TargetArch::Virtual(80)));
+    /// assert_eq!(TargetArch::select_for_driver((8, 6), &supported, (12, 4), (12, 2)), Some(TargetArch::Real(80)));
+    /// assert_eq!(TargetArch::select_for_driver((9, 0), &supported, (12, 4), (12, 2)), None);
+    /// ```
+    pub fn select_for_driver(
+        compute_capability: (i32, i32),
+        supported_archs: &[i32],
+        nvrtc_version: (i32, i32),
+        driver_version: (i32, i32),
+    ) -> Option<Self> {
+        if driver_version.0 < nvrtc_version.0 {
+            return None;
+        }
+        match Self::select(compute_capability, supported_archs)? {
+            Self::Virtual(_) if driver_version < nvrtc_version => None,
+            arch => Some(arch),
+        }
+    }
+
+    /// The compute capability as `major * 10 + minor`, e.g. `86`.
+    pub fn version(&self) -> i32 {
+        match self {
+            Self::Real(v) | Self::Virtual(v) => *v,
+        }
+    }
+}
+
+impl core::fmt::Display for TargetArch {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Real(v) => write!(f, "sm_{v}"),
+            Self::Virtual(v) => write!(f, "compute_{v}"),
+        }
+    }
+}
+
+/// The (major, minor) version of the installed nvrtc. See [result::version()].
+pub fn version() -> Result<(i32, i32), CompileError> {
+    #[cfg(feature = "dynamic-loading")]
+    sys::LIBRARY.load().map_err(CompileError::LoadError)?;
+    result::version().map_err(CompileError::GetVersionError)
+}
+
+/// The architectures the installed nvrtc can compile for, as `major * 10 + minor`.
+/// See [result::get_supported_archs()].
+pub fn supported_archs() -> Result<Vec<i32>, CompileError> {
+    #[cfg(feature = "dynamic-loading")]
+    sys::LIBRARY.load().map_err(CompileError::LoadError)?;
+    result::get_supported_archs().map_err(CompileError::GetSupportedArchsError)
+}
+
 /// Represents an error that happens during nvrtc compilation.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum CompileError {
+    /// The nvrtc library could not be loaded
+    #[cfg(feature = "dynamic-loading")]
+    LoadError(crate::dynamic_loading::LoadError),
+
     /// Error happened during [result::create_program()]
     CreationError(result::NvrtcError),
 
@@ -136,14 +672,58 @@ pub enum CompileError {
     /// Error happened during [result::get_ptx()]
     GetPtxError(result::NvrtcError),
 
+    /// Error happened during [result::get_cubin()]
+    GetCubinError(result::NvrtcError),
+
+    /// Error happened during [result::get_lto_ir()]
+    #[cfg(feature = "cuda-12")]
+    GetLtoIrError(result::NvrtcError),
+
+    /// Error happened during [result::add_name_expression()]
+    AddNameExpressionError(result::NvrtcError),
+
+    /// Error happened during [result::get_lowered_name()]
+    GetLoweredNameError(result::NvrtcError),
+
     /// Error happened during [result::destroy_program()]
     DestroyError(result::NvrtcError),
+
+    /// Error happened during [result::version()]
+    GetVersionError(result::NvrtcError),
+
+    /// Error happened during [result::get_supported_archs()]
+    GetSupportedArchsError(result::NvrtcError),
+}
+
+impl CompileError {
+    /// The errors & warnings in the log of a [CompileError::CompileError], empty for
+    /// any other error. Use [Diagnostic::render()] to show them with the source line they
+    /// point at.
+    pub fn diagnostics(&self) -> Vec<Diagnostic> {
+        match self {
+            Self::CompileError { log, .. } => diagnostic::parse_log(&log.to_string_lossy()),
+            _ => Vec::new(),
+        }
+    }
 }
 
 #[cfg(feature = "std")]
 impl std::fmt::Display for CompileError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{self:?}")
+        match self {
+            Self::CompileError { nvrtc, log, .. } => {
+                let diagnostics = self.diagnostics();
+                if diagnostics.is_empty() {
+                    return write!(f, "{nvrtc:?}: {}", log.to_string_lossy());
+                }
+                writeln!(f, "{nvrtc:?}")?;
+                for diagnostic in diagnostics {
+                    write!(f, "\n{diagnostic}")?;
+                }
+                Ok(())
+            }
+            _ => write!(f, "{self:?}"),
+        }
     }
 }
 
@@ -154,12 +734,12 @@ impl std::error::Error for CompileError {}
 /// See <https://docs.nvidia.com/cuda/nvrtc/index.html#group__options>
 /// for all available flags and documentation for what they do.
 ///
-/// All fields of this struct match one of the flags in the documentation.
-/// if a field is `None` it will not be passed to the compiler.
+/// All fields of this struct match one of the flags in the documentation, except for
+/// `headers`. if a field is `None` (or empty) it will not be passed to the compiler.
 ///
-/// All fields default to `None`.
+/// All fields default to `None` (or empty).
 ///
-/// *NOTE*: not all flags are currently supported.
+/// *NOTE*: not all flags have a field, pass any others with `extra_flags`.
 ///
 /// Example:
 /// ```rust
@@ -179,10 +759,42 @@ pub struct CompileOptions {
     pub use_fast_math: Option<bool>,
     pub maxrregcount: Option<usize>,
     pub include_paths: Vec<String>,
-    pub arch: Option<&'static str>,
+    /// e.g. `sm_80` or `compute_80`. See [TargetArch] for picking one that matches a device.
+    pub arch: Option<String>,
+    /// Macros to define, each either `NAME` or `NAME=VALUE`
+    pub defines: Vec<String>,
+    /// The c++ dialect, e.g. `c++17` or `c++20`
+    pub std: Option<String>,
+    /// `--device-debug`, i.e. `-G`
+    pub device_debug: Option<bool>,
+    /// `--generate-line-info`, i.e. `-lineinfo`
+    pub lineinfo: Option<bool>,
+    pub extra_device_vectorization: Option<bool>,
+    pub relocatable_device_code: Option<bool>,
+    /// `--device-as-default-execution-space`, i.e. `-default-device`
+    pub default_device: Option<bool>,
+    /// Headers to include at the start of the source
+    pub pre_include: Vec<String>,
+    /// Passed to the compiler as is, after all other flags
+    pub extra_flags: Vec<String>,
+    /// Headers that the source can `#include` by name, mapped to their contents. These
+    /// are found before any file in `include_paths`, so they don't need to exist on disk.
+    ///
+    /// ```rust
+    /// # use cudarc::nvrtc::*;
+    /// let opts = CompileOptions {
+    ///     headers: [("common.cuh".into(), "#define SCALE 2.0f".into())].into(),
+    ///     ..Default::default()
+    /// };
+    /// let src = "#include \"common.cuh\"
+    /// extern \"C\" __global__ void kernel(float *out) { *out *= SCALE; }";
+    /// let ptx = compile_ptx_with_opts(src, opts).unwrap();
+    /// ```
+    pub headers: BTreeMap<String, String>,
 }
 
 impl CompileOptions {
+    /// The flags to pass to nvrtc. `headers` are passed when creating the program instead.
     pub(crate) fn build(self) -> Vec<String> {
         let mut options: Vec<String> = Vec::new();
 
@@ -203,7 +815,7 @@ impl CompileOptions {
         }
 
         if let Some(true) = self.use_fast_math {
-            options.push("--fmad=true".into());
+            options.push("--use_fast_math".into());
         }
 
         if let Some(count) = self.maxrregcount {
@@ -218,6 +830,40 @@ impl CompileOptions {
             options.push(std::format!("--gpu-architecture={arch}"))
         }
 
+        for define in self.defines {
+            options.push(std::format!("--define-macro={define}"));
+        }
+
+        if let Some(std) = self.std {
+            options.push(std::format!("--std={std}"));
+        }
+
+        if let Some(true) = self.device_debug {
+            options.push("--device-debug".into());
+        }
+
+        if let Some(true) = self.lineinfo {
+            options.push("--generate-line-info".into());
+        }
+
+        if let Some(true) = self.extra_device_vectorization {
+            options.push("--extra-device-vectorization".into());
+        }
+
+        if let Some(v) = self.relocatable_device_code {
+            options.push(std::format!("--relocatable-device-code={v}"));
+        }
+
+        if let Some(true) = self.default_device {
+            options.push("--device-as-default-execution-space".into());
+        }
+
+        for header in self.pre_include {
+            options.push(std::format!("--pre-include={header}"));
+        }
+
+        options.extend(self.extra_flags);
+
         options
     }
 }
@@ -225,6 +871,7 @@ impl CompileOptions {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::nvrtc::Severity;
 
     #[test]
     fn test_compile_no_opts() {
@@ -238,6 +885,182 @@ mod tests {
         compile_ptx_with_opts(SRC, Default::default()).unwrap();
     }
 
+    #[test]
+    fn test_compile_diagnostics() {
+        const SRC: &str = "extern \"C\" __global__ void kernel(float *out) {
+    int unused;
+    *out = 1.0f;
+}";
+        let ptx = compile_ptx(SRC).unwrap();
+        let diagnostics = ptx.diagnostics();
+        let warning = &diagnostics[0];
+        assert_eq!(warning.severity, Severity::Warning);
+        assert_eq!(warning.line, 2);
+
+        let err = compile_ptx(SRC.replace("1.0f", "y")).unwrap_err();
+        let diagnostics = err.diagnostics();
+        let error = diagnostics
+            .iter()
+            .find(|d| d.severity == Severity::Error)
+            .unwrap();
+        assert_eq!(error.line, 3);
+        assert!(error.message.contains("\"y\""));
+    }
+
+    #[test]
+    fn test_compile_with_headers() {
+        const SRC: &str = "#include \"scale.cuh\"
+extern \"C\" __global__ void kernel(float *out) { *out = scale(*out); }";
+        assert!(matches!(
+            compile_ptx(SRC),
+            Err(CompileError::CompileError { .. })
+        ));
+        let opts = CompileOptions {
+            headers: [(
+                "scale.cuh".into(),
+                "__device__ float scale(float x) { return 2.0f * x; }".into(),
+            )]
+            .into(),
+            ..Default::default()
+        };
+        compile_ptx_with_opts(SRC, opts).unwrap();
+    }
+
+    #[test]
+    fn test_compile_cubin() {
+        const SRC: &str = "extern \"C\" __global__ void kernel(float *out) { *out = 1.0f; }";
+        let opts = CompileOptions {
+            arch: Some("sm_70".into()),
+            ..Default::default()
+        };
+        let cubin = compile_cubin(SRC, opts).unwrap();
+        match cubin.kind {
+            BinaryKind::Bytes(bytes) => assert!(bytes.starts_with(b"\x7fELF")),
+            BinaryKind::File(_) => unreachable!(),
+        }
+
+        // a virtual architecture has no cubin
+        let opts = CompileOptions {
+            arch: Some("compute_70".into()),
+            ..Default::default()
+        };
+        assert!(matches!(
+            compile_cubin(SRC, opts),
+            Err(CompileError::GetCubinError(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_entries() {
+        const PTX: &str = "
+//
+// Generated by NVIDIA NVVM Compiler
+//
+.version 7.8
+.target sm_52
+.address_size 64
+
+	// .globl	sin_kernel
+
+.visible .entry sin_kernel(
+	.param .u64 sin_kernel_param_0,
+	.param .u64 .ptr .global .align 4 sin_kernel_param_1,
+	.param .f32 sin_kernel_param_2,
+	.param .align 8 .b8 sin_kernel_param_3[24]
+)
+{
+	ret;
+}
+
+.visible .entry no_params()
+{
+	ret;
+}
+
+.visible .entry unsized_param(
+	.param .u64 unsized_param_param_0,
+	.param .align 8 .b8 unsized_param_param_1[]
+)
+{
+	ret;
+}
+";
+        let entries = parse_entries(PTX);
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].name, "sin_kernel");
+        let params = entries[0].params.as_ref().unwrap();
+        let sizes: Vec<_> = params.iter().map(|p| p.size).collect();
+        assert_eq!(sizes, [8, 8, 4, 24]);
+        assert_eq!(
+            params[3],
+            PtxParam {
+                name: "sin_kernel_param_3".into(),
+                ty: "b8".into(),
+                len: 24,
+                align: 8,
+                size: 24,
+            }
+        );
+        assert_eq!(entries[1].name, "no_params");
+        assert_eq!(entries[1].params, Some(Vec::new()));
+        assert_eq!(entries[2].name, "unsized_param");
+        assert_eq!(entries[2].params, None);
+    }
+
+    #[test]
+    fn test_select_arch() {
+        let supported = [35, 50, 52, 60, 61, 70, 75, 80, 86, 90];
+        assert_eq!(
+            TargetArch::select((8, 6), &supported),
+            Some(TargetArch::Real(86))
+        );
+        assert_eq!(
+            TargetArch::select((8, 9), &supported),
+            Some(TargetArch::Real(86))
+        );
+        assert_eq!(
+            TargetArch::select((6, 2), &supported),
+            Some(TargetArch::Real(61))
+        );
+        // nvrtc is older than the device
+        assert_eq!(
+            TargetArch::select((10, 0), &supported),
+            Some(TargetArch::Virtual(90))
+        );
+        assert_eq!(
+            TargetArch::select((7, 2), &[52, 60, 75]),
+            Some(TargetArch::Virtual(60))
+        );
+        assert_eq!(TargetArch::select((3, 0), &supported), None);
+        assert_eq!(TargetArch::Real(86).to_string(), "sm_86");
+        assert_eq!(TargetArch::Virtual(90).to_string(), "compute_90");
+    }
+
+    #[test]
+    fn test_select_arch_for_driver() {
+        let supported = [50, 52, 60, 61, 70, 75, 80, 86, 90];
+        let select =
+            |cc, nvrtc, driver| TargetArch::select_for_driver(cc, &supported, nvrtc, driver);
+        // the driver is as new as nvrtc, or newer
+        assert_eq!(select((8, 6), (12, 2), (12, 2)), Some(TargetArch::Real(86)));
+        assert_eq!(
+            select((10, 0), (12, 2), (12, 4)),
+            Some(TargetArch::Virtual(90))
+        );
+        assert_eq!(
+            select((10, 0), (12, 2), (13, 0)),
+            Some(TargetArch::Virtual(90))
+        );
+        // the driver is older than nvrtc: cubins load, ptx doesn't
+        assert_eq!(select((8, 6), (12, 4), (12, 2)), Some(TargetArch::Real(86)));
+        assert_eq!(select((8, 9), (12, 4), (12, 0)), Some(TargetArch::Real(86)));
+        assert_eq!(select((10, 0), (12, 4), (12, 2)), None);
+        // the driver has an older major version than nvrtc
+        assert_eq!(select((8, 6), (12, 0), (11, 8)), None);
+        // unsupported devices stay unsupported
+        assert_eq!(select((3, 0), (12, 0), (12, 0)), None);
+    }
+
     #[test]
     fn test_compile_options_build_none() {
         let opts: CompileOptions = Default::default();
@@ -262,4 +1085,78 @@ mod tests {
         };
         assert_eq!(&opts.build(), &["--prec-div=false", "--maxrregcount=60"]);
     }
+
+    #[test]
+    fn test_compile_options_build_fast_math() {
+        let opts = CompileOptions {
+            use_fast_math: Some(true),
+            ..Default::default()
+        };
+        assert_eq!(&opts.build(), &["--use_fast_math"]);
+
+        let opts = CompileOptions {
+            use_fast_math: Some(false),
+            ..Default::default()
+        };
+        assert!(opts.build().is_empty());
+    }
+
+    #[test]
+    fn test_compile_options_build_defines() {
+        let opts = CompileOptions {
+            defines: std::vec!["DEBUG".into(), "BLOCK_SIZE=256".into()],
+            std: Some("c++17".into()),
+            ..Default::default()
+        };
+        assert_eq!(
+            &opts.build(),
+            &[
+                "--define-macro=DEBUG",
+                "--define-macro=BLOCK_SIZE=256",
+                "--std=c++17"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_compile_options_build_debug() {
+        let opts = CompileOptions {
+            device_debug: Some(true),
+            lineinfo: Some(true),
+            extra_device_vectorization: Some(false),
+            relocatable_device_code: Some(true),
+            default_device: Some(true),
+            ..Default::default()
+        };
+        assert_eq!(
+            &opts.build(),
+            &[
+                "--device-debug",
+                "--generate-line-info",
+                "--relocatable-device-code=true",
+                "--device-as-default-execution-space"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_compile_options_build_includes_and_extra_flags() {
+        let opts = CompileOptions {
+            include_paths: std::vec!["/usr/include".into()],
+            pre_include: std::vec!["common.h".into()],
+            extra_flags: std::vec!["--dopt=on".into(), "-w".into()],
+            arch: Some("sm_80".into()),
+            ..Default::default()
+        };
+        assert_eq!(
+            &opts.build(),
+            &[
+                "--include-path=/usr/include",
+                "--gpu-architecture=sm_80",
+                "--pre-include=common.h",
+                "--dopt=on",
+                "-w"
+            ]
+        );
+    }
 }
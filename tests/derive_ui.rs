@@ -0,0 +1,10 @@
+//! Checks that `#[derive(DeviceRepr)]` rejects structs whose layout cuda can't rely on.
+//!
+//! Set `TRYBUILD=overwrite` to regenerate the expected `.stderr` files.
+
+#[cfg(feature = "derive")]
+#[test]
+fn derive_ui() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/*.rs");
+}
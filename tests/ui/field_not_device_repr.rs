@@ -0,0 +1,13 @@
+use cudarc::driver::DeviceRepr;
+
+#[derive(Clone, Copy)]
+struct NotDeviceRepr(u8);
+
+#[derive(Clone, Copy, DeviceRepr)]
+#[repr(C)]
+struct Particle {
+    x: f32,
+    tag: NotDeviceRepr,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use cudarc::driver::DeviceRepr;
+
+#[derive(Clone, Copy, DeviceRepr)]
+struct Particle {
+    x: f32,
+    y: f32,
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+use cudarc::driver::DeviceRepr;
+
+#[derive(Clone, Copy, DeviceRepr)]
+#[repr(C, packed)]
+struct Particle {
+    x: f32,
+    mass: f64,
+}
+
+fn main() {}